@@ -0,0 +1,40 @@
+// Cost of one process() call of a single instance at increasing numbers of
+// fixed steps per tick: cargo bench --bench process
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hindmarsh_rose_v2_rust::rtsyn_plugin_api;
+
+const PERIOD: f64 = 0.001;
+// Steps per tick, set through dt with calibration off.
+const STEPS: [usize; 4] = [10, 100, 1000, 10000];
+
+fn bench_process(c: &mut Criterion) {
+    let api = unsafe { &*rtsyn_plugin_api() };
+    for integrator in ["euler", "rk4", "rk6"] {
+        let mut group = c.benchmark_group(format!("process_{integrator}"));
+        for steps in STEPS {
+            group.throughput(Throughput::Elements(steps as u64));
+            let config = serde_json::json!({
+                "integrator": integrator,
+                "burst_duration": 0.0,
+                "period_seconds": PERIOD,
+                "dt": PERIOD / steps as f64
+            })
+            .to_string();
+            let handle = (api.create)(0);
+            (api.set_config_json)(handle, config.as_ptr(), config.len());
+            let mut tick = 0;
+            group.bench_with_input(BenchmarkId::from_parameter(steps), &steps, |b, _| {
+                b.iter(|| {
+                    (api.process)(handle, tick, PERIOD);
+                    tick += 1;
+                })
+            });
+            (api.destroy)(handle);
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_process);
+criterion_main!(benches);
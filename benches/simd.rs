@@ -0,0 +1,43 @@
+// Scalar, packed and four-lane RK stepping of a population of cells, one
+// step per iteration: cargo bench --features simd --bench simd
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hindmarsh_rose_v2_rust::simd::CellGroup;
+
+const DT: f64 = 0.01;
+const POPULATIONS: [usize; 4] = [1, 4, 64, 1024];
+
+fn population(cells: usize, integrator: &str) -> CellGroup {
+    let mut population = CellGroup::new(cells);
+    population.set_integrator(integrator);
+    // Spread the cells over the bursting range so no lane idles.
+    for cell in 0..cells {
+        population.set_input(cell, 0.5 * cell as f64 / cells as f64);
+    }
+    population
+}
+
+fn bench_layouts(c: &mut Criterion) {
+    for integrator in ["rk4", "rk6"] {
+        let mut group = c.benchmark_group(format!("step_{integrator}"));
+        for cells in POPULATIONS {
+            group.throughput(Throughput::Elements(cells as u64));
+            let mut scalar = population(cells, integrator);
+            group.bench_with_input(BenchmarkId::new("scalar", cells), &cells, |b, _| {
+                b.iter(|| scalar.step_scalar(black_box(DT)))
+            });
+            let mut packed = population(cells, integrator);
+            group.bench_with_input(BenchmarkId::new("packed", cells), &cells, |b, _| {
+                b.iter(|| packed.step_packed(black_box(DT)))
+            });
+            let mut lanes = population(cells, integrator);
+            group.bench_with_input(BenchmarkId::new("lanes", cells), &cells, |b, _| {
+                b.iter(|| lanes.step(black_box(DT)))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_layouts);
+criterion_main!(benches);
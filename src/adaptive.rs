@@ -0,0 +1,217 @@
+// Error-controlled stepping. Each tick still advances the model by exactly
+// its calibrated span (dt * s_points): the internal step h adapts to keep
+// the scaled local error below 1 and the last step of a tick is shortened to
+// land on the tick boundary. The step size carries over between ticks.
+// Rk45 estimates the error with its embedded Dormand-Prince 5(4) pair; every
+// other integrator with step doubling, comparing one step of h against two
+// of h/2.
+//
+// The step size follows a PI controller on the scaled error norm
+// (Hairer & Wanner's gains, with the integral exponent scaled to the order
+// of the estimate): the integral term reacts to the current error, the
+// proportional term to its change since the last accepted step, which damps
+// the step-size oscillation a pure I controller shows around spikes. A step
+// after a rejection may not grow.
+
+use crate::math;
+use crate::rk;
+use crate::state::ControllerSnapshot;
+
+// Bounds on the step size change factor per attempt.
+const MIN_FACTOR: f64 = 0.2;
+const MAX_FACTOR: f64 = 5.0;
+const SAFETY: f64 = 0.9;
+const BETA: f64 = 0.04;
+// Floor on the remembered error so an exact step cannot blow up the
+// proportional term.
+const MIN_ERROR: f64 = 1e-4;
+
+// How calibrated runs (burst_duration > 0) cover a tick. Pi, the default,
+// maps the burst duration onto an exact model-time span per tick and covers
+// it with error-controlled steps of the configured integrator. Table is the
+// legacy RTXI dt lookup with fixed steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepControl {
+    Pi,
+    Table,
+}
+
+impl StepControl {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "pi" => Some(Self::Pi),
+            "table" | "legacy" => Some(Self::Table),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pi => "pi",
+            Self::Table => "table",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Adaptive {
+    pub abs_tol: f64,
+    pub rel_tol: f64,
+    h: f64,
+    accepted: u64,
+    rejected: u64,
+    tick_rejected: u64,
+    error_sum: f64,
+    previous_error: f64,
+    after_rejection: bool,
+}
+
+// Outcome of one attempted step.
+pub enum Attempt<const N: usize> {
+    Accepted { state: [f64; N], h: f64 },
+    Rejected,
+}
+
+impl Adaptive {
+    pub fn new() -> Self {
+        Self {
+            abs_tol: 1e-6,
+            rel_tol: 1e-6,
+            h: 0.0,
+            accepted: 0,
+            rejected: 0,
+            tick_rejected: 0,
+            error_sum: 0.0,
+            previous_error: MIN_ERROR,
+            after_rejection: false,
+        }
+    }
+
+    pub fn begin_tick(&mut self, initial_h: f64) {
+        if self.h <= 0.0 {
+            self.h = initial_h;
+        }
+        self.tick_rejected = 0;
+    }
+
+    pub fn step_size(&self) -> f64 {
+        self.h
+    }
+
+    pub fn snapshot(&self) -> ControllerSnapshot {
+        ControllerSnapshot {
+            step_size: self.h,
+            previous_error: self.previous_error,
+            after_rejection: self.after_rejection,
+        }
+    }
+
+    // A zero step size, as in blobs from before the controller was saved,
+    // lets the next tick pick its initial step again.
+    pub fn restore(&mut self, snapshot: &ControllerSnapshot) {
+        self.h = snapshot.step_size;
+        self.previous_error = snapshot.previous_error.max(MIN_ERROR);
+        self.after_rejection = snapshot.after_rejection;
+    }
+
+    pub fn accepted(&self) -> u64 {
+        self.accepted
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    pub fn tick_rejected(&self) -> u64 {
+        self.tick_rejected
+    }
+
+    // Mean scaled error norm of accepted steps (1 is the tolerance); forced
+    // steps can push it above 1.
+    pub fn mean_error(&self) -> f64 {
+        if self.accepted == 0 {
+            0.0
+        } else {
+            self.error_sum / self.accepted as f64
+        }
+    }
+
+    // Attempts one step of at most `remaining`. `estimate` takes a step of
+    // the given size and returns the new state with its local error, of
+    // order `order + 1` in h. With `force` set the step is accepted whatever
+    // its error, used once the per-tick budget is spent.
+    pub fn attempt<const N: usize, E>(
+        &mut self,
+        y: [f64; N],
+        remaining: f64,
+        force: bool,
+        order: u32,
+        mut estimate: E,
+    ) -> Attempt<N>
+    where
+        E: FnMut(f64) -> ([f64; N], [f64; N]),
+    {
+        let h = if force { remaining } else { self.h.min(remaining) };
+        let (next, error) = estimate(h);
+        let norm = (error
+            .iter()
+            .zip(y.iter().zip(next.iter()))
+            .map(|(e, (a, b))| {
+                let scale = self.abs_tol + self.rel_tol * a.abs().max(b.abs());
+                math::powi(e / scale, 2)
+            })
+            .sum::<f64>()
+            / N as f64)
+            .sqrt();
+
+        let factor = if !norm.is_finite() {
+            MIN_FACTOR
+        } else {
+            let norm = norm.max(f64::MIN_POSITIVE);
+            let alpha = 1.0 / f64::from(order + 1) - 0.75 * BETA;
+            (SAFETY * math::powf(norm, -alpha) * math::powf(self.previous_error, BETA)).clamp(MIN_FACTOR, MAX_FACTOR)
+        };
+        if norm <= 1.0 || force {
+            self.accepted += 1;
+            self.error_sum += norm;
+            self.previous_error = norm.max(MIN_ERROR);
+            let factor = if self.after_rejection { factor.min(1.0) } else { factor };
+            self.after_rejection = false;
+            // A step shortened to hit the tick boundary says little about
+            // the step size the dynamics allow, so keep the larger one.
+            self.h = if h < self.h { self.h.max(h * factor) } else { h * factor };
+            return Attempt::Accepted { state: next, h };
+        }
+        self.rejected += 1;
+        self.tick_rejected += 1;
+        self.after_rejection = true;
+        self.h = h * factor.min(1.0);
+        Attempt::Rejected
+    }
+}
+
+// The embedded Dormand-Prince estimate; its error is of order 5.
+pub fn embedded<const N: usize, F>(y: [f64; N], h: f64, f: F) -> ([f64; N], [f64; N])
+where
+    F: FnMut(&[f64; N]) -> [f64; N],
+{
+    rk::integrate_embedded(&rk::DOPRI5, &rk::DOPRI5_ERROR, y, h, f)
+}
+
+// Step doubling for a one-step method of the given order: the two half
+// steps are kept, and their difference to the full step, scaled by
+// Richardson's 2^order - 1, estimates their error.
+pub fn doubled<const N: usize, S>(y: [f64; N], h: f64, order: u32, mut step: S) -> ([f64; N], [f64; N])
+where
+    S: FnMut([f64; N], f64) -> [f64; N],
+{
+    let full = step(y, h);
+    let midpoint = step(y, 0.5 * h);
+    let half = step(midpoint, 0.5 * h);
+    let scale = f64::from((1u32 << order) - 1);
+    let mut error = [0.0; N];
+    for j in 0..N {
+        error[j] = (half[j] - full[j]) / scale;
+    }
+    (half, error)
+}
@@ -0,0 +1,68 @@
+// Slow automatic gain control matching the model's membrane variable to a
+// living recording: exponentially weighted mean and variance of both signals
+// (time constant `tau` seconds) give gain = sd_live / sd_model and
+// offset = mean_live - gain * mean_model.
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RunningStats {
+    mean: f64,
+    var: f64,
+    primed: bool,
+}
+
+impl RunningStats {
+    fn update(&mut self, value: f64, alpha: f64) {
+        if !self.primed {
+            self.mean = value;
+            self.var = 0.0;
+            self.primed = true;
+            return;
+        }
+        let delta = value - self.mean;
+        self.mean += alpha * delta;
+        self.var = (1.0 - alpha) * (self.var + alpha * delta * delta);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Agc {
+    pub tau: f64,
+    live: RunningStats,
+    model: RunningStats,
+}
+
+impl Agc {
+    pub fn enabled(&self) -> bool {
+        self.tau > 0.0
+    }
+
+    pub fn update(&mut self, live: f64, model: f64, period: f64) {
+        if !self.enabled() {
+            return;
+        }
+        let alpha = (period / self.tau).min(1.0);
+        self.live.update(live, alpha);
+        self.model.update(model, alpha);
+    }
+
+    pub fn gain(&self) -> f64 {
+        if self.model.var > f64::EPSILON {
+            (self.live.var / self.model.var).sqrt()
+        } else {
+            1.0
+        }
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.live.mean - self.gain() * self.model.mean
+    }
+
+    pub fn scale(&self, model: f64) -> f64 {
+        self.gain() * model + self.offset()
+    }
+
+    pub fn reset(&mut self) {
+        self.live = RunningStats::default();
+        self.model = RunningStats::default();
+    }
+}
@@ -0,0 +1,220 @@
+// Periodic-spline stand-in for the integrator. The autonomous limit cycle is
+// sampled once per configuration on a worker thread, since a fit integrates
+// thousands of model time units; at runtime the state is read back from a
+// cubic interpolant indexed by phase, plus a static first-order correction
+// for the injected current. Costs a handful of flops per tick.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::OnceLock;
+
+const TRANSIENT: f64 = 3000.0;
+const SEARCH_WINDOW: f64 = 3000.0;
+const RECURRENCE_TOL: f64 = 1e-2;
+const SENSITIVITY_DELTA: f64 = 1e-3;
+
+#[derive(Debug, Clone)]
+pub struct LimitCycle {
+    samples: Vec<[f64; 3]>,
+    period: f64,
+    // d<x>/de over one cycle; i_syn enters the x equation as -e does.
+    input_gain: f64,
+}
+
+struct Cycle {
+    start: [f64; 3],
+    period: f64,
+}
+
+// Integrates past the transient and returns a point on the cycle together
+// with its period, found as the first return of an upward threshold crossing
+// to within RECURRENCE_TOL of the reference crossing.
+fn find_cycle<F>(start: [f64; 3], dt: f64, threshold: f64, bias: f64, step: &F) -> Option<Cycle>
+where
+    F: Fn([f64; 3], f64, f64) -> [f64; 3],
+{
+    let mut state = start;
+    let mut t = 0.0;
+    while t < TRANSIENT {
+        state = step(state, dt, bias);
+        t += dt;
+    }
+
+    let mut crossings: Vec<(f64, [f64; 3])> = Vec::new();
+    t = 0.0;
+    while t < SEARCH_WINDOW {
+        let next = step(state, dt, bias);
+        t += dt;
+        if !next.iter().all(|v| v.is_finite()) {
+            return None;
+        }
+        if state[0] < threshold && next[0] >= threshold {
+            crossings.push((t, next));
+        }
+        state = next;
+    }
+
+    let (t0, reference) = *crossings.first()?;
+    let distance = |s: &[f64; 3]| {
+        s.iter()
+            .zip(reference.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt()
+    };
+    let (t1, _) = crossings[1..]
+        .iter()
+        .find(|(_, s)| distance(s) < RECURRENCE_TOL)
+        .or_else(|| {
+            crossings[1..]
+                .iter()
+                .min_by(|a, b| distance(&a.1).total_cmp(&distance(&b.1)))
+        })?;
+    Some(Cycle {
+        start: reference,
+        period: t1 - t0,
+    })
+}
+
+fn mean_x<F>(cycle: &Cycle, dt: f64, bias: f64, step: &F) -> f64
+where
+    F: Fn([f64; 3], f64, f64) -> [f64; 3],
+{
+    let n = (cycle.period / dt).ceil().max(1.0) as usize;
+    let h = cycle.period / n as f64;
+    let mut state = cycle.start;
+    let mut sum = 0.0;
+    for _ in 0..n {
+        state = step(state, h, bias);
+        sum += state[0];
+    }
+    sum / n as f64
+}
+
+impl LimitCycle {
+    // `step(state, dt, bias)` advances the autonomous model by dt with `bias`
+    // added to e. Returns None when no oscillation is found (e.g. the
+    // parameters put the model at a stable fixed point).
+    pub fn fit<F>(start: [f64; 3], dt: f64, points: usize, threshold: f64, step: F) -> Option<Self>
+    where
+        F: Fn([f64; 3], f64, f64) -> [f64; 3],
+    {
+        let points = points.max(8);
+        let cycle = find_cycle(start, dt, threshold, 0.0, &step)?;
+        if cycle.period <= dt {
+            return None;
+        }
+
+        // Resample one period on a uniform grid, integrating between samples
+        // at a step no larger than dt.
+        let h_sample = cycle.period / points as f64;
+        let sub = (h_sample / dt).ceil().max(1.0) as usize;
+        let h = h_sample / sub as f64;
+        let mut samples = Vec::with_capacity(points);
+        let mut state = cycle.start;
+        for _ in 0..points {
+            samples.push(state);
+            for _ in 0..sub {
+                state = step(state, h, 0.0);
+            }
+        }
+
+        let shifted = find_cycle(cycle.start, dt, threshold, SENSITIVITY_DELTA, &step);
+        let input_gain = match shifted {
+            Some(shifted) => {
+                (mean_x(&shifted, dt, SENSITIVITY_DELTA, &step) - mean_x(&cycle, dt, 0.0, &step))
+                    / SENSITIVITY_DELTA
+            }
+            None => 0.0,
+        };
+
+        Some(Self {
+            samples,
+            period: cycle.period,
+            input_gain,
+        })
+    }
+
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    // Periodic Catmull-Rom interpolation at `phase` in [0, 1).
+    pub fn eval(&self, phase: f64, input: f64) -> [f64; 3] {
+        let n = self.samples.len();
+        let u = phase.rem_euclid(1.0) * n as f64;
+        let i = (u.floor() as usize) % n;
+        let f = u - u.floor();
+        let p0 = self.samples[(i + n - 1) % n];
+        let p1 = self.samples[i];
+        let p2 = self.samples[(i + 1) % n];
+        let p3 = self.samples[(i + 2) % n];
+        let mut out = [0.0; 3];
+        for j in 0..3 {
+            out[j] = 0.5
+                * (2.0 * p1[j]
+                    + (p2[j] - p0[j]) * f
+                    + (2.0 * p0[j] - 5.0 * p1[j] + 4.0 * p2[j] - p3[j]) * f * f
+                    + (3.0 * p1[j] - p0[j] - 3.0 * p2[j] + p3[j]) * f * f * f);
+        }
+        out[0] -= self.input_gain * input;
+        out
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// Fits run one after another on a single worker thread, started with the
+// first instance. A refit requested from the tick thread (by a trial epoch,
+// a scheduled change or a control update) then only queues a job. None once
+// the thread could not be spawned.
+static WORKER: OnceLock<Option<Sender<Job>>> = OnceLock::new();
+
+pub fn start_worker() {
+    WORKER.get_or_init(|| {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("hr-approx".to_string())
+            .spawn(move || {
+                for job in queue {
+                    // A panicking fit drops its result sender, which its
+                    // PendingFit reports as no cycle; the worker carries on.
+                    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                }
+            })
+            .ok()?;
+        Some(jobs)
+    });
+}
+
+// A fit queued on the worker.
+#[derive(Debug)]
+pub struct PendingFit(Receiver<Option<LimitCycle>>);
+
+impl PendingFit {
+    // Without a running worker the job is dropped and the fit reports no
+    // cycle.
+    pub fn submit<F>(start: [f64; 3], dt: f64, points: usize, threshold: f64, step: F) -> Self
+    where
+        F: Fn([f64; 3], f64, f64) -> [f64; 3] + Send + 'static,
+    {
+        let (done, result) = mpsc::channel();
+        let job: Job = Box::new(move || {
+            let _ = done.send(LimitCycle::fit(start, dt, points, threshold, step));
+        });
+        if let Some(worker) = WORKER.get().and_then(Option::as_ref) {
+            let _ = worker.send(job);
+        }
+        Self(result)
+    }
+
+    // The fit once the worker has finished; a fit that panicked or never ran
+    // counts as no cycle found.
+    pub fn poll(&self) -> Option<Option<LimitCycle>> {
+        match self.0.try_recv() {
+            Ok(cycle) => Some(cycle),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(None),
+        }
+    }
+}
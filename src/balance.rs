@@ -0,0 +1,54 @@
+// Online accuracy monitor built on z's balance law. Integrating dz/dt along
+// the computed trajectory (trapezoidal rule over substeps) must reproduce
+// the change in z; the residual between the two drifts slowly with the
+// quadrature error, while a sudden jump between ticks points at an
+// integration error in the main solver.
+
+#[derive(Debug, Clone, Default)]
+pub struct BalanceMonitor {
+    pub enabled: bool,
+    // Per-tick residual change counted as a jump; 0 disables counting.
+    pub jump_threshold: f64,
+    reference: f64,
+    integral: f64,
+    rate: f64,
+    residual: f64,
+    tick_start: f64,
+    jumps: u64,
+}
+
+impl BalanceMonitor {
+    // Restarts the balance from state z with dz/dt = rate.
+    pub fn rebase(&mut self, z: f64, rate: f64) {
+        self.reference = z;
+        self.integral = 0.0;
+        self.rate = rate;
+        self.residual = 0.0;
+        self.tick_start = 0.0;
+    }
+
+    // Called after each substep of size dt with the new z and dz/dt.
+    pub fn step(&mut self, z: f64, rate: f64, dt: f64) {
+        self.integral += 0.5 * (self.rate + rate) * dt;
+        self.rate = rate;
+        self.residual = z - self.reference - self.integral;
+    }
+
+    // Called once per tick; returns true when the residual jumped.
+    pub fn end_tick(&mut self) -> bool {
+        let jumped = self.jump_threshold > 0.0 && (self.residual - self.tick_start).abs() > self.jump_threshold;
+        if jumped {
+            self.jumps += 1;
+        }
+        self.tick_start = self.residual;
+        jumped
+    }
+
+    pub fn residual(&self) -> f64 {
+        self.residual
+    }
+
+    pub fn jumps(&self) -> u64 {
+        self.jumps
+    }
+}
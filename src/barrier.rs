@@ -0,0 +1,89 @@
+// Deterministic coupling between instances of a barrier group. Every member
+// publishes its state at the end of each tick into a two-slot buffer tagged
+// with the host tick number. A coupled member reads the latest value tagged
+// before its own current tick, so in tick k it always sees its peer's tick
+// k-1 state, whichever of the two the host processes first. The registry
+// lock is only held to copy a few floats.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Slots {
+    ticks: [Option<u64>; 2],
+    states: [[f64; 3]; 2],
+}
+
+static REGISTRY: Mutex<BTreeMap<String, BTreeMap<u64, Slots>>> = Mutex::new(BTreeMap::new());
+
+fn registry() -> std::sync::MutexGuard<'static, BTreeMap<String, BTreeMap<u64, Slots>>> {
+    REGISTRY.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Registration of one instance in a group; leaves the group when dropped.
+#[derive(Debug)]
+pub struct Membership {
+    group: String,
+    id: u64,
+}
+
+impl Membership {
+    pub fn join(group: &str, id: u64) -> Self {
+        registry()
+            .entry(group.to_string())
+            .or_default()
+            .insert(id, Slots::default());
+        Self {
+            group: group.to_string(),
+            id,
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn members(&self) -> usize {
+        registry().get(&self.group).map_or(0, BTreeMap::len)
+    }
+
+    pub fn publish(&self, tick: u64, state: [f64; 3]) {
+        if let Some(slots) = registry().get_mut(&self.group).and_then(|group| group.get_mut(&self.id)) {
+            let slot = (tick % 2) as usize;
+            slots.ticks[slot] = Some(tick);
+            slots.states[slot] = state;
+        }
+    }
+
+    // State `peer` published for the latest tick before `tick`.
+    pub fn read(&self, peer: u64, tick: u64) -> Option<[f64; 3]> {
+        let registry = registry();
+        let slots = registry.get(&self.group)?.get(&peer)?;
+        (0..2)
+            .filter_map(|slot| Some((slots.ticks[slot]?, slots.states[slot])))
+            .filter(|(published, _)| *published < tick)
+            .max_by_key(|(published, _)| *published)
+            .map(|(_, state)| state)
+    }
+}
+
+impl Drop for Membership {
+    fn drop(&mut self) {
+        let mut registry = registry();
+        if let Some(group) = registry.get_mut(&self.group) {
+            group.remove(&self.id);
+            if group.is_empty() {
+                registry.remove(&self.group);
+            }
+        }
+    }
+}
+
+// Coupling current taken from a peer's state: gain * state[variable],
+// entering the x equation like i_syn.
+#[derive(Debug, Clone, Copy)]
+pub struct Coupling {
+    pub peer: u64,
+    pub variable: usize,
+    pub gain: f64,
+}
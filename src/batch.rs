@@ -0,0 +1,54 @@
+// Host hints about when outputs are next read. While outputs are not going
+// to be consumed, process calls only count ticks; the consuming tick then
+// integrates all pending ticks in one go and runs the per-tick output
+// bookkeeping once with the combined period.
+
+#[derive(Debug, Clone)]
+pub struct TickBatch {
+    every: usize,
+    countdown: usize,
+    pending: usize,
+}
+
+impl TickBatch {
+    pub fn new() -> Self {
+        Self {
+            every: 1,
+            countdown: 1,
+            pending: 0,
+        }
+    }
+
+    pub fn every(&self) -> usize {
+        self.every
+    }
+
+    // Outputs are read every `ticks` ticks from now on.
+    pub fn set_every(&mut self, ticks: usize) {
+        self.every = ticks.max(1);
+        self.countdown = self.every;
+    }
+
+    // One-shot: outputs are next read `ticks` ticks from now, counting the
+    // coming one.
+    pub fn hint(&mut self, ticks: usize) {
+        self.countdown = ticks.max(1);
+    }
+
+    // Called on every process call; returns true when the tick can be
+    // deferred to a later one.
+    pub fn defer(&mut self) -> bool {
+        self.pending += 1;
+        if self.countdown > 1 {
+            self.countdown -= 1;
+            return true;
+        }
+        self.countdown = self.every;
+        false
+    }
+
+    // Number of host ticks the current process call has to cover.
+    pub fn take(&mut self) -> usize {
+        std::mem::take(&mut self.pending).max(1)
+    }
+}
@@ -0,0 +1,110 @@
+// Per-tick latency of the plugin on this machine across integrators and
+// population sizes. A population of N is N independent instances processed
+// back to back within each tick, as a host would schedule them.
+
+use hindmarsh_rose_v2_rust::rtsyn_plugin_api;
+use std::ffi::c_void;
+use std::time::Instant;
+
+const WARMUP_TICKS: u64 = 200;
+
+// Names accepted by the "integrator" config key.
+const INTEGRATORS: [&str; 8] = ["euler", "heun", "rk4", "rk6", "rk45", "backward_euler", "ab3", "ab4"];
+
+pub struct Options {
+    ticks: u64,
+    period: f64,
+    integrators: Vec<String>,
+    populations: Vec<usize>,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            ticks: 5000,
+            period: 0.001,
+            integrators: INTEGRATORS.map(String::from).to_vec(),
+            populations: vec![1, 10, 100],
+        };
+        for (key, value) in crate::flags(args)? {
+            match key {
+                "ticks" => options.ticks = value.parse().map_err(|_| format!("bad value {value:?} for --ticks"))?,
+                "period" => options.period = value.parse().map_err(|_| format!("bad value {value:?} for --period"))?,
+                "integrators" => options.integrators = crate::parse_list(key, value)?,
+                "populations" => options.populations = crate::parse_list(key, value)?,
+                _ => return Err(format!("unknown option --{key}")),
+            }
+        }
+        if let Some(unknown) = options.integrators.iter().find(|name| !INTEGRATORS.contains(&name.as_str())) {
+            return Err(format!("unknown integrator {unknown:?}, expected one of {}", INTEGRATORS.join(", ")));
+        }
+        if options.populations.contains(&0) {
+            return Err("--populations must be positive".to_string());
+        }
+        if options.ticks == 0 || options.period <= 0.0 {
+            return Err("--ticks and --period must be positive".to_string());
+        }
+        Ok(options)
+    }
+}
+
+struct Latency {
+    mean_us: f64,
+    p99_us: f64,
+    max_us: f64,
+}
+
+fn measure(options: &Options, integrator: &str, population: usize) -> Latency {
+    let api = unsafe { &*rtsyn_plugin_api() };
+    let config = serde_json::json!({ "integrator": integrator }).to_string();
+    let handles: Vec<*mut c_void> = (0..population as u64).map(|id| (api.create)(id)).collect();
+    for &handle in &handles {
+        (api.set_config_json)(handle, config.as_ptr(), config.len());
+    }
+    let mut samples = Vec::with_capacity(options.ticks as usize);
+    for tick in 0..WARMUP_TICKS + options.ticks {
+        let started = Instant::now();
+        for &handle in &handles {
+            (api.process)(handle, tick, options.period);
+        }
+        if tick >= WARMUP_TICKS {
+            samples.push(started.elapsed().as_secs_f64() * 1e6);
+        }
+    }
+    for handle in handles {
+        (api.destroy)(handle);
+    }
+    samples.sort_by(f64::total_cmp);
+    let p99 = ((samples.len() as f64 * 0.99) as usize).min(samples.len() - 1);
+    Latency {
+        mean_us: samples.iter().sum::<f64>() / samples.len() as f64,
+        p99_us: samples[p99],
+        max_us: samples[samples.len() - 1],
+    }
+}
+
+pub fn run(options: &Options) {
+    let budget_us = options.period * 1e6;
+    println!(
+        "{} ticks of {} s per case, precision f64",
+        options.ticks, options.period
+    );
+    println!(
+        "{:<16} {:>10} {:>12} {:>12} {:>12} {:>10}",
+        "integrator", "population", "mean_us", "p99_us", "max_us", "budget_%"
+    );
+    for integrator in &options.integrators {
+        for &population in &options.populations {
+            let latency = measure(options, integrator, population);
+            println!(
+                "{:<16} {:>10} {:>12.2} {:>12.2} {:>12.2} {:>10.1}",
+                integrator,
+                population,
+                latency.mean_us,
+                latency.p99_us,
+                latency.max_us,
+                100.0 * latency.p99_us / budget_us
+            );
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Command-line front end driving the plugin through the same PluginApi
+// table a host uses.
+//
+//   hindmarsh-rose-sim bench [--ticks N] [--period S] [--integrators a,b]
+//                            [--populations 1,10,100]
+//   hindmarsh-rose-sim run <scenario.json>
+
+mod bench;
+mod scenario;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: hindmarsh-rose-sim bench [--ticks N] [--period S] [--integrators a,b] [--populations 1,10]
+       hindmarsh-rose-sim run <scenario.json>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("bench") => bench::Options::parse(&args[1..]).map(|options| bench::run(&options)),
+        Some("run") if args.len() == 2 => scenario::Scenario::load(&args[1]).and_then(|scenario| scenario.run()),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Splits "--key value" pairs, rejecting dangling keys and stray values.
+fn flags(args: &[String]) -> Result<Vec<(&str, &str)>, String> {
+    args.chunks(2)
+        .map(|pair| match pair {
+            [key, value] if key.starts_with("--") => Ok((&key[2..], value.as_str())),
+            _ => Err(format!("unexpected argument {:?}\n{USAGE}", pair[0])),
+        })
+        .collect()
+}
+
+fn parse_list<T: std::str::FromStr>(key: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| item.trim().parse().map_err(|_| format!("bad value {item:?} for --{key}")))
+        .collect()
+}
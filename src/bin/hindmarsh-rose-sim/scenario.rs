@@ -0,0 +1,247 @@
+// Offline batch runs described by a JSON scenario file:
+//
+//   {
+//     "output_dir": "results",        // default "results"
+//     "period": 0.001,                // host tick period in seconds
+//     "jobs": 4,                      // default: available cores
+//     "runs": [{
+//       "name": "baseline",
+//       "config": { "e": 3.2 },       // sent once through set_config_json
+//       "duration": 10.0,             // seconds of host time
+//       "outputs": ["Membrane potential (V)"],
+//       "record_every": 1,            // write every n-th tick
+//       "stimuli": [{ "input": "i_syn", "start": 1.0, "stop": 2.0, "value": 0.5 }]
+//     }]
+//   }
+//
+// Each run writes <output_dir>/<name>/outputs.csv (time plus one column per
+// output) and run.json, the run description as executed. Runs are spread
+// over `jobs` worker threads; a line is printed as each one finishes, with
+// an FNV-1a checksum of the recorded output bits. Builds with the
+// `deterministic` feature print the same checksums on every platform.
+
+use hindmarsh_rose_v2_rust::rtsyn_plugin_api;
+use serde_json::Value;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+struct Stimulus {
+    input: String,
+    start: f64,
+    stop: f64,
+    value: f64,
+}
+
+struct Run {
+    name: String,
+    config: Value,
+    ticks: u64,
+    outputs: Vec<String>,
+    record_every: u64,
+    stimuli: Vec<Stimulus>,
+    spec: Value,
+}
+
+pub struct Scenario {
+    output_dir: PathBuf,
+    period: f64,
+    jobs: usize,
+    runs: Vec<Run>,
+}
+
+fn field<'a>(value: &'a Value, key: &str, context: &str) -> Result<&'a Value, String> {
+    value.get(key).ok_or_else(|| format!("{context}: missing \"{key}\""))
+}
+
+impl Stimulus {
+    fn parse(value: &Value, context: &str) -> Result<Self, String> {
+        let number = |key| {
+            field(value, key, context)?
+                .as_f64()
+                .ok_or_else(|| format!("{context}: \"{key}\" must be a number"))
+        };
+        Ok(Self {
+            input: field(value, "input", context)?
+                .as_str()
+                .ok_or_else(|| format!("{context}: \"input\" must be a string"))?
+                .to_string(),
+            start: number("start")?,
+            stop: number("stop")?,
+            value: number("value")?,
+        })
+    }
+
+    fn active(&self, time: f64) -> bool {
+        time >= self.start && time < self.stop
+    }
+}
+
+impl Run {
+    fn parse(value: &Value, index: usize, period: f64) -> Result<Self, String> {
+        let name = value
+            .get("name")
+            .and_then(Value::as_str)
+            .map_or_else(|| format!("run{index}"), str::to_string);
+        let context = format!("run {name:?}");
+        if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+            return Err(format!("{context}: name must be a plain directory name"));
+        }
+        let duration = field(value, "duration", &context)?
+            .as_f64()
+            .filter(|duration| *duration > 0.0)
+            .ok_or_else(|| format!("{context}: \"duration\" must be a positive number"))?;
+        let outputs = match value.get("outputs") {
+            None => vec!["Membrane potential (V)".to_string()],
+            Some(outputs) => outputs
+                .as_array()
+                .and_then(|names| names.iter().map(|name| name.as_str().map(str::to_string)).collect())
+                .ok_or_else(|| format!("{context}: \"outputs\" must be an array of names"))?,
+        };
+        let stimuli = match value.get("stimuli") {
+            None => Vec::new(),
+            Some(stimuli) => stimuli
+                .as_array()
+                .ok_or_else(|| format!("{context}: \"stimuli\" must be an array"))?
+                .iter()
+                .map(|stimulus| Stimulus::parse(stimulus, &context))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self {
+            name,
+            config: value.get("config").cloned().unwrap_or(Value::Object(Default::default())),
+            ticks: (duration / period).round().max(1.0) as u64,
+            outputs,
+            record_every: value.get("record_every").and_then(Value::as_u64).unwrap_or(1).max(1),
+            stimuli,
+            spec: value.clone(),
+        })
+    }
+
+    fn execute(&self, dir: &Path, period: f64) -> Result<u64, String> {
+        let io = |error: std::io::Error| format!("run {:?}: {error}", self.name);
+        fs::create_dir_all(dir).map_err(io)?;
+        fs::write(dir.join("run.json"), self.spec.to_string()).map_err(io)?;
+        let mut csv = BufWriter::new(fs::File::create(dir.join("outputs.csv")).map_err(io)?);
+        writeln!(csv, "time,{}", self.outputs.join(",")).map_err(io)?;
+
+        let api = unsafe { &*rtsyn_plugin_api() };
+        let handle = (api.create)(0);
+        let config = self.config.to_string();
+        (api.set_config_json)(handle, config.as_ptr(), config.len());
+        let mut inputs: Vec<&str> = self.stimuli.iter().map(|stimulus| stimulus.input.as_str()).collect();
+        inputs.sort_unstable();
+        inputs.dedup();
+
+        let mut checksum: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut result = Ok(());
+        for tick in 0..self.ticks {
+            let time = tick as f64 * period;
+            // Overlapping stimuli on the same input add up.
+            for &input in &inputs {
+                let value: f64 = self
+                    .stimuli
+                    .iter()
+                    .filter(|stimulus| stimulus.input == input && stimulus.active(time))
+                    .map(|stimulus| stimulus.value)
+                    .sum();
+                (api.set_input)(handle, input.as_ptr(), input.len(), value);
+            }
+            (api.process)(handle, tick, period);
+            if tick % self.record_every == 0 {
+                let row: Vec<String> = self
+                    .outputs
+                    .iter()
+                    .map(|name| {
+                        let value = (api.get_output)(handle, name.as_ptr(), name.len());
+                        checksum = value
+                            .to_bits()
+                            .to_le_bytes()
+                            .iter()
+                            .fold(checksum, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+                        value.to_string()
+                    })
+                    .collect();
+                result = writeln!(csv, "{},{}", time + period, row.join(",")).map_err(io);
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+        (api.destroy)(handle);
+        result?;
+        csv.flush().map_err(io)?;
+        Ok(checksum)
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+        let value: Value = serde_json::from_str(&text).map_err(|error| format!("{path}: {error}"))?;
+        let period = value.get("period").and_then(Value::as_f64).unwrap_or(0.001);
+        if period <= 0.0 {
+            return Err(format!("{path}: \"period\" must be positive"));
+        }
+        let runs = field(&value, "runs", path)?
+            .as_array()
+            .ok_or_else(|| format!("{path}: \"runs\" must be an array"))?
+            .iter()
+            .enumerate()
+            .map(|(index, run)| Run::parse(run, index, period))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut names: Vec<&str> = runs.iter().map(|run| run.name.as_str()).collect();
+        names.sort_unstable();
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(format!("{path}: duplicate run name {:?}", pair[0]));
+        }
+        let cores = std::thread::available_parallelism().map_or(1, usize::from);
+        Ok(Self {
+            output_dir: PathBuf::from(value.get("output_dir").and_then(Value::as_str).unwrap_or("results")),
+            period,
+            jobs: value.get("jobs").and_then(Value::as_u64).map_or(cores, |jobs| jobs as usize).max(1),
+            runs,
+        })
+    }
+
+    // Returns an error naming every run that failed; the others still
+    // complete.
+    pub fn run(&self) -> Result<(), String> {
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let failures = Mutex::new(Vec::new());
+        let total = self.runs.len();
+        eprintln!("{total} runs on {} workers into {}", self.jobs.min(total), self.output_dir.display());
+        std::thread::scope(|scope| {
+            for _ in 0..self.jobs.min(total) {
+                scope.spawn(|| {
+                    while let Some(run) = self.runs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let started = Instant::now();
+                        let result = run.execute(&self.output_dir.join(&run.name), self.period);
+                        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        match result {
+                            Ok(checksum) => eprintln!(
+                                "[{finished}/{total}] {} done in {:.2} s, checksum {checksum:016x}",
+                                run.name,
+                                started.elapsed().as_secs_f64()
+                            ),
+                            Err(message) => {
+                                eprintln!("[{finished}/{total}] {message}");
+                                failures.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(run.name.clone());
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        let failures = failures.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} of {total} runs failed: {}", failures.len(), failures.join(", ")))
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// Burst cycle measurements in model time, updated at every burst onset:
+// period is onset-to-onset and duration runs from the onset to the last
+// spike before the next onset. Per-cycle aggregates of x (mean and peak
+// from one onset to the next) are refreshed at the same moment. Intervals
+// are taken as elapsed model time, so reverse-time runs measure the same
+// cycle.
+
+#[derive(Debug, Clone, Default)]
+pub struct BurstTracker {
+    onset: Option<f64>,
+    last_spike: f64,
+    pub period: Option<f64>,
+    pub duration: Option<f64>,
+    pub cycle_mean: Option<f64>,
+    pub cycle_peak: Option<f64>,
+    sum: f64,
+    samples: u64,
+    peak: f64,
+}
+
+impl BurstTracker {
+    pub fn on_spike(&mut self, t: f64, onset: bool) {
+        if onset {
+            if let Some(previous) = self.onset {
+                self.period = Some((t - previous).abs());
+                self.duration = Some((self.last_spike - previous).abs());
+                if self.samples > 0 {
+                    self.cycle_mean = Some(self.sum / self.samples as f64);
+                    self.cycle_peak = Some(self.peak);
+                }
+            }
+            self.onset = Some(t);
+            self.sum = 0.0;
+            self.samples = 0;
+            self.peak = f64::NEG_INFINITY;
+        }
+        self.last_spike = t;
+    }
+
+    // Called once per substep with the membrane variable.
+    pub fn sample(&mut self, x: f64) {
+        if self.onset.is_some() {
+            self.sum += x;
+            self.samples += 1;
+            self.peak = self.peak.max(x);
+        }
+    }
+
+    // Fraction of the last measured period elapsed since the latest onset.
+    pub fn phase(&self, t: f64) -> Option<f64> {
+        let period = self.period.filter(|period| *period > 0.0)?;
+        Some((t - self.onset?).abs() / period)
+    }
+
+    pub fn duty_cycle(&self) -> Option<f64> {
+        match (self.duration, self.period) {
+            (Some(duration), Some(period)) if period > 0.0 => Some(duration / period),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventKind, EventSink};
+    use crate::field::{derivatives, jacobian, Params};
+    use crate::rk::Integrator;
+
+    // Feeds (t, x) samples through spike detection into a tracker, the way
+    // observe_events does once per substep.
+    fn track(samples: impl Iterator<Item = (f64, f64)>) -> (BurstTracker, Vec<f64>) {
+        let mut events = EventSink::new();
+        let mut bursts = BurstTracker::default();
+        let mut periods = Vec::new();
+        for (t, x) in samples {
+            let detected = events.observe(x, true, t);
+            bursts.sample(x);
+            if let Some(kind) = detected {
+                bursts.on_spike(t, kind == EventKind::BurstOnset);
+                if kind == EventKind::BurstOnset {
+                    periods.extend(bursts.period);
+                }
+            }
+        }
+        (bursts, periods)
+    }
+
+    // A reverse run sees the cycle's samples in the opposite order with t
+    // decreasing. The cycle (regular bursting at e = 2.5) is recorded
+    // forward, as the reverse field repels from it, and both directions must
+    // measure the same period and spike amplitude.
+    #[test]
+    fn measures_the_same_cycle_in_reverse_time() {
+        let params = Params { e: 2.5, ..Params::DEFAULT };
+        let dt = 0.05;
+        let mut state = [-0.9013, -3.1594, 3.24782];
+        let mut t = 0.0;
+        let mut trace = Vec::new();
+        for step in 0..80_000 {
+            state = Integrator::Rk4.step(state, dt, |v| derivatives(*v, &params), |v| jacobian(*v, &params));
+            t += dt;
+            // Past the transient onto the attracting cycle.
+            if step >= 40_000 {
+                trace.push((t, state[0]));
+            }
+        }
+
+        let (forward, forward_periods) = track(trace.iter().copied());
+        let (reverse, reverse_periods) = track(trace.iter().rev().copied());
+        assert!(forward_periods.len() >= 10, "only {} cycles in the reference run", forward_periods.len());
+        assert!(reverse_periods.iter().all(|period| *period > 0.0));
+
+        // The first interval either way starts mid-burst at the trace's edge.
+        let reference = forward.period.unwrap();
+        for period in forward_periods.iter().skip(1).chain(reverse_periods.iter().skip(1)) {
+            assert!((period - reference).abs() <= 2.0 * dt, "period {period} against {reference}");
+        }
+        let peak = forward.cycle_peak.unwrap();
+        assert!((reverse.cycle_peak.unwrap() - peak).abs() < 1e-3);
+        let phase = reverse.phase(reverse.onset.unwrap() - 0.25 * reference).unwrap();
+        assert!((phase - 0.25).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,65 @@
+// Conductance inputs for dynamic-clamp style injection. With
+// "conductance_inputs": true the inputs g_syn_exc and g_syn_inh add
+//
+//   g_exc (e_rev_exc - x) + g_inh (e_rev_inh - x)
+//
+// to dx/dt. Unlike the other input currents this is not held over the tick:
+// the constant part joins the drive and the x-dependent part is the field's
+// leak term, so every solver step sees the current at its own x.
+
+use crate::field::Shape;
+use crate::inputs::{InputPort, Staleness};
+
+#[derive(Debug, Clone)]
+pub struct ConductanceInputs {
+    pub enabled: bool,
+    pub e_rev_exc: f64,
+    pub e_rev_inh: f64,
+    pub exc: InputPort,
+    pub inh: InputPort,
+}
+
+impl Default for ConductanceInputs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            e_rev_exc: 2.0,
+            e_rev_inh: -2.0,
+            exc: InputPort::new(),
+            inh: InputPort::new(),
+        }
+    }
+}
+
+impl ConductanceInputs {
+    fn total(&self) -> f64 {
+        self.exc.value + self.inh.value
+    }
+
+    // g_exc e_rev_exc + g_inh e_rev_inh, the part independent of x.
+    fn reversal_current(&self) -> f64 {
+        self.exc.value * self.e_rev_exc + self.inh.value * self.e_rev_inh
+    }
+
+    // Folds the conductances into `shape` and `input`; None while both are
+    // zero, so the textbook fast path stays in use.
+    pub fn fold(&self, shape: Shape<f64>, input: f64) -> Option<(Shape<f64>, f64)> {
+        if !self.enabled || (self.exc.value == 0.0 && self.inh.value == 0.0) {
+            return None;
+        }
+        let shape = Shape { leak: shape.leak + self.total(), ..shape };
+        Some((shape, input - self.reversal_current()))
+    }
+
+    pub fn current(&self, x: f64) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.reversal_current() - self.total() * x
+    }
+
+    pub fn tick(&mut self, period: f64, staleness: &Staleness) {
+        self.exc.tick(period, staleness);
+        self.inh.tick(period, staleness);
+    }
+}
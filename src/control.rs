@@ -0,0 +1,207 @@
+// Optional HTTP control endpoint for browser dashboards, compiled in with
+// the `control_server` feature and started by the `control_port` config key.
+//
+//   GET  /params   current parameters as JSON
+//   POST /params   JSON object queued as a config update
+//   GET  /outputs  latest summary (time, parameters, outputs)
+//   GET  /stream   the same summary as server-sent events
+//
+// The server runs on its own threads and never touches the instance: the
+// process thread drains queued updates and publishes summaries through
+// try_lock, so a busy server can delay an update by a tick but never block
+// the real-time path. Without the feature `start` always returns None.
+
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+// Host seconds between published summaries.
+pub const PUBLISH_INTERVAL: f64 = 0.05;
+
+#[derive(Debug, Default)]
+struct Shared {
+    pending: Vec<Value>,
+    summary: Value,
+    version: u64,
+    stopped: bool,
+}
+
+#[derive(Debug)]
+pub struct ControlServer {
+    shared: Arc<Mutex<Shared>>,
+    address: String,
+    next_publish: f64,
+}
+
+impl ControlServer {
+    #[cfg(feature = "control_server")]
+    pub fn start(bind: &str, port: u16) -> Option<Self> {
+        let listener = std::net::TcpListener::bind((bind, port)).ok()?;
+        let address = listener.local_addr().ok()?.to_string();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        http::spawn(listener, Arc::clone(&shared)).ok()?;
+        Some(Self {
+            shared,
+            address,
+            next_publish: 0.0,
+        })
+    }
+
+    #[cfg(not(feature = "control_server"))]
+    pub fn start(_bind: &str, _port: u16) -> Option<Self> {
+        None
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    // Config updates posted since the last call; empty while the server
+    // holds the lock.
+    pub fn take_updates(&self) -> Vec<Value> {
+        match self.shared.try_lock() {
+            Ok(mut shared) if !shared.pending.is_empty() => std::mem::take(&mut shared.pending),
+            _ => Vec::new(),
+        }
+    }
+
+    // Publishes `summary()` at most every PUBLISH_INTERVAL of host time `now`.
+    pub fn publish(&mut self, now: f64, summary: impl FnOnce() -> Value) {
+        if now < self.next_publish {
+            return;
+        }
+        if let Ok(mut shared) = self.shared.try_lock() {
+            shared.summary = summary();
+            shared.version += 1;
+            self.next_publish = now + PUBLISH_INTERVAL;
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.stopped = true;
+        }
+    }
+}
+
+#[cfg(feature = "control_server")]
+mod http {
+    use super::Shared;
+    use serde_json::Value;
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::time::Duration;
+
+    const POLL: Duration = Duration::from_millis(50);
+    const MAX_BODY: usize = 64 * 1024;
+
+    fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+        shared.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Accepts connections until the owning ControlServer is dropped.
+    pub fn spawn(listener: TcpListener, shared: Arc<Mutex<Shared>>) -> io::Result<()> {
+        listener.set_nonblocking(true)?;
+        std::thread::Builder::new()
+            .name("hr-control".to_string())
+            .spawn(move || loop {
+                if lock(&shared).stopped {
+                    return;
+                }
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let shared = Arc::clone(&shared);
+                        std::thread::spawn(move || {
+                            // A client hanging up mid-response is not an error.
+                            let _ = serve(stream, &shared);
+                        });
+                    }
+                    Err(_) => std::thread::sleep(POLL),
+                }
+            })?;
+        Ok(())
+    }
+
+    fn serve(stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut stream = stream;
+        match (method, path) {
+            ("GET", "/params") => {
+                let params = lock(shared).summary.get("params").cloned().unwrap_or(Value::Null);
+                respond(&mut stream, "200 OK", &params.to_string())
+            }
+            ("GET", "/outputs") => {
+                let summary = lock(shared).summary.to_string();
+                respond(&mut stream, "200 OK", &summary)
+            }
+            ("POST" | "PUT", "/params") if length <= MAX_BODY => {
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body)?;
+                match serde_json::from_slice::<Value>(&body) {
+                    Ok(update @ Value::Object(_)) => {
+                        lock(shared).pending.push(update);
+                        respond(&mut stream, "202 Accepted", "{}")
+                    }
+                    _ => respond(&mut stream, "400 Bad Request", r#"{"error":"expected a JSON object"}"#),
+                }
+            }
+            ("GET", "/stream") => stream_events(&mut stream, shared),
+            _ => respond(&mut stream, "404 Not Found", r#"{"error":"not found"}"#),
+        }
+    }
+
+    fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    // Sends each new summary as one event until the client disconnects or
+    // the server stops.
+    fn stream_events(stream: &mut TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+             Access-Control-Allow-Origin: *\r\nConnection: keep-alive\r\n\r\n"
+        )?;
+        let mut seen = 0;
+        loop {
+            let next = {
+                let shared = lock(shared);
+                if shared.stopped {
+                    return Ok(());
+                }
+                (shared.version != seen).then(|| (shared.version, shared.summary.to_string()))
+            };
+            if let Some((version, summary)) = next {
+                seen = version;
+                write!(stream, "data: {summary}\n\n")?;
+                stream.flush()?;
+            }
+            std::thread::sleep(POLL);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Optional co-simulation bridge over a Unix domain socket, compiled in with
+// the `cosim` feature and started by the `cosim_socket` config key. The
+// external simulator (NEURON, Brian, ...) listens on the socket; the plugin
+// connects and both sides exchange one frame per tick:
+//
+//   magic "HRCS" | u16 version | u16 count | u64 tick | count x f64
+//
+// all little-endian. Plugin frames carry [t, x, y, z, i_syn]; peer frames
+// carry currents, of which the first is added to the synaptic input (later
+// values are reserved). The plugin remains the real-time element: frames
+// are handed to an I/O thread through a bounded channel and dropped when it
+// is full, and received currents are picked up with try_lock, so a slow or
+// absent peer never stalls a tick. Without the feature `start` always
+// returns None.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const MAGIC: [u8; 4] = *b"HRCS";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 16;
+// Outgoing frames buffered while the peer is slow to read.
+#[cfg(all(feature = "cosim", unix))]
+const QUEUE: usize = 256;
+
+pub const STATE_FIELDS: [&str; 5] = ["t", "x", "y", "z", "i_syn"];
+
+fn encode(tick: u64, values: &[f64]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + 8 * values.len());
+    frame.extend_from_slice(&MAGIC);
+    frame.extend_from_slice(&VERSION.to_le_bytes());
+    frame.extend_from_slice(&(values.len() as u16).to_le_bytes());
+    frame.extend_from_slice(&tick.to_le_bytes());
+    for value in values {
+        frame.extend_from_slice(&value.to_le_bytes());
+    }
+    frame
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    // Latest peer frame not yet taken by the process thread.
+    latest: Option<(u64, Vec<f64>)>,
+    connected: bool,
+}
+
+#[derive(Debug)]
+pub struct CoSim {
+    path: String,
+    shared: Arc<Mutex<Shared>>,
+    stopped: Arc<AtomicBool>,
+    received: Arc<AtomicU64>,
+    outgoing: std::sync::mpsc::SyncSender<Vec<u8>>,
+    sent: u64,
+    dropped: u64,
+    // Tick of the peer frame currently applied.
+    peer_tick: Option<u64>,
+    pub current: f64,
+}
+
+impl CoSim {
+    #[cfg(all(feature = "cosim", unix))]
+    pub fn start(path: &str) -> Option<Self> {
+        let (outgoing, queue) = std::sync::mpsc::sync_channel(QUEUE);
+        let cosim = Self {
+            path: path.to_string(),
+            shared: Arc::default(),
+            stopped: Arc::default(),
+            received: Arc::default(),
+            outgoing,
+            sent: 0,
+            dropped: 0,
+            peer_tick: None,
+            current: 0.0,
+        };
+        uds::spawn(
+            path.to_string(),
+            queue,
+            Arc::clone(&cosim.shared),
+            Arc::clone(&cosim.stopped),
+            Arc::clone(&cosim.received),
+        )
+        .ok()?;
+        Some(cosim)
+    }
+
+    #[cfg(not(all(feature = "cosim", unix)))]
+    pub fn start(_path: &str) -> Option<Self> {
+        None
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Applies the newest peer frame, if one arrived since the last call and
+    // the I/O thread is not holding the lock; otherwise the previous
+    // current is held.
+    pub fn receive(&mut self) {
+        let Ok(mut shared) = self.shared.try_lock() else {
+            return;
+        };
+        if let Some((tick, values)) = shared.latest.take() {
+            self.peer_tick = Some(tick);
+            self.current = values.first().copied().unwrap_or(0.0);
+        }
+    }
+
+    pub fn send(&mut self, tick: u64, state: [f64; 5]) {
+        match self.outgoing.try_send(encode(tick, &state)) {
+            Ok(()) => self.sent += 1,
+            Err(_) => self.dropped += 1,
+        }
+    }
+
+    pub fn stats(&self, tick: u64) -> serde_json::Value {
+        let connected = self.shared.try_lock().map(|shared| shared.connected).ok();
+        serde_json::json!({
+            "path": self.path,
+            "connected": connected,
+            "frames_sent": self.sent,
+            "frames_dropped": self.dropped,
+            "frames_received": self.received.load(Ordering::Relaxed),
+            "peer_tick": self.peer_tick,
+            "peer_lag": self.peer_tick.map(|peer| tick.saturating_sub(peer)),
+            "current": self.current
+        })
+    }
+}
+
+impl Drop for CoSim {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(feature = "cosim", unix))]
+mod uds {
+    use super::{Shared, HEADER_LEN, MAGIC, VERSION};
+    use std::io::{self, Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::mpsc::{Receiver, RecvTimeoutError};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const RETRY: Duration = Duration::from_millis(200);
+    const POLL: Duration = Duration::from_millis(50);
+    // Frames larger than this are treated as a protocol error.
+    const MAX_VALUES: usize = 64;
+
+    // Parses a frame header, returning the tick and value count.
+    fn decode_header(header: &[u8; HEADER_LEN]) -> Option<(u64, usize)> {
+        if header[..4] != MAGIC || u16::from_le_bytes([header[4], header[5]]) != VERSION {
+            return None;
+        }
+        let count = u16::from_le_bytes([header[6], header[7]]) as usize;
+        let tick = u64::from_le_bytes(header[8..].try_into().ok()?);
+        (count <= MAX_VALUES).then_some((tick, count))
+    }
+
+    fn set_connected(shared: &Mutex<Shared>, connected: bool) {
+        if let Ok(mut shared) = shared.lock() {
+            shared.connected = connected;
+        }
+    }
+
+    // Connects (and reconnects) to the peer until the owning CoSim is
+    // dropped. Frames queued while disconnected are discarded.
+    pub fn spawn(
+        path: String,
+        queue: Receiver<Vec<u8>>,
+        shared: Arc<Mutex<Shared>>,
+        stopped: Arc<AtomicBool>,
+        received: Arc<AtomicU64>,
+    ) -> io::Result<()> {
+        std::thread::Builder::new()
+            .name("hr-cosim".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    let Ok(stream) = UnixStream::connect(&path) else {
+                        while queue.try_recv().is_ok() {}
+                        std::thread::sleep(RETRY);
+                        continue;
+                    };
+                    set_connected(&shared, true);
+                    if let Ok(reader) = stream.try_clone() {
+                        let (shared, stopped, received) =
+                            (Arc::clone(&shared), Arc::clone(&stopped), Arc::clone(&received));
+                        std::thread::spawn(move || {
+                            // A peer hanging up ends the reader; the writer
+                            // notices on its next write.
+                            let _ = read_frames(reader, &shared, &stopped, &received);
+                        });
+                    }
+                    let _ = write_frames(stream, &queue, &stopped);
+                    set_connected(&shared, false);
+                }
+            })?;
+        Ok(())
+    }
+
+    fn write_frames(mut stream: UnixStream, queue: &Receiver<Vec<u8>>, stopped: &AtomicBool) -> io::Result<()> {
+        loop {
+            if stopped.load(Ordering::Relaxed) {
+                return stream.shutdown(std::net::Shutdown::Both);
+            }
+            match queue.recv_timeout(POLL) {
+                Ok(frame) => stream.write_all(&frame)?,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+    }
+
+    fn read_frames(
+        mut stream: UnixStream,
+        shared: &Mutex<Shared>,
+        stopped: &AtomicBool,
+        received: &AtomicU64,
+    ) -> io::Result<()> {
+        let mut header = [0; HEADER_LEN];
+        while !stopped.load(Ordering::Relaxed) {
+            stream.read_exact(&mut header)?;
+            let Some((tick, count)) = decode_header(&header) else {
+                return Err(io::ErrorKind::InvalidData.into());
+            };
+            let mut body = vec![0; 8 * count];
+            stream.read_exact(&mut body)?;
+            let values = body
+                .chunks_exact(8)
+                .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap_or_default()))
+                .collect();
+            if let Ok(mut shared) = shared.lock() {
+                shared.latest = Some((tick, values));
+            }
+            received.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+// Per-tick cost accounting for host schedulers: a static FLOP estimate per
+// integration substep plus measured wall-clock statistics of process().
+
+use std::time::Instant;
+
+// One Runge-Kutta stage: a field evaluation (~19 flops), the k = dt * f
+// scaling and its share of the stage/weight combinations over three state
+// variables. An RK6 step is six of these.
+pub const FLOPS_PER_STAGE: u64 = 42;
+
+const MEAN_WEIGHT: f64 = 0.01;
+
+#[derive(Debug, Clone, Default)]
+pub struct CostMeter {
+    mean_ns: f64,
+    max_ns: u64,
+    ticks: u64,
+}
+
+impl CostMeter {
+    pub fn record(&mut self, started: Instant) {
+        let ns = started.elapsed().as_nanos() as u64;
+        self.mean_ns = if self.ticks == 0 {
+            ns as f64
+        } else {
+            self.mean_ns + MEAN_WEIGHT * (ns as f64 - self.mean_ns)
+        };
+        self.max_ns = self.max_ns.max(ns);
+        self.ticks += 1;
+    }
+
+    pub fn mean_ns(&self) -> f64 {
+        self.mean_ns
+    }
+
+    pub fn max_ns(&self) -> u64 {
+        self.max_ns
+    }
+}
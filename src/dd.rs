@@ -0,0 +1,156 @@
+// Double-double scalar for the `double_double` feature: each value is an
+// unevaluated sum hi + lo of two f64 with |lo| <= ulp(hi) / 2, giving about
+// 106 bits of mantissa. Sums use the error-free two-sum and products the
+// FMA-based two-product, following Hida, Li and Bailey's QD library. Model
+// constants and tableau weights enter as f64, so the gain is in the
+// accumulated rounding of the state over long runs, not in the method
+// itself. The instance keeps the low words between steps in a StateTail.
+
+use crate::field::{Float, Params, Scalar};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+// a + b exactly as s + e.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+// As two_sum, for |a| >= |b|.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+// a * b exactly as p + e.
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+impl DoubleDouble {
+    fn new(hi: f64, lo: f64) -> Self {
+        let (hi, lo) = quick_two_sum(hi, lo);
+        Self { hi, lo }
+    }
+
+    pub fn hi(self) -> f64 {
+        self.hi
+    }
+
+    pub fn lo(self) -> f64 {
+        self.lo
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (s, e) = two_sum(self.hi, rhs.hi);
+        let (t, f) = two_sum(self.lo, rhs.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        Self::new(s, e + f)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let (p, e) = two_prod(self.hi, rhs.hi);
+        Self::new(p, e + (self.hi * rhs.lo + self.lo * rhs.hi))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    // Long division with three f64 quotient digits.
+    fn div(self, rhs: Self) -> Self {
+        let q1 = self.hi / rhs.hi;
+        let r = self - rhs * Self::lit(q1);
+        let q2 = r.hi / rhs.hi;
+        let r = r - rhs * Self::lit(q2);
+        let q3 = r.hi / rhs.hi;
+        Self::new(q1, q2) + Self::lit(q3)
+    }
+}
+
+impl Scalar for DoubleDouble {
+    fn lit(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+}
+
+impl Float for DoubleDouble {
+    // 2^-104.
+    const EPSILON: Self = Self {
+        hi: 4.930380657631324e-32,
+        lo: 0.0,
+    };
+
+    fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+}
+
+pub fn lift_params(p: &Params<f64>) -> Params<DoubleDouble> {
+    p.map(DoubleDouble::lit)
+}
+
+// Low words of the instance state between steps. They only belong to the
+// high words they were stored with, so a state written by anything else
+// (reset, restore, another solver path) silently starts from zero low words.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateTail {
+    hi: [f64; 3],
+    lo: [f64; 3],
+}
+
+impl StateTail {
+    pub fn lift(&self, state: [f64; 3]) -> [DoubleDouble; 3] {
+        let lo = if state == self.hi { self.lo } else { [0.0; 3] };
+        [0, 1, 2].map(|j| DoubleDouble::new(state[j], lo[j]))
+    }
+
+    // Keeps the low words and returns the high ones for the instance.
+    pub fn store(&mut self, state: [DoubleDouble; 3]) -> [f64; 3] {
+        self.hi = state.map(DoubleDouble::hi);
+        self.lo = state.map(DoubleDouble::lo);
+        self.hi
+    }
+
+    pub fn lo(&self) -> [f64; 3] {
+        self.lo
+    }
+}
@@ -0,0 +1,84 @@
+// Time-delayed self-feedback k (x(t - tau) - x(t)), the Pyragas term used to
+// stabilise HR orbits or switch between coexisting ones, with gain
+// "delay_gain" and delay "delay_tau" in model time. The history is one
+// sample of x per tick in a ring buffer, read back with linear
+// interpolation, so tau resolves to a fraction of a tick. x(t - tau) is
+// held over the tick while -k x(t) goes into the field's leak term and so
+// follows every step, as for the conductance inputs. Until the history
+// reaches back tau the term is off. Time is counted as elapsed model time,
+// so reverse-time runs delay by the same amount.
+
+use crate::field::Shape;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default)]
+pub struct DelayedFeedback {
+    pub gain: f64,
+    pub tau: f64,
+    history: VecDeque<(f64, f64)>,
+    clock: f64,
+}
+
+impl DelayedFeedback {
+    pub fn enabled(&self) -> bool {
+        self.gain != 0.0 && self.tau > 0.0
+    }
+
+    // Reserves the history for ticks covering `model_per_tick`, so recording
+    // does not allocate once running.
+    pub fn reserve(&mut self, model_per_tick: f64) {
+        if self.enabled() && model_per_tick > 0.0 {
+            let samples = (self.tau / model_per_tick).ceil() as usize + 2;
+            self.history.reserve(samples.saturating_sub(self.history.len()));
+        }
+    }
+
+    // Appends x at the end of a tick that covered `span` of model time.
+    pub fn record(&mut self, x: f64, span: f64) {
+        self.clock += span.abs();
+        self.history.push_back((self.clock, x));
+        let target = self.clock - self.tau;
+        while self.history.len() > 2 && self.history[1].0 <= target {
+            self.history.pop_front();
+        }
+    }
+
+    // x(t - tau), once the history reaches back that far.
+    pub fn delayed(&self) -> Option<f64> {
+        let target = self.clock - self.tau;
+        let &(t0, x0) = self.history.front()?;
+        if t0 > target {
+            return None;
+        }
+        let Some(&(t1, x1)) = self.history.get(1) else {
+            return Some(x0);
+        };
+        let w = if t1 > t0 { ((target - t0) / (t1 - t0)).min(1.0) } else { 1.0 };
+        Some(x0 + w * (x1 - x0))
+    }
+
+    // Folds the feedback into `shape` and `input`; None while it is off.
+    pub fn fold(&self, shape: Shape<f64>, input: f64) -> Option<(Shape<f64>, f64)> {
+        if !self.enabled() {
+            return None;
+        }
+        let delayed = self.delayed()?;
+        Some((Shape { leak: shape.leak + self.gain, ..shape }, input - self.gain * delayed))
+    }
+
+    pub fn current(&self, x: f64) -> f64 {
+        match self.delayed() {
+            Some(delayed) if self.enabled() => self.gain * (delayed - x),
+            _ => 0.0,
+        }
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.clock = 0.0;
+    }
+}
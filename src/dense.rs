@@ -0,0 +1,113 @@
+// Dense output over the last integration step. With a fixed dt the whole
+// number of steps per tick rarely spans exactly the model time that
+// corresponds to the host tick, so the last computed step lands up to half
+// a step off the tick boundary. The cubic Hermite interpolant through both
+// ends of the step (states and their derivatives) recovers the state at the
+// exact target time, or extrapolates it by at most half a step.
+//
+// The same interpolant locates threshold crossings inside the step, which
+// gives spike times well below one step (and one host period) of
+// resolution.
+
+// Bracketing iterations for a crossing; the Illinois variant of regula falsi
+// converges superlinearly, so this is far more than a smooth cubic needs.
+const MAX_ROOT_ITERATIONS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct DenseOutput {
+    pub enabled: bool,
+    start: [f64; 3],
+    start_rate: [f64; 3],
+    end: [f64; 3],
+    end_rate: [f64; 3],
+    t0: f64,
+    h: f64,
+    target: f64,
+}
+
+impl DenseOutput {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            start: [0.0; 3],
+            start_rate: [0.0; 3],
+            end: [0.0; 3],
+            end_rate: [0.0; 3],
+            t0: 0.0,
+            h: 0.0,
+            target: 0.0,
+        }
+    }
+
+    // Forgets the last step, e.g. after the state jumped.
+    pub fn clear(&mut self) {
+        self.h = 0.0;
+    }
+
+    pub fn record(&mut self, start: ([f64; 3], [f64; 3]), end: ([f64; 3], [f64; 3]), t0: f64, h: f64) {
+        (self.start, self.start_rate) = start;
+        (self.end, self.end_rate) = end;
+        self.t0 = t0;
+        self.h = h;
+    }
+
+    pub fn set_target(&mut self, t: f64) {
+        self.target = t;
+    }
+
+    // Interpolated state at the target time, None when disabled or when the
+    // target is not covered by the last step.
+    pub fn state(&self) -> Option<[f64; 3]> {
+        if !self.enabled || self.h <= 0.0 {
+            return None;
+        }
+        let theta = (self.target - self.t0) / self.h;
+        if !(0.0..=1.5).contains(&theta) {
+            return None;
+        }
+        Some([0, 1, 2].map(|j| self.component(j, theta)))
+    }
+
+    fn component(&self, j: usize, theta: f64) -> f64 {
+        let (t2, t3) = (theta * theta, theta * theta * theta);
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + theta;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        h00 * self.start[j] + h10 * self.h * self.start_rate[j] + h01 * self.end[j] + h11 * self.h * self.end_rate[j]
+    }
+
+    // Time at which component `j` rises through `level` within the last
+    // step, None unless the step starts below and ends at or above it.
+    pub fn crossing(&self, j: usize, level: f64) -> Option<f64> {
+        if self.h <= 0.0 || !(self.start[j] < level && self.end[j] >= level) {
+            return None;
+        }
+        let (mut lo, mut hi) = (0.0, 1.0);
+        let (mut f_lo, mut f_hi) = (self.start[j] - level, self.end[j] - level);
+        let mut side = 0;
+        for _ in 0..MAX_ROOT_ITERATIONS {
+            let theta = (lo * f_hi - hi * f_lo) / (f_hi - f_lo);
+            let f = self.component(j, theta) - level;
+            if f.abs() < 1e-12 || hi - lo < 1e-12 {
+                return Some(self.t0 + theta * self.h);
+            }
+            // Illinois: halve the stale endpoint's value when the same side
+            // moves twice, so the bracket shrinks from both ends.
+            if f < 0.0 {
+                (lo, f_lo) = (theta, f);
+                if side == -1 {
+                    f_hi /= 2.0;
+                }
+                side = -1;
+            } else {
+                (hi, f_hi) = (theta, f);
+                if side == 1 {
+                    f_lo /= 2.0;
+                }
+                side = 1;
+            }
+        }
+        Some(self.t0 + 0.5 * (lo + hi) * self.h)
+    }
+}
@@ -0,0 +1,88 @@
+// Guard against non-finite state. Every integration step is checked before
+// it is accepted; a step that produced NaN or infinity is discarded and the
+// configured policy applied, so bad parameters or a NaN on i_syn cannot
+// leave the plugin emitting garbage for the rest of the session.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    // Accept the step anyway (the pre-guard behaviour).
+    Off,
+    // Return to the configured initial conditions.
+    Reset,
+    // Keep the last finite state while time advances.
+    Hold,
+    // As Hold, with the drive outputs forced to zero on affected ticks.
+    Zero,
+}
+
+impl Recovery {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "off" | "none" => Some(Self::Off),
+            "reset" => Some(Self::Reset),
+            "hold" => Some(Self::Hold),
+            "zero" => Some(Self::Zero),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Reset => "reset",
+            Self::Hold => "hold",
+            Self::Zero => "zero",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DivergenceGuard {
+    pub policy: Recovery,
+    tripped: bool,
+    recoveries: u64,
+    last_trip: Option<f64>,
+}
+
+impl DivergenceGuard {
+    pub fn new() -> Self {
+        Self {
+            policy: Recovery::Hold,
+            tripped: false,
+            recoveries: 0,
+            last_trip: None,
+        }
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.tripped = false;
+    }
+
+    // True when a step to `state` must be discarded and recovered from.
+    pub fn check(&mut self, state: &[f64; 3], t: f64) -> bool {
+        if self.policy == Recovery::Off || state.iter().all(|v| v.is_finite()) {
+            return false;
+        }
+        self.tripped = true;
+        self.recoveries += 1;
+        self.last_trip = Some(t);
+        true
+    }
+
+    // Set on ticks in which a step was discarded.
+    pub fn tripped(&self) -> bool {
+        self.tripped
+    }
+
+    pub fn blocks_outputs(&self) -> bool {
+        self.tripped && self.policy == Recovery::Zero
+    }
+
+    pub fn recoveries(&self) -> u64 {
+        self.recoveries
+    }
+
+    pub fn last_trip(&self) -> Option<f64> {
+        self.last_trip
+    }
+}
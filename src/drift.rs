@@ -0,0 +1,39 @@
+use crate::math;
+use std::f64::consts::TAU;
+
+// Synthetic electrode artefacts for validating host-side drift compensation:
+// a constant offset, a linear ramp and a slow sinusoidal wander, all in model
+// units and added to the membrane outputs only (the dynamics are untouched).
+#[derive(Debug, Clone, Default)]
+pub struct DriftInjection {
+    pub offset: f64,
+    pub rate: f64,
+    pub amplitude: f64,
+    pub period: f64,
+    elapsed: f64,
+}
+
+impl DriftInjection {
+    pub fn enabled(&self) -> bool {
+        self.offset != 0.0 || self.rate != 0.0 || self.amplitude != 0.0
+    }
+
+    pub fn tick(&mut self, period: f64) {
+        if self.enabled() {
+            self.elapsed += period;
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        let wander = if self.period > 0.0 {
+            self.amplitude * math::sin(TAU * self.elapsed / self.period)
+        } else {
+            0.0
+        };
+        self.offset + self.rate * self.elapsed + wander
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
@@ -0,0 +1,93 @@
+// Dynamic clamp: the model acts as a virtual presynaptic neuron for a living
+// cell whose membrane potential arrives on input v_m (volts), and output
+// i_inject is the current to inject into it,
+//
+//   i_inject = g_syn s (e_syn - v_m) + g_gap (v_hr - v_m) + g_leak (e_leak - v_m)
+//
+// with s the graded synapse activation of graded.rs driven by the model's x
+// (v_half and slope in model units), and v_hr = scale x + offset the model
+// mapped onto volts for the electrical term. Currents come out in the units
+// of the conductances times volts, e.g. nS V = nA. Configured with
+//
+//   "dynamic_clamp": {"g_syn": 10.0, "e_syn": -0.08, "v_half": -0.25,
+//                     "slope": 0.1, "tau_fast": 0.0, "g_gap": 0.0, ...}
+//
+// and removed again with "dynamic_clamp": false.
+
+use crate::graded::GradedSynapse;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct DynamicClamp {
+    synapse: GradedSynapse,
+    g_gap: f64,
+    scale: f64,
+    offset: f64,
+    g_leak: f64,
+    e_leak: f64,
+    i_inject: f64,
+}
+
+impl DynamicClamp {
+    pub fn new() -> Self {
+        let mut synapse = GradedSynapse::default();
+        synapse.e_rev = -0.08;
+        Self {
+            synapse,
+            g_gap: 0.0,
+            scale: 0.03,
+            offset: -0.055,
+            g_leak: 0.0,
+            e_leak: -0.06,
+            i_inject: 0.0,
+        }
+    }
+
+    pub fn configure(&mut self, config: &Value) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        let synapse = &mut self.synapse;
+        synapse.g_max = get("g_syn", synapse.g_max);
+        synapse.e_rev = get("e_syn", synapse.e_rev);
+        synapse.v_half = get("v_half", synapse.v_half);
+        synapse.slope = get("slope", synapse.slope);
+        synapse.tau_fast = get("tau_fast", synapse.tau_fast).max(0.0);
+        synapse.tau_slow = get("tau_slow", synapse.tau_slow).max(0.0);
+        synapse.slow_fraction = get("slow_fraction", synapse.slow_fraction).clamp(0.0, 1.0);
+        self.g_gap = get("g_gap", self.g_gap);
+        self.scale = get("scale", self.scale);
+        self.offset = get("offset", self.offset);
+        self.g_leak = get("g_leak", self.g_leak);
+        self.e_leak = get("e_leak", self.e_leak);
+    }
+
+    // Updates i_inject after a tick of `period` seconds that left the model
+    // at `x` while the living cell sat at `v_m`.
+    pub fn tick(&mut self, x: f64, v_m: f64, period: f64) {
+        self.synapse.tick(x, period);
+        let v_hr = self.scale * x + self.offset;
+        // The synapse computes the current leaving the cell, I = g s (v - e).
+        self.i_inject = -self.synapse.current(v_m) + self.g_gap * (v_hr - v_m) + self.g_leak * (self.e_leak - v_m);
+    }
+
+    pub fn i_inject(&self) -> f64 {
+        self.i_inject
+    }
+
+    pub fn reset(&mut self) {
+        self.synapse.reset();
+        self.i_inject = 0.0;
+    }
+
+    pub fn stats(&self) -> Value {
+        serde_json::json!({
+            "i_inject": self.i_inject,
+            "activation": self.synapse.activation(),
+            "g_syn": self.synapse.g_max,
+            "e_syn": self.synapse.e_rev,
+            "g_gap": self.g_gap,
+            "g_leak": self.g_leak
+        })
+    }
+}
@@ -0,0 +1,69 @@
+// Energy drift monitor. HR has no conserved quantity, but the fast
+// subsystem has a natural Lyapunov-like energy: with y relaxed onto its
+// nullcline y = c - d x^2, dx/dt = -dV/dx for
+//
+//   V(x; z) = a x^4/4 + (d - b) x^3/3 - c x + (vh z - e) x
+//
+// and the distance of y from the nullcline adds a kinetic-like term,
+// E = V + (y - c + d x^2)^2 / 2. The exact flow changes E at the rate
+// grad(E) . f, so the change the solver produces minus the trapezoidal
+// integral of that rate accumulates the numerical drift. Unlike the z
+// balance it involves every variable and the cubic nonlinearity, so a dt
+// that corrupts the spikes shows up here first.
+
+use crate::field::{derivatives, Params, Shape};
+use crate::math;
+
+pub fn energy(vars: [f64; 3], p: &Params<f64>) -> f64 {
+    let [x, y, z] = vars;
+    let Shape { a, b, c, d, .. } = p.shape();
+    let w = y - c + d * x * x;
+    a * math::powi(x, 4) / 4.0 + (d - b) * math::powi(x, 3) / 3.0 - c * x + (p.vh * z - p.e) * x + 0.5 * w * w
+}
+
+// dE/dt along the exact flow.
+pub fn energy_rate(vars: [f64; 3], p: &Params<f64>) -> f64 {
+    let [x, y, z] = vars;
+    let Shape { a, b, c, d, .. } = p.shape();
+    let w = y - c + d * x * x;
+    let [xdot, ydot, zdot] = derivatives(vars, p);
+    let de_dx = a * math::powi(x, 3) + (d - b) * x * x - c + p.vh * z - p.e + 2.0 * d * x * w;
+    de_dx * xdot + w * ydot + p.vh * x * zdot
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EnergyMonitor {
+    pub enabled: bool,
+    reference: f64,
+    integral: f64,
+    rate: f64,
+    drift: f64,
+    max_drift: f64,
+}
+
+impl EnergyMonitor {
+    // Restarts the drift from energy `e` with dE/dt = rate.
+    pub fn rebase(&mut self, e: f64, rate: f64) {
+        self.reference = e;
+        self.integral = 0.0;
+        self.rate = rate;
+        self.drift = 0.0;
+    }
+
+    // Called after each substep of size dt with the new energy and its rate.
+    pub fn step(&mut self, e: f64, rate: f64, dt: f64) {
+        self.integral += 0.5 * (self.rate + rate) * dt;
+        self.rate = rate;
+        self.drift = e - self.reference - self.integral;
+        self.max_drift = self.max_drift.max(self.drift.abs());
+    }
+
+    pub fn drift(&self) -> f64 {
+        self.drift
+    }
+
+    // Largest |drift| seen since the monitor was created.
+    pub fn max_drift(&self) -> f64 {
+        self.max_drift
+    }
+}
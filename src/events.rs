@@ -0,0 +1,136 @@
+use std::ffi::c_void;
+
+pub type EventCallback = extern "C" fn(user_data: *mut c_void, kind: u32, value: f64);
+
+// Values passed as `kind` to the host callback.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    // value: model time of the upward threshold crossing
+    Spike = 0,
+    // value: model time of the first spike after a silent gap
+    BurstOnset = 1,
+    // value: model time at which the state stopped being finite
+    NonFinite = 2,
+    // value: newly selected dt
+    CalibrationChanged = 3,
+    // value: model time at which the quiescence watchdog kicked or reset
+    WatchdogIntervention = 4,
+    // value: the total input current that tripped the safety interlock
+    InterlockFault = 5,
+    // value: model time at which a burst-locked config change took effect
+    ScheduledConfigApplied = 6,
+}
+
+#[derive(Debug)]
+pub struct EventSink {
+    callback: Option<EventCallback>,
+    user_data: *mut c_void,
+    pub spike_threshold: f64,
+    pub burst_gap: f64,
+    above: bool,
+    last_spike: Option<f64>,
+    non_finite: bool,
+}
+
+impl EventSink {
+    pub fn new() -> Self {
+        Self {
+            callback: None,
+            user_data: std::ptr::null_mut(),
+            spike_threshold: 0.5,
+            burst_gap: 50.0,
+            above: false,
+            last_spike: None,
+            non_finite: false,
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: Option<EventCallback>, user_data: *mut c_void) {
+        self.callback = callback;
+        self.user_data = user_data;
+    }
+
+    pub fn emit(&self, kind: EventKind, value: f64) {
+        if let Some(callback) = self.callback {
+            callback(self.user_data, kind as u32, value);
+        }
+    }
+
+    // Called once per integration substep with the new membrane variable.
+    // Returns Spike or BurstOnset when a spike starts on this substep.
+    pub fn observe(&mut self, x: f64, finite: bool, t: f64) -> Option<EventKind> {
+        if !finite {
+            if !self.non_finite {
+                self.non_finite = true;
+                self.emit(EventKind::NonFinite, t);
+            }
+            return None;
+        }
+        self.non_finite = false;
+
+        let above = x >= self.spike_threshold;
+        let mut detected = None;
+        if above && !self.above {
+            let onset = self.last_spike.is_none_or(|last| (t - last).abs() >= self.burst_gap);
+            if onset {
+                self.emit(EventKind::BurstOnset, t);
+            }
+            self.emit(EventKind::Spike, t);
+            self.last_spike = Some(t);
+            detected = Some(if onset {
+                EventKind::BurstOnset
+            } else {
+                EventKind::Spike
+            });
+        }
+        self.above = above;
+        detected
+    }
+}
+
+// Names of the event outputs, indexed like EventLatch's flags.
+pub const EVENT_OUTPUTS: [&str; 2] = ["spike", "burst_onset"];
+
+// Per-tick view of spike events for the event outputs. A latched output
+// reports an event from any substep of the tick; an unlatched one only
+// reports events on the final substep, i.e. the tick boundary sample.
+#[derive(Debug, Clone)]
+pub struct EventLatch {
+    pub latch: [bool; 2],
+    in_tick: [bool; 2],
+    at_boundary: [bool; 2],
+}
+
+impl EventLatch {
+    pub fn new() -> Self {
+        Self {
+            latch: [true; 2],
+            in_tick: [false; 2],
+            at_boundary: [false; 2],
+        }
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.in_tick = [false; 2];
+    }
+
+    // Called once per substep with the event detected on it, if any.
+    pub fn observe(&mut self, detected: Option<EventKind>) {
+        self.at_boundary = [
+            detected.is_some(),
+            detected == Some(EventKind::BurstOnset),
+        ];
+        for (seen, now) in self.in_tick.iter_mut().zip(self.at_boundary) {
+            *seen |= now;
+        }
+    }
+
+    pub fn value(&self, index: usize) -> bool {
+        if self.latch[index] {
+            self.in_tick[index]
+        } else {
+            self.at_boundary[index]
+        }
+    }
+}
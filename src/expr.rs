@@ -0,0 +1,239 @@
+// Small arithmetic expression engine for user-defined outputs, e.g.
+// "x - 0.5*z" or "tanh(2*(x - vh))". Supports + - * / ^, unary minus,
+// parentheses, numeric literals, named variables and a handful of functions.
+// Variables are resolved to slot indices at compile time so evaluation is a
+// plain tree walk without lookups or allocation.
+
+use crate::math;
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Const(f64),
+    Var(usize),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Func {
+    Sin,
+    Cos,
+    Tanh,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+    Min,
+    Max,
+}
+
+impl Func {
+    fn parse(name: &str) -> Option<(Self, usize)> {
+        match name {
+            "sin" => Some((Self::Sin, 1)),
+            "cos" => Some((Self::Cos, 1)),
+            "tanh" => Some((Self::Tanh, 1)),
+            "exp" => Some((Self::Exp, 1)),
+            "ln" => Some((Self::Ln, 1)),
+            "sqrt" => Some((Self::Sqrt, 1)),
+            "abs" => Some((Self::Abs, 1)),
+            "min" => Some((Self::Min, 2)),
+            "max" => Some((Self::Max, 2)),
+            _ => None,
+        }
+    }
+}
+
+impl Expr {
+    // `vars` names the slots later passed to `eval`, in order.
+    pub fn compile(source: &str, vars: &[&str]) -> Result<Self, String> {
+        let mut parser = Parser {
+            src: source.as_bytes(),
+            pos: 0,
+            vars,
+        };
+        let expr = parser.sum()?;
+        parser.skip_ws();
+        if parser.pos != parser.src.len() {
+            return Err(format!("unexpected input at offset {}", parser.pos));
+        }
+        Ok(expr)
+    }
+
+    pub fn eval(&self, values: &[f64]) -> f64 {
+        match self {
+            Self::Const(v) => *v,
+            Self::Var(slot) => values[*slot],
+            Self::Neg(inner) => -inner.eval(values),
+            Self::Binary(op, lhs, rhs) => {
+                let (a, b) = (lhs.eval(values), rhs.eval(values));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Pow => math::powf(a, b),
+                }
+            }
+            Self::Call(func, args) => {
+                let a = args[0].eval(values);
+                match func {
+                    Func::Sin => math::sin(a),
+                    Func::Cos => math::cos(a),
+                    Func::Tanh => math::tanh(a),
+                    Func::Exp => math::exp(a),
+                    Func::Ln => math::ln(a),
+                    Func::Sqrt => a.sqrt(),
+                    Func::Abs => a.abs(),
+                    Func::Min => a.min(args[1].eval(values)),
+                    Func::Max => a.max(args[1].eval(values)),
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    vars: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_ws();
+        if self.src.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = if self.eat(b'+') {
+                BinOp::Add
+            } else if self.eat(b'-') {
+                BinOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat(b'*') {
+                BinOp::Mul
+            } else if self.eat(b'/') {
+                BinOp::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(b'-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    // power := atom ('^' unary)?, right-associative and binding tighter
+    // than unary minus on its left: -x^2 == -(x^2).
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.eat(b'^') {
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        if self.eat(b'(') {
+            let inner = self.sum()?;
+            if !self.eat(b')') {
+                return Err(format!("expected ')' at offset {}", self.pos));
+            }
+            return Ok(inner);
+        }
+        self.skip_ws();
+        let start = self.pos;
+        match self.src.get(self.pos) {
+            Some(c) if c.is_ascii_digit() || *c == b'.' => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == b'.') {
+                    self.pos += 1;
+                }
+                if self.src.get(self.pos).is_some_and(|c| *c == b'e' || *c == b'E') {
+                    self.pos += 1;
+                    if self.src.get(self.pos).is_some_and(|c| *c == b'+' || *c == b'-') {
+                        self.pos += 1;
+                    }
+                    while self.src.get(self.pos).is_some_and(u8::is_ascii_digit) {
+                        self.pos += 1;
+                    }
+                }
+                let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                text.parse()
+                    .map(Expr::Const)
+                    .map_err(|_| format!("invalid number '{text}'"))
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') {
+                    self.pos += 1;
+                }
+                let name = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                if let Some((func, arity)) = Func::parse(name) {
+                    return self.call(func, arity, name);
+                }
+                match self.vars.iter().position(|var| *var == name) {
+                    Some(slot) => Ok(Expr::Var(slot)),
+                    None => Err(format!("unknown variable '{name}'")),
+                }
+            }
+            _ => Err(format!("unexpected input at offset {}", self.pos)),
+        }
+    }
+
+    fn call(&mut self, func: Func, arity: usize, name: &str) -> Result<Expr, String> {
+        if !self.eat(b'(') {
+            return Err(format!("expected '(' after {name}"));
+        }
+        let mut args = vec![self.sum()?];
+        while self.eat(b',') {
+            args.push(self.sum()?);
+        }
+        if !self.eat(b')') {
+            return Err(format!("expected ')' at offset {}", self.pos));
+        }
+        if args.len() != arity {
+            return Err(format!("{name} takes {arity} argument(s)"));
+        }
+        Ok(Expr::Call(func, args))
+    }
+}
@@ -0,0 +1,207 @@
+// Hindmarsh-Rose vector field, generic over the scalar type so that dual
+// numbers (see the `autodiff` feature), fixed-point, double-double and SIMD
+// lanes can be pushed through it.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+pub trait Scalar:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    // Converts a model constant, rounding it for narrower types.
+    fn lit(value: f64) -> Self;
+}
+
+impl Scalar for f64 {
+    fn lit(value: f64) -> Self {
+        value
+    }
+}
+
+#[cfg(feature = "autodiff")]
+impl Scalar for num_dual::Dual64 {
+    fn lit(value: f64) -> Self {
+        Self::from(value)
+    }
+}
+
+// Four f64 lanes, see simd.rs.
+#[cfg(feature = "simd")]
+impl Scalar for wide::f64x4 {
+    fn lit(value: f64) -> Self {
+        Self::splat(value)
+    }
+}
+
+// Plain floating-point scalars, as needed by the Newton solver.
+pub trait Float: Scalar + Div<Output = Self> + PartialOrd {
+    const EPSILON: Self;
+
+    fn abs(self) -> Self;
+}
+
+impl Float for f64 {
+    const EPSILON: Self = f64::EPSILON;
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params<T> {
+    pub e: T,
+    pub mu: T,
+    pub s: T,
+    pub vh: T,
+    pub input: T,
+    // Set only by the canonical formulation, see formulation.rs; None is
+    // the textbook shape, which derivatives evaluates with literals.
+    pub shape: Option<Shape<T>>,
+}
+
+// Cubic and quadratic coefficients of the fast subsystem, a conductance
+// leak drawing x toward 0 (zero in the textbook model; conductance inputs
+// set it, see conductance.rs) and the z reversal potential.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shape<T> {
+    pub a: T,
+    pub b: T,
+    pub c: T,
+    pub d: T,
+    pub leak: T,
+    pub x_r: T,
+}
+
+impl Shape<f64> {
+    pub const TEXTBOOK: Self = Self {
+        a: 1.0,
+        b: 3.0,
+        c: 1.0,
+        d: 5.0,
+        leak: 0.0,
+        x_r: -1.6,
+    };
+}
+
+impl Params<f64> {
+    // Defaults of a fresh instance.
+    pub const DEFAULT: Self = Self {
+        e: 3.25,
+        mu: 0.006,
+        s: 4.0,
+        vh: 1.0,
+        input: 0.0,
+        shape: None,
+    };
+}
+
+impl<T: Copy> Params<T> {
+    // Converts every parameter, e.g. into a narrower scalar.
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Params<U> {
+        Params {
+            e: f(self.e),
+            mu: f(self.mu),
+            s: f(self.s),
+            vh: f(self.vh),
+            input: f(self.input),
+            shape: self.shape.map(|shape| Shape {
+                a: f(shape.a),
+                b: f(shape.b),
+                c: f(shape.c),
+                d: f(shape.d),
+                leak: f(shape.leak),
+                x_r: f(shape.x_r),
+            }),
+        }
+    }
+}
+
+impl<T: Scalar> Params<T> {
+    pub fn shape(&self) -> Shape<T> {
+        self.shape.unwrap_or(Shape {
+            a: T::lit(Shape::TEXTBOOK.a),
+            b: T::lit(Shape::TEXTBOOK.b),
+            c: T::lit(Shape::TEXTBOOK.c),
+            d: T::lit(Shape::TEXTBOOK.d),
+            leak: T::lit(Shape::TEXTBOOK.leak),
+            x_r: T::lit(Shape::TEXTBOOK.x_r),
+        })
+    }
+}
+
+// Parameter products are deliberately left unfolded: vh * z is shared
+// between xdot and zdot, so precomputing mu * vh, mu * s and mu * s * 1.6
+// saves no operation, and a folded variant measured 10-20% slower per RK4
+// step in benches/process.rs. For the same reason the textbook shape keeps
+// its own branch with literal coefficients: reading them from Params
+// measured about 12% slower per Euler and RK4 step. The general branch
+// rounds identically for the textbook values.
+#[inline(always)]
+pub fn derivatives<T: Scalar>(vars: [T; 3], p: &Params<T>) -> [T; 3] {
+    let c = T::lit;
+    let [x, y, z] = vars;
+    let Some(shape) = &p.shape else {
+        let xdot = y + c(3.0) * (x * x) - (x * x * x) - p.vh * z + p.e - p.input;
+        let ydot = c(1.0) - c(5.0) * (x * x) - y;
+        let zdot = p.mu * (-p.vh * z + p.s * (x + c(1.6)));
+        return [xdot, ydot, zdot];
+    };
+    let xdot = y + shape.b * (x * x) - (x * x) * (shape.a * x) - shape.leak * x - p.vh * z + p.e - p.input;
+    let ydot = shape.c - shape.d * (x * x) - y;
+    let zdot = p.mu * (-p.vh * z + p.s * (x - shape.x_r));
+    [xdot, ydot, zdot]
+}
+
+// d(xdot, ydot, zdot) / d(x, y, z).
+pub fn jacobian<T: Scalar>(vars: [T; 3], p: &Params<T>) -> [[T; 3]; 3] {
+    let c = T::lit;
+    let x = vars[0];
+    let Shape { a, b, d, leak, .. } = p.shape();
+    [
+        [c(2.0) * b * x - c(3.0) * a * (x * x) - leak, c(1.0), -p.vh],
+        [-(c(2.0) * d) * x, -c(1.0), c(0.0)],
+        [p.mu * p.s, c(0.0), -p.mu * p.vh],
+    ]
+}
+
+// Individual terms of xdot, in CURRENT_TERMS order; they sum to
+// derivatives(..)[0].
+pub const CURRENT_TERMS: [&str; 5] = ["I_cubic", "I_y", "I_z", "I_e", "I_syn"];
+
+pub fn current_terms(vars: [f64; 3], p: &Params<f64>) -> [f64; 5] {
+    let [x, y, z] = vars;
+    let Shape { a, b, leak, .. } = p.shape();
+    [b * (x * x) - (x * x) * (a * x), y, -p.vh * z, p.e, -p.input - leak * x]
+}
+
+// Exact partial derivatives of (xdot, ydot, zdot) with respect to
+// (e, mu, s, vh), obtained by forward-mode differentiation.
+#[cfg(feature = "autodiff")]
+pub fn parameter_gradients(vars: [f64; 3], p: &Params<f64>) -> [[f64; 4]; 3] {
+    use num_dual::Dual64;
+
+    let lift = |v: f64| Dual64::from(v);
+    let state = vars.map(lift);
+    let mut out = [[0.0; 4]; 3];
+    for param in 0..4 {
+        let seed = |i: usize, v: f64| {
+            if i == param {
+                Dual64::new(v, 1.0)
+            } else {
+                lift(v)
+            }
+        };
+        let dual = Params {
+            e: seed(0, p.e),
+            mu: seed(1, p.mu),
+            s: seed(2, p.s),
+            vh: seed(3, p.vh),
+            ..p.map(lift)
+        };
+        let d = derivatives(state, &dual);
+        for (row, value) in out.iter_mut().zip(d.iter()) {
+            row[param] = value.eps;
+        }
+    }
+    out
+}
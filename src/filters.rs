@@ -0,0 +1,89 @@
+use std::f64::consts::TAU;
+
+// Single-pole high-pass (RC) filter, sampled once per process tick.
+#[derive(Debug, Clone, Default)]
+pub struct HighPass {
+    pub cutoff: f64,
+    prev_in: Option<f64>,
+    prev_out: f64,
+}
+
+impl HighPass {
+    pub fn apply(&mut self, input: f64, period: f64) -> f64 {
+        let Some(prev_in) = self.prev_in else {
+            self.prev_in = Some(input);
+            return 0.0;
+        };
+        let rc = 1.0 / (TAU * self.cutoff);
+        let alpha = rc / (rc + period);
+        self.prev_out = alpha * (self.prev_out + input - prev_in);
+        self.prev_in = Some(input);
+        self.prev_out
+    }
+
+    pub fn output(&self) -> f64 {
+        self.prev_out
+    }
+
+    pub fn reset(&mut self) {
+        self.prev_in = None;
+        self.prev_out = 0.0;
+    }
+}
+
+pub const MAX_MEDIAN_WINDOW: usize = 15;
+
+// Running median over the last `window` samples; a window of 0 or 1 passes
+// samples through unchanged. Storage is fixed so filtering never allocates.
+#[derive(Debug, Clone)]
+pub struct Median {
+    window: usize,
+    buf: [f64; MAX_MEDIAN_WINDOW],
+    len: usize,
+    next: usize,
+}
+
+impl Default for Median {
+    fn default() -> Self {
+        Self {
+            window: 0,
+            buf: [0.0; MAX_MEDIAN_WINDOW],
+            len: 0,
+            next: 0,
+        }
+    }
+}
+
+impl Median {
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn set_window(&mut self, window: usize) {
+        let window = window.min(MAX_MEDIAN_WINDOW);
+        if window != self.window {
+            self.window = window;
+            self.len = 0;
+            self.next = 0;
+        }
+    }
+
+    pub fn apply(&mut self, sample: f64) -> f64 {
+        if self.window <= 1 {
+            return sample;
+        }
+        self.buf[self.next] = sample;
+        self.next = (self.next + 1) % self.window;
+        self.len = (self.len + 1).min(self.window);
+
+        let mut sorted = self.buf;
+        let sorted = &mut sorted[..self.len];
+        sorted.sort_unstable_by(f64::total_cmp);
+        let mid = self.len / 2;
+        if self.len % 2 == 1 {
+            sorted[mid]
+        } else {
+            0.5 * (sorted[mid - 1] + sorted[mid])
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Parameter sets a model's core is configured with, see model.rs: "hr3"
+// takes the native set, "hr3_canonical" and "hr4" the canonical one. The
+// native form is the one this plugin has always used, configured by
+// (e, mu, s, vh):
+//
+//   dx/dt = y + 3x^2 - x^3 - vh z + e - I_syn
+//   dy/dt = 1 - 5x^2 - y
+//   dz/dt = mu (s (x + 1.6) - vh z)
+//
+// The canonical formulation takes the textbook parameters instead,
+//
+//   dx/dt = y - a x^3 + b x^2 - z + I - I_syn
+//   dy/dt = c - d x^2 - y
+//   dz/dt = r (s (x - x_r) - z)
+//
+// so published sets can be entered as printed. Both are one vector field:
+// canonical is native with vh = 1, mu = r and e = I, and a native set is
+// canonical with r = mu vh and z scaled by vh, which `canonical` reports for
+// whatever is configured. Switching a running instance from a native model
+// to a canonical one converts its set and z the same way.
+
+use crate::field::{Params, Shape};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formulation {
+    Native,
+    Canonical,
+}
+
+impl Formulation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Canonical => "canonical",
+        }
+    }
+
+    // Reads this set's keys from `config` into the native-equivalent `core`;
+    // absent keys keep their value.
+    pub fn configure(self, config: &Value, core: &mut Params<f64>) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        match self {
+            Self::Native => {
+                core.e = get("e", core.e);
+                core.mu = get("mu", core.mu);
+                core.s = get("s", core.s);
+                core.vh = get("vh", core.vh);
+            }
+            Self::Canonical => {
+                let shape = core.shape.unwrap_or(Shape::TEXTBOOK);
+                core.shape = Some(Shape {
+                    a: get("a", shape.a),
+                    b: get("b", shape.b),
+                    c: get("c", shape.c),
+                    d: get("d", shape.d),
+                    x_r: get("x_r", shape.x_r),
+                    ..shape
+                });
+                core.mu = get("r", core.mu);
+                core.s = get("s", core.s);
+                core.e = get("I", core.e);
+                core.vh = 1.0;
+            }
+        }
+    }
+}
+
+// Textbook parameters and z equivalent to the native set `p` at state z.
+pub fn canonical(p: &Params<f64>, z: f64) -> Value {
+    let shape = p.shape();
+    serde_json::json!({
+        "a": shape.a,
+        "b": shape.b,
+        "c": shape.c,
+        "d": shape.d,
+        "r": p.mu * p.vh,
+        "s": p.s,
+        "x_r": shape.x_r,
+        "I": p.e,
+        "z": p.vh * z
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::derivatives;
+
+    fn from_canonical(set: &Value) -> Params<f64> {
+        let mut core = Params::DEFAULT;
+        Formulation::Canonical.configure(set, &mut core);
+        core
+    }
+
+    const STATES: [[f64; 3]; 3] = [[-0.9013, -3.1594, 3.24782], [1.2, -1.0, 2.9], [-1.6, -11.8, 3.6]];
+
+    // The reported canonical set drives the same field once z is taken in
+    // units of vh z.
+    #[test]
+    fn canonical_set_reproduces_the_native_field() {
+        let native = Params { e: 3.1, mu: 0.004, s: 3.8, vh: 1.7, ..Params::DEFAULT };
+        for [x, y, z] in STATES {
+            let set = canonical(&native, z);
+            let zc = set["z"].as_f64().unwrap();
+            let expected = derivatives([x, y, z], &native);
+            let mapped = derivatives([x, y, zc], &from_canonical(&set));
+            assert!((mapped[0] - expected[0]).abs() < 1e-12);
+            assert!((mapped[1] - expected[1]).abs() < 1e-12);
+            assert!((mapped[2] - native.vh * expected[2]).abs() < 1e-12);
+        }
+    }
+
+    // Canonical to native-equivalent and back reports the set as entered.
+    #[test]
+    fn canonical_set_round_trips() {
+        let set = serde_json::json!({
+            "a": 1.0, "b": 2.7, "c": 1.2, "d": 5.4, "r": 0.005, "s": 4.2, "x_r": -1.56, "I": 2.9, "z": 3.1
+        });
+        assert_eq!(canonical(&from_canonical(&set), 3.1), set);
+        let native = Params::DEFAULT;
+        let back = from_canonical(&canonical(&native, 3.0));
+        assert_eq!(back.shape(), native.shape());
+        assert_eq!((back.e, back.mu, back.s), (native.e, native.mu, native.s));
+    }
+}
@@ -0,0 +1,80 @@
+// Graded chemical synapse driven by a presynaptic potential on input
+// v_pre_syn, in the form RTHybrid-style hybrid circuits use. The activation
+// relaxes toward
+//
+//   s_inf = 1 / (1 + exp((v_half - v_pre) / slope))
+//
+// through a fast and a slow first-order component with time constants
+// tau_fast and tau_slow in host seconds (0 makes a component instantaneous),
+// and the current
+//
+//   I = g_max ((1 - slow_fraction) s_fast + slow_fraction s_slow) (x - e_rev)
+//
+// enters dx/dt like i_syn, so e_rev below the cell's potential inhibits.
+// v_half and slope are in the units of v_pre_syn, e_rev in model units.
+
+use crate::math;
+
+#[derive(Debug, Clone)]
+pub struct GradedSynapse {
+    pub g_max: f64,
+    pub e_rev: f64,
+    pub v_half: f64,
+    pub slope: f64,
+    pub tau_fast: f64,
+    pub tau_slow: f64,
+    pub slow_fraction: f64,
+    s_fast: f64,
+    s_slow: f64,
+}
+
+impl Default for GradedSynapse {
+    fn default() -> Self {
+        Self {
+            g_max: 0.0,
+            e_rev: -2.0,
+            v_half: -0.25,
+            slope: 0.1,
+            tau_fast: 0.0,
+            tau_slow: 0.0,
+            slow_fraction: 0.0,
+            s_fast: 0.0,
+            s_slow: 0.0,
+        }
+    }
+}
+
+impl GradedSynapse {
+    pub fn enabled(&self) -> bool {
+        self.g_max != 0.0
+    }
+
+    // Relaxes both components over `period` seconds at presynaptic `v_pre`.
+    pub fn tick(&mut self, v_pre: f64, period: f64) {
+        let s_inf = if self.slope != 0.0 {
+            1.0 / (1.0 + math::exp((self.v_half - v_pre) / self.slope))
+        } else if v_pre >= self.v_half {
+            1.0
+        } else {
+            0.0
+        };
+        let relax = |s: &mut f64, tau: f64| {
+            *s += if tau > 0.0 { (s_inf - *s) * (1.0 - math::exp(-period / tau)) } else { s_inf - *s };
+        };
+        relax(&mut self.s_fast, self.tau_fast);
+        relax(&mut self.s_slow, self.tau_slow);
+    }
+
+    pub fn activation(&self) -> f64 {
+        (1.0 - self.slow_fraction) * self.s_fast + self.slow_fraction * self.s_slow
+    }
+
+    pub fn current(&self, x: f64) -> f64 {
+        self.g_max * self.activation() * (x - self.e_rev)
+    }
+
+    pub fn reset(&mut self) {
+        self.s_fast = 0.0;
+        self.s_slow = 0.0;
+    }
+}
@@ -0,0 +1,161 @@
+// Half-center oscillator: the instance's own cell (neuron 1) and a second HR
+// cell in this module (neuron 2), each inhibiting the other through a graded
+// chemical synapse
+//
+//   I_k = g (x_k - reversal) / (1 + exp(-slope (x_j - threshold)))
+//
+// which enters dx_k/dt like i_syn. Both currents are computed from the
+// states at the start of the tick and held for the tick, so the two cells
+// are treated alike whatever solver neuron 1 uses. Configured with
+//
+//   "half_center": {"g": 0.1, "reversal": -2.0, "threshold": -0.25,
+//                   "slope": 10.0, "e": 3.0, "x": -1.2, "y": -6.0, "z": 3.0}
+//
+// where e, if given, detunes neuron 2 from neuron 1 (which it otherwise
+// copies), and removed again with "half_center": false.
+
+use crate::field::{derivatives, jacobian, Params};
+use crate::math;
+use crate::rk::Integrator;
+use serde_json::Value;
+
+// Largest internal step of the cells integrated here, in population.rs and
+// simd.rs, in model time.
+pub const MAX_STEP: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Synapse {
+    pub g: f64,
+    pub reversal: f64,
+    pub threshold: f64,
+    pub slope: f64,
+}
+
+impl Synapse {
+    // Current into a cell at `post` from a cell at `pre`.
+    pub fn current(&self, pre: f64, post: f64) -> f64 {
+        self.g * (post - self.reversal) / (1.0 + math::exp(-self.slope * (pre - self.threshold)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HalfCenter {
+    synapse: Synapse,
+    e: Option<f64>,
+    initial: [f64; 3],
+    state: [f64; 3],
+    currents: [f64; 2],
+}
+
+impl HalfCenter {
+    pub fn new() -> Self {
+        // Off neuron 1's default initial condition, so the pair starts
+        // out of phase.
+        let initial = [-1.2, -6.0, 3.0];
+        Self {
+            synapse: Synapse {
+                g: 0.1,
+                reversal: -2.0,
+                threshold: -0.25,
+                slope: 10.0,
+            },
+            e: None,
+            initial,
+            state: initial,
+            currents: [0.0; 2],
+        }
+    }
+
+    pub fn configure(&mut self, config: &Value) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        self.synapse = Synapse {
+            g: get("g", self.synapse.g).max(0.0),
+            reversal: get("reversal", self.synapse.reversal),
+            threshold: get("threshold", self.synapse.threshold),
+            slope: get("slope", self.synapse.slope),
+        };
+        if let Some(e) = config.get("e") {
+            self.e = e.as_f64();
+        }
+        let initial = [get("x", self.initial[0]), get("y", self.initial[1]), get("z", self.initial[2])];
+        if initial != self.initial {
+            self.initial = initial;
+            self.reset();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.initial;
+        self.currents = [0.0; 2];
+    }
+
+    // Computes this tick's synaptic currents with neuron 1 at `x1` and
+    // returns the one into neuron 1.
+    pub fn begin_tick(&mut self, x1: f64) -> f64 {
+        let x2 = self.state[0];
+        self.currents = [self.synapse.current(x2, x1), self.synapse.current(x1, x2)];
+        self.currents[0]
+    }
+
+    // Advances neuron 2 by the signed model time `span` with neuron 1's
+    // parameters `params` and its own external current `i_syn`.
+    pub fn advance(&mut self, params: &Params<f64>, span: f64, i_syn: f64) {
+        let params = Params {
+            e: self.e.unwrap_or(params.e),
+            input: i_syn + self.currents[1],
+            ..*params
+        };
+        self.state = advance_cell(self.state, &params, span);
+    }
+
+    // Maximal conductance of the synapse, shared by both directions.
+    pub fn g(&self) -> f64 {
+        self.synapse.g
+    }
+
+    pub fn set_g(&mut self, g: f64) {
+        self.synapse.g = g.max(0.0);
+    }
+
+    pub fn x(&self) -> f64 {
+        self.state[0]
+    }
+
+    pub fn state(&self) -> [f64; 3] {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: [f64; 3]) {
+        self.state = state;
+    }
+
+    pub fn stats(&self) -> Value {
+        serde_json::json!({
+            "state": self.state,
+            "currents": self.currents,
+            "e": self.e,
+            "synapse": {
+                "g": self.synapse.g,
+                "reversal": self.synapse.reversal,
+                "threshold": self.synapse.threshold,
+                "slope": self.synapse.slope
+            }
+        })
+    }
+}
+
+// Integrates a follower cell over the signed model time `span` in RK4 steps
+// of at most MAX_STEP.
+pub fn advance_cell(mut state: [f64; 3], params: &Params<f64>, span: f64) -> [f64; 3] {
+    if span == 0.0 {
+        return state;
+    }
+    let steps = (span.abs() / MAX_STEP).ceil().max(1.0) as usize;
+    let h = span / steps as f64;
+    for _ in 0..steps {
+        state = Integrator::Rk4.step(state, h, |v| derivatives(*v, params), |v| jacobian(*v, params));
+    }
+    state
+}
@@ -0,0 +1,110 @@
+// Four-variable Hindmarsh-Rose model (Pinto et al. 2000), selected with
+// "model": "hr4". A second, slower variable u feeds back on the recovery
+// variable and follows it:
+//
+//   dy/dt = c - d x^2 - y - g u
+//   du/dt = nu (r (y + y_r) - k u)
+//
+// The other equations are unchanged, so the core takes the canonical
+// parameter set, in which the published values are printed, plus the u_*
+// keys. nu is only about 6.7 times smaller than mu and u follows y, which
+// moves on the fast time scale, so u is integrated with x, y and z in every
+// substep, see model.rs.
+
+use crate::field::{self, Params, Shape};
+use crate::formulation::Formulation;
+use crate::model::{NeuronModel, MAX_STATE};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecondSlow {
+    pub g: f64,
+    pub nu: f64,
+    pub k: f64,
+    pub r: f64,
+    pub y_r: f64,
+    pub u: f64,
+    // Initial u, restored on reset.
+    pub u0: f64,
+}
+
+impl SecondSlow {
+    pub const PINTO: Self = Self { g: 0.0278, nu: 0.0009, k: 0.9573, r: 3.0, y_r: 1.619, u: 0.0, u0: 0.0 };
+}
+
+impl NeuronModel for SecondSlow {
+    fn name(&self) -> &'static str {
+        "hr4"
+    }
+
+    fn formulation(&self) -> Formulation {
+        Formulation::Canonical
+    }
+
+    fn state_names(&self) -> &'static [&'static str] {
+        &["u"]
+    }
+
+    fn state(&self) -> &[f64] {
+        std::slice::from_ref(&self.u)
+    }
+
+    fn state_mut(&mut self) -> &mut [f64] {
+        std::slice::from_mut(&mut self.u)
+    }
+
+    fn field(&self, state: [f64; MAX_STATE], p: &Params<f64>) -> [f64; MAX_STATE] {
+        let [x, y, z, u] = state;
+        let shape = p.shape();
+        let core = Params { shape: Some(Shape { c: shape.c - self.g * u, ..shape }), ..*p };
+        let [dx, dy, dz] = field::derivatives([x, y, z], &core);
+        [dx, dy, dz, self.nu * (self.r * (y + self.y_r) - self.k * u)]
+    }
+
+    fn jacobian(&self, state: [f64; MAX_STATE], p: &Params<f64>) -> [[f64; MAX_STATE]; MAX_STATE] {
+        let [x, y, z, _] = state;
+        let [dx, dy, dz] = field::jacobian([x, y, z], p);
+        [
+            [dx[0], dx[1], dx[2], 0.0],
+            [dy[0], dy[1], dy[2], -self.g],
+            [dz[0], dz[1], dz[2], 0.0],
+            [0.0, self.nu * self.r, 0.0, -self.nu * self.k],
+        ]
+    }
+
+    // The y equation with the feedback of u folded into its constant.
+    fn shape(&self, shape: Shape<f64>) -> Option<Shape<f64>> {
+        Some(Shape { c: shape.c - self.g * self.u, ..shape })
+    }
+
+    fn configure(&mut self, config: &Value) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        self.g = get("u_g", self.g);
+        self.nu = get("u_nu", self.nu);
+        self.k = get("u_k", self.k);
+        self.r = get("u_r", self.r);
+        self.y_r = get("u_y_r", self.y_r);
+        let u0 = get("u", self.u0);
+        if u0 != self.u0 {
+            self.u0 = u0;
+            self.u = u0;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.u = self.u0;
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "g": self.g,
+            "nu": self.nu,
+            "k": self.k,
+            "r": self.r,
+            "y_r": self.y_r,
+            "u": self.u0
+        })
+    }
+}
@@ -0,0 +1,95 @@
+// Backward Euler, y1 = y0 + dt * f(y1), solved by Newton iteration on the
+// analytic Jacobian. Only first-order accurate, but A-stable, so it stays
+// bounded for time steps at which the explicit schemes diverge.
+
+use crate::field::Float;
+use std::cmp::Ordering;
+
+const MAX_ITERATIONS: usize = 8;
+const TOLERANCE: f64 = 1e-12;
+
+pub fn backward_euler<const N: usize, T, F, J>(y: [T; N], dt: T, mut f: F, mut jacobian: J) -> [T; N]
+where
+    T: Float,
+    F: FnMut(&[T; N]) -> [T; N],
+    J: FnMut(&[T; N]) -> [[T; N]; N],
+{
+    let (zero, one) = (T::lit(0.0), T::lit(1.0));
+    // Narrow types cannot resolve TOLERANCE; stop at a few ulps instead.
+    let tolerance = if T::lit(TOLERANCE) < T::EPSILON {
+        T::lit(4.0) * T::EPSILON
+    } else {
+        T::lit(TOLERANCE)
+    };
+    // An explicit Euler predictor keeps the iteration count low on smooth
+    // stretches.
+    let mut next = y;
+    let d = f(&y);
+    for j in 0..N {
+        next[j] = next[j] + dt * d[j];
+    }
+    for _ in 0..MAX_ITERATIONS {
+        // Residual g = next - y - dt * f(next) and its Jacobian I - dt * J.
+        let d = f(&next);
+        let jac = jacobian(&next);
+        let mut g = [zero; N];
+        let mut m = [[zero; N]; N];
+        for i in 0..N {
+            g[i] = next[i] - y[i] - dt * d[i];
+            for j in 0..N {
+                m[i][j] = if i == j { one } else { zero } - dt * jac[i][j];
+            }
+        }
+        let Some(delta) = solve(m, g) else {
+            break;
+        };
+        let mut change = zero;
+        for j in 0..N {
+            next[j] = next[j] - delta[j];
+            let relative = delta[j].abs() / (one + next[j].abs());
+            if relative > change {
+                change = relative;
+            }
+        }
+        if change < tolerance {
+            break;
+        }
+    }
+    next
+}
+
+// Gaussian elimination with partial pivoting; None for a singular matrix.
+fn solve<const N: usize, T: Float>(mut m: [[T; N]; N], mut b: [T; N]) -> Option<[T; N]> {
+    let zero = T::lit(0.0);
+    for col in 0..N {
+        let mut pivot = col;
+        for row in col + 1..N {
+            if m[row][col].abs() > m[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        // Also rejects a NaN pivot.
+        if m[pivot][col].abs().partial_cmp(&zero) != Some(Ordering::Greater) {
+            return None;
+        }
+        m.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = m[col];
+        for row in col + 1..N {
+            let factor = m[row][col] / pivot_row[col];
+            for (value, &p) in m[row].iter_mut().zip(&pivot_row).skip(col) {
+                *value = *value - factor * p;
+            }
+            b[row] = b[row] - factor * b[col];
+        }
+    }
+    let mut x = [zero; N];
+    for row in (0..N).rev() {
+        let mut sum = b[row];
+        for k in row + 1..N {
+            sum = sum - m[row][k] * x[k];
+        }
+        x[row] = sum / m[row][row];
+    }
+    Some(x)
+}
@@ -0,0 +1,59 @@
+// Per-port bookkeeping for host-driven inputs. A port whose setter has not
+// been called for `stale_after` seconds is flagged stale and, if
+// `stale_decay` is positive, relaxes toward zero with that time constant.
+
+use crate::filters::Median;
+use crate::math;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Staleness {
+    pub stale_after: f64,
+    pub stale_decay: f64,
+}
+
+impl Staleness {
+    pub fn new() -> Self {
+        Self {
+            stale_after: 0.1,
+            stale_decay: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputPort {
+    pub value: f64,
+    pub median: Median,
+    age: f64,
+}
+
+impl InputPort {
+    pub fn new() -> Self {
+        Self {
+            value: 0.0,
+            median: Median::default(),
+            age: 0.0,
+        }
+    }
+
+    pub fn set(&mut self, value: f64) {
+        self.value = self.median.apply(value);
+        self.age = 0.0;
+    }
+
+    pub fn age(&self) -> f64 {
+        self.age
+    }
+
+    pub fn is_stale(&self, staleness: &Staleness) -> bool {
+        staleness.stale_after > 0.0 && self.age > staleness.stale_after
+    }
+
+    // Called once per process tick of `period` seconds.
+    pub fn tick(&mut self, period: f64, staleness: &Staleness) {
+        self.age += period;
+        if self.is_stale(staleness) && staleness.stale_decay > 0.0 {
+            self.value *= math::exp(-period / staleness.stale_decay);
+        }
+    }
+}
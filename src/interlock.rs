@@ -0,0 +1,52 @@
+// Safety interlock on the input current. Once the magnitude of the total
+// current injected into the x equation exceeds `limit` the fault latches
+// until cleared explicitly; while latched and `zero_outputs` is set, the
+// outputs that drive stimulation read zero.
+
+#[derive(Debug, Clone)]
+pub struct Interlock {
+    pub limit: f64,
+    pub zero_outputs: bool,
+    fault: bool,
+    trips: u64,
+}
+
+impl Interlock {
+    pub fn new() -> Self {
+        Self {
+            limit: 0.0,
+            zero_outputs: true,
+            fault: false,
+            trips: 0,
+        }
+    }
+
+    // Returns true when this sample trips a fault that was not latched.
+    pub fn check(&mut self, input: f64) -> bool {
+        if self.limit <= 0.0 || self.fault {
+            return false;
+        }
+        if input.abs() > self.limit || !input.is_finite() {
+            self.fault = true;
+            self.trips += 1;
+            return true;
+        }
+        false
+    }
+
+    pub fn fault(&self) -> bool {
+        self.fault
+    }
+
+    pub fn blocks_outputs(&self) -> bool {
+        self.fault && self.zero_outputs
+    }
+
+    pub fn trips(&self) -> u64 {
+        self.trips
+    }
+
+    pub fn clear(&mut self) {
+        self.fault = false;
+    }
+}
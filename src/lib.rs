@@ -1,74 +1,486 @@
+#[macro_use]
+mod telemetry;
+
+mod adaptive;
+mod agc;
+mod approx;
+mod balance;
+mod barrier;
+mod batch;
+mod bursts;
+mod conductance;
+mod control;
+mod cosim;
+mod cost;
+#[cfg(feature = "double_double")]
+mod dd;
+mod delay;
+mod dense;
+mod divergence;
+mod drift;
+mod dynamic_clamp;
+mod energy;
+mod events;
+mod expr;
+mod field;
+mod filters;
+mod formulation;
+mod graded;
+mod half_center;
+mod hr4;
+mod fixed;
+mod implicit;
+mod inputs;
+mod interlock;
+mod kahan;
+mod lifecycle;
+mod math;
+mod model;
+mod multirate;
+mod multistep;
+mod noise;
+mod param_drift;
+mod phase;
+mod population;
+mod ports;
+mod prep;
+mod presets;
+mod ramp;
+mod richardson;
+mod rk;
+mod rng;
+mod schedule;
+#[cfg(feature = "simd")]
+pub mod simd;
+mod spectrum;
+mod spike_input;
+mod state;
+mod status;
+mod stiffness;
+mod temperature;
+mod template;
+mod trials;
+mod tuning;
+mod watchdog;
+
+use adaptive::{Adaptive, Attempt, StepControl};
+use agc::Agc;
+use approx::{LimitCycle, PendingFit};
+use balance::BalanceMonitor;
+use barrier::{Coupling, Membership};
+use batch::TickBatch;
+use bursts::BurstTracker;
+use conductance::ConductanceInputs;
+use delay::DelayedFeedback;
+use control::ControlServer;
+use cosim::CoSim;
+use cost::{CostMeter, FLOPS_PER_STAGE};
+use dense::DenseOutput;
+use divergence::{DivergenceGuard, Recovery};
+use drift::DriftInjection;
+use dynamic_clamp::DynamicClamp;
+use energy::EnergyMonitor;
+use events::{EventCallback, EventKind, EventLatch, EventSink, EVENT_OUTPUTS};
+use expr::Expr;
+use field::{derivatives, jacobian, Params, Scalar, Shape, CURRENT_TERMS};
+use filters::HighPass;
+use formulation::Formulation;
+use graded::GradedSynapse;
+use half_center::HalfCenter;
+use hr4::SecondSlow;
+use model::{Hr3, NeuronModel, MAX_STATE};
+use fixed::Fixed;
+use inputs::{InputPort, Staleness};
+use interlock::Interlock;
+use kahan::Kahan;
+use lifecycle::Entered;
+use ramp::SoftStart;
+use richardson::Richardson;
+use multirate::Multirate;
+use multistep::AdamsBashforth;
+use noise::StateNoise;
+use param_drift::ParamDrift;
+use phase::PhaseHistogram;
+use population::Population;
+use prep::SimulatedPrep;
+use presets::Preset;
+use rk::Integrator;
+use rng::{RngStreams, Stream};
 use rtsyn_plugin::{PluginApi, PluginString};
+use schedule::{Anchor, BurstLockedSwitch};
 use serde_json::Value;
+use spectrum::Spectrogram;
+use spike_input::SpikeInput;
+use state::Snapshot;
+use status::Status;
+use stiffness::StiffnessMonitor;
 use std::ffi::c_void;
+use std::time::Instant;
+use temperature::Temperature;
+use template::{BurstBank, Distance, TemplateMatcher};
+use trials::TrialEngine;
+use tuning::{ExtremumSeeker, Metric, TunedParam};
+use watchdog::{Intervention, QuiescenceWatchdog};
 
-const INPUTS: &[&str] = &["i_syn"];
+const INPUTS: &[&str] = &["i_syn", "i_ext", "e", "period_in", "v_live", "event_in"];
 const OUTPUTS: &[&str] = &["Membrane potential (V)", "Membrane potential (mV)"];
+// Config keys that trigger an action rather than set a value.
+const ONE_SHOT_KEYS: [&str; 3] = ["clear_fault", "cancel_scheduled", "restart_epochs"];
+
+// Variables available to "outputs_custom" expressions, in slot order.
+const EXPR_VARS: [&str; 10] = ["x", "y", "z", "t", "e", "mu", "s", "vh", "i_syn", "x_ac"];
+
+// Default for max_steps_per_tick.
+const MAX_STEPS_PER_TICK: usize = 10_000;
+// Mean model time between burst onsets at the default parameters, measured
+// over 20000 time units. Overridable with model_burst_span when e, mu, s or
+// vh move the cycle.
+const MODEL_BURST_SPAN: f64 = 120.0;
+// Outputs that may be wired to stimulation hardware; forced to zero while the
+// interlock fault is latched and scaled by the soft-start ramp.
+const DRIVE_OUTPUTS: &[&str] = &[
+    "Membrane potential (V)",
+    "Membrane potential (mV)",
+    "Scaled membrane potential",
+    "x_ac",
+    "i_inject",
+];
+// FNV-1a offset basis, the trajectory checksum before the first tick.
+const CHECKSUM_SEED: u64 = 0xcbf2_9ce4_8422_2325;
 
 #[derive(Debug)]
 struct HindmarshRosev2Rust {
     x: f64,
     y: f64,
     z: f64,
-    input_syn: f64,
+    input_syn: InputPort,
+    input_ext: InputPort,
+    i_ext: f64,
+    period_in: InputPort,
+    v_live: InputPort,
+    event_in: InputPort,
+    event_in_high: bool,
+    staleness: Staleness,
     e: f64,
     mu: f64,
     s: f64,
     vh: f64,
+    shape: Option<Shape<f64>>,
+    freeze_z: bool,
+    clamp_z: bool,
+    z_clamp: f64,
+    temperature: Temperature,
     dt: f64,
     burst_duration: f64,
     s_points: usize,
+    // Unrounded steps per tick that s_points approximates, and the share of
+    // a step carried between ticks when fractional_steps is set.
+    exact_steps: f64,
+    fractional_steps: bool,
+    step_fraction: f64,
+    // Integrate backwards in time: steps are taken with -dt and t decreases.
+    reverse_time: bool,
     period_seconds: f64,
     cfg_x: f64,
     cfg_y: f64,
     cfg_z: f64,
+    model: Box<dyn NeuronModel>,
+    t: f64,
+    steps: u64,
+    events: EventSink,
+    bursts: BurstTracker,
+    target_freq: f64,
+    target_duty: f64,
+    shadow: Option<[f64; 3]>,
+    shadow_refinement: usize,
+    shadow_resync: f64,
+    shadow_since_sync: f64,
+    shadow_divergence: f64,
+    carry_step_deficit: bool,
+    step_deficit: usize,
+    max_steps: usize,
+    steps_clamped: usize,
+    calibration_max_rate: f64,
+    calibration_changes: u64,
+    elapsed_seconds: f64,
+    approximant: bool,
+    approx: Option<LimitCycle>,
+    approx_pending: Option<PendingFit>,
+    // Inputs of the last requested fit, see approx_key.
+    approx_key: Option<(Params<f64>, f64, Integrator, bool, usize, f64)>,
+    approx_points: usize,
+    approx_phase: f64,
+    tuner: Option<ExtremumSeeker>,
+    x_ac: HighPass,
+    agc: Agc,
+    drift: DriftInjection,
+    watchdog: QuiescenceWatchdog,
+    divergence: DivergenceGuard,
+    port_metadata: bool,
+    output_aliases: Vec<(String, String)>,
+    output_scaling: Vec<(String, f64, f64)>,
+    input_scaling: Vec<(String, f64, f64)>,
+    cost: CostMeter,
+    id: u64,
+    rng: RngStreams,
+    ic_jitter: f64,
+    overloaded: bool,
+    interlock: Interlock,
+    soft_start: SoftStart,
+    switch: BurstLockedSwitch,
+    trials: TrialEngine,
+    template: TemplateMatcher,
+    burst_bank: BurstBank,
+    spectrogram: Spectrogram,
+    custom_outputs: Vec<(String, Expr, f64)>,
+    custom_output_errors: Vec<(String, String)>,
+    // Entries of the last config that were rejected, by key path.
+    config_errors: Vec<(String, String)>,
+    current_outputs: bool,
+    balance: BalanceMonitor,
+    energy: EnergyMonitor,
+    noise: StateNoise,
+    param_drift: ParamDrift,
+    param_drift_seed: Option<u64>,
+    event_outputs: bool,
+    event_latch: EventLatch,
+    applied_config: serde_json::Map<String, Value>,
+    idle_tolerance: f64,
+    idle: Option<f64>,
+    idle_ticks: u64,
+    multirate: Multirate,
+    compensated: bool,
+    fixed_point: bool,
+    dense: DenseOutput,
+    precise_spikes: bool,
+    tick_start: f64,
+    last_spike_time: Option<f64>,
+    state_sum: Kahan<3>,
+    #[cfg(feature = "double_double")]
+    state_tail: dd::StateTail,
+    trajectory_checksum: u64,
+    time_sum: Kahan<1>,
+    cycle_outputs: bool,
+    event_phases: PhaseHistogram,
+    barrier: Option<Membership>,
+    control: Option<ControlServer>,
+    control_port: u16,
+    cosim: Option<CoSim>,
+    prep: Option<SimulatedPrep>,
+    half_center: Option<HalfCenter>,
+    half_center_current: f64,
+    input_syn2: InputPort,
+    population: Option<Population>,
+    population_current: f64,
+    coupling: Option<Coupling>,
+    coupling_current: f64,
+    g_gap: f64,
+    v_pre: InputPort,
+    graded: GradedSynapse,
+    v_pre_syn: InputPort,
+    spike_input: SpikeInput,
+    conductance: ConductanceInputs,
+    delayed_feedback: DelayedFeedback,
+    dynamic_clamp: Option<DynamicClamp>,
+    v_m: InputPort,
+    tick: u64,
+    integrator: Integrator,
+    adaptive: Adaptive,
+    step_control: StepControl,
+    stiffness: StiffnessMonitor,
+    multistep: AdamsBashforth,
+    richardson: Richardson,
+    model_burst_span: f64,
+    batch: TickBatch,
+    tick_span: usize,
 }
 
 impl HindmarshRosev2Rust {
-    fn new() -> Self {
+    fn new(id: u64) -> Self {
         let x = -0.9013747551021072;
         let y = -3.15948829665501;
         let z = 3.247826955037619;
+        let seed = rng::derive(rng::global_seed(), id);
+        // Instances are created off the tick thread; later refits only
+        // queue work for it.
+        approx::start_worker();
         Self {
             x,
             y,
             z,
-            input_syn: 0.0,
-            e: 3.25,
-            mu: 0.006,
-            s: 4.0,
-            vh: 1.0,
+            input_syn: InputPort::new(),
+            input_ext: InputPort::new(),
+            i_ext: 0.0,
+            period_in: InputPort::new(),
+            v_live: InputPort::new(),
+            event_in: InputPort::new(),
+            event_in_high: false,
+            staleness: Staleness::new(),
+            e: Params::DEFAULT.e,
+            mu: Params::DEFAULT.mu,
+            s: Params::DEFAULT.s,
+            vh: Params::DEFAULT.vh,
+            shape: None,
+            freeze_z: false,
+            clamp_z: false,
+            z_clamp: 0.0,
+            temperature: Temperature::default(),
             dt: 0.15,
             burst_duration: 1.0,
             s_points: 1,
+            exact_steps: 1.0,
+            fractional_steps: true,
+            step_fraction: 0.0,
+            reverse_time: false,
             period_seconds: 0.001,
             cfg_x: x,
             cfg_y: y,
             cfg_z: z,
+            model: Box::new(Hr3(Formulation::Native)),
+            t: 0.0,
+            steps: 0,
+            events: EventSink::new(),
+            bursts: BurstTracker::default(),
+            target_freq: 0.0,
+            target_duty: 0.0,
+            shadow: None,
+            shadow_refinement: 0,
+            shadow_resync: 100.0,
+            shadow_since_sync: 0.0,
+            shadow_divergence: 0.0,
+            carry_step_deficit: true,
+            step_deficit: 0,
+            max_steps: MAX_STEPS_PER_TICK,
+            steps_clamped: 0,
+            calibration_max_rate: 0.0,
+            calibration_changes: 0,
+            elapsed_seconds: 0.0,
+            approximant: false,
+            approx: None,
+            approx_pending: None,
+            approx_key: None,
+            approx_points: 1024,
+            approx_phase: 0.0,
+            tuner: None,
+            x_ac: HighPass::default(),
+            agc: Agc::default(),
+            drift: DriftInjection::default(),
+            watchdog: QuiescenceWatchdog::new(),
+            divergence: DivergenceGuard::new(),
+            port_metadata: false,
+            output_aliases: Vec::new(),
+            output_scaling: Vec::new(),
+            input_scaling: Vec::new(),
+            cost: CostMeter::default(),
+            id,
+            rng: RngStreams::new(seed),
+            ic_jitter: 0.0,
+            overloaded: false,
+            interlock: Interlock::new(),
+            soft_start: SoftStart::default(),
+            switch: BurstLockedSwitch::new(),
+            trials: TrialEngine::default(),
+            template: TemplateMatcher::new(),
+            burst_bank: BurstBank::new(),
+            spectrogram: Spectrogram::new(),
+            custom_outputs: Vec::new(),
+            custom_output_errors: Vec::new(),
+            config_errors: Vec::new(),
+            current_outputs: false,
+            balance: BalanceMonitor::default(),
+            energy: EnergyMonitor::default(),
+            noise: StateNoise::default(),
+            param_drift: ParamDrift::new(param_drift_seed(seed)),
+            param_drift_seed: None,
+            event_outputs: false,
+            event_latch: EventLatch::new(),
+            applied_config: serde_json::Map::new(),
+            idle_tolerance: 0.0,
+            idle: None,
+            idle_ticks: 0,
+            multirate: Multirate::new(),
+            compensated: false,
+            fixed_point: false,
+            dense: DenseOutput::new(),
+            precise_spikes: false,
+            tick_start: 0.0,
+            last_spike_time: None,
+            state_sum: Kahan::new(),
+            #[cfg(feature = "double_double")]
+            state_tail: dd::StateTail::default(),
+            trajectory_checksum: CHECKSUM_SEED,
+            time_sum: Kahan::new(),
+            cycle_outputs: false,
+            event_phases: PhaseHistogram::new(20),
+            barrier: None,
+            control: None,
+            control_port: 0,
+            cosim: None,
+            prep: None,
+            half_center: None,
+            half_center_current: 0.0,
+            input_syn2: InputPort::new(),
+            population: None,
+            population_current: 0.0,
+            coupling: None,
+            coupling_current: 0.0,
+            g_gap: 0.0,
+            v_pre: InputPort::new(),
+            graded: GradedSynapse::default(),
+            v_pre_syn: InputPort::new(),
+            spike_input: SpikeInput::default(),
+            conductance: ConductanceInputs::default(),
+            delayed_feedback: DelayedFeedback::default(),
+            dynamic_clamp: None,
+            v_m: InputPort::new(),
+            tick: 0,
+            integrator: Integrator::Rk6,
+            adaptive: Adaptive::new(),
+            step_control: StepControl::Pi,
+            stiffness: StiffnessMonitor::new(),
+            multistep: AdamsBashforth::new(),
+            richardson: Richardson::new(),
+            model_burst_span: MODEL_BURST_SPAN,
+            batch: TickBatch::new(),
+            tick_span: 1,
         }
     }
 
     fn update_burst_settings(&mut self) {
+        let previous = (self.dt, self.s_points);
+        self.compute_burst_settings();
+        if (self.dt, self.s_points) != previous {
+            self.calibration_changes += 1;
+            log_info!(instance = self.id, dt = self.dt, s_points = self.s_points, "calibration changed");
+            self.events.emit(EventKind::CalibrationChanged, self.dt);
+        }
+    }
+
+    fn compute_burst_settings(&mut self) {
         if self.period_seconds <= 0.0 {
             self.s_points = 1;
+            self.exact_steps = 1.0;
             return;
         }
 
-        if self.burst_duration > 0.0 {
+        if self.pi_controlled() {
+            // One tick covers an exact share of the model burst; the error
+            // controller picks the internal steps.
+            self.dt = self.model_burst_span / (self.burst_duration / self.period_seconds);
+            self.exact_steps = 1.0;
+        } else if self.burst_duration > 0.0 {
             // Use sophisticated dt selection like original RTXI implementation
             let freq = 1.0 / self.period_seconds;
             let pts_match = self.burst_duration * freq;
             self.dt = self.select_optimal_dt(pts_match);
-            self.s_points = ((self.select_pts_burst(self.burst_duration, freq) / (self.burst_duration * freq)).round() as usize).max(1);
+            self.exact_steps = self.select_pts_burst(self.burst_duration, freq) / (self.burst_duration * freq);
         } else {
             // Simple case - use fixed dt and calculate steps
-            let steps = ((self.period_seconds / self.dt).round() as usize).max(1);
-            self.s_points = steps;
-        }
-        
-        if self.s_points == 0 {
-            self.s_points = 1;
+            self.exact_steps = self.period_seconds / self.dt;
         }
+        // Ticks always take at least one step.
+        self.exact_steps = self.exact_steps.max(1.0);
+        self.s_points = (self.exact_steps.round() as usize).max(1);
     }
 
     fn select_optimal_dt(&self, pts_match: f64) -> f64 {
@@ -92,9 +504,59 @@ impl HindmarshRosev2Rust {
     }
 
     fn set_config(&mut self, config: &Value) {
+        if let Some(expanded) = presets::expand(config) {
+            return self.set_config(&expanded);
+        } else if config.get("preset").is_some() {
+            log_warn!(instance = self.id, "unknown preset, ignored");
+        }
+        self.config_errors.clear();
         let get = |key: &str, default: f64| -> f64 {
             config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
         };
+        let get_bool = |key: &str, default: bool| -> bool {
+            config.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+        };
+        // An explicit seed makes this instance independent of the session seed.
+        if let Some(seed) = config.get("seed").and_then(|v| v.as_u64()) {
+            if seed != self.rng.seed() {
+                self.rng = RngStreams::new(seed);
+            }
+        }
+        self.ic_jitter = get("ic_jitter", self.ic_jitter).max(0.0);
+        let sigma = self.noise.sigma;
+        self.noise.sigma = [
+            get("noise_x", sigma[0]).max(0.0),
+            get("noise_y", sigma[1]).max(0.0),
+            get("noise_z", sigma[2]).max(0.0),
+        ];
+        if let Some(seed) = config.get("param_drift_seed").and_then(|v| v.as_u64()) {
+            self.param_drift_seed = Some(seed);
+        }
+        self.param_drift.reseed(self.param_drift_seed.unwrap_or_else(|| param_drift_seed(self.rng.seed())));
+        for (prefix, walk) in [("e", &mut self.param_drift.e), ("mu", &mut self.param_drift.mu)] {
+            walk.sigma = get(&format!("{prefix}_drift_sigma"), walk.sigma).max(0.0);
+            walk.tau = get(&format!("{prefix}_drift_tau"), walk.tau).max(0.0);
+            walk.range = get(&format!("{prefix}_drift_range"), walk.range).max(0.0);
+        }
+
+        let switching = match config.get("model").and_then(|v| v.as_str()) {
+            Some(name) if name != self.model.name() => {
+                let model = model::parse(name);
+                if model.is_none() {
+                    log_warn!(instance = self.id, "unknown model, ignored");
+                }
+                model
+            }
+            _ => None,
+        };
+        let formulation = switching.as_ref().map(|model| model.formulation());
+        if self.model.formulation() == Formulation::Native && formulation == Some(Formulation::Canonical) {
+            // Carry the native set over, see formulation.rs: r = mu vh with
+            // z in units of vh z, so the trajectory continues unchanged.
+            self.mu *= self.vh;
+            self.rescale_z(self.vh);
+            self.vh = 1.0;
+        }
         let x = get("x", self.x);
         let y = get("y", self.y);
         let z = get("z", self.z);
@@ -102,226 +564,3184 @@ impl HindmarshRosev2Rust {
             self.cfg_x = x;
             self.cfg_y = y;
             self.cfg_z = z;
-            self.x = x;
-            self.y = y;
-            self.z = z;
-        }
-        self.e = get("e", self.e);
-        self.mu = get("mu", self.mu);
-        self.s = get("s", self.s);
-        self.vh = get("vh", self.vh);
+            self.reset_state();
+        }
+        if let Some(model) = switching {
+            // The native form has no a, b, c, d or x_r of its own.
+            if model.formulation() == Formulation::Native {
+                self.shape = None;
+            }
+            self.model = model;
+        }
+        let mut core = Params { e: self.e, mu: self.mu, s: self.s, vh: self.vh, input: 0.0, shape: self.shape };
+        self.model.configure_core(config, &mut core);
+        (self.e, self.mu, self.s, self.vh, self.shape) = (core.e, core.mu, core.s, core.vh, core.shape);
+        self.i_ext = get("i_ext", self.i_ext);
+        self.freeze_z = get_bool("freeze_z", self.freeze_z);
+        // Clamping without a z_clamp holds z where it is.
+        let clamp_z = get_bool("clamp_z", self.clamp_z);
+        if clamp_z && !self.clamp_z {
+            self.z_clamp = self.z;
+        }
+        self.clamp_z = clamp_z;
+        self.z_clamp = get("z_clamp", self.z_clamp);
+        self.model.configure(config);
+        let factor = get("temperature_factor", 0.0);
+        let fast = get("temperature_factor_fast", if factor > 0.0 { factor } else { self.temperature.fast });
+        let slow = get("temperature_factor_slow", if factor > 0.0 { factor } else { self.temperature.slow });
+        if fast > 0.0 && slow > 0.0 {
+            self.temperature = Temperature { fast, slow };
+        } else {
+            log_warn!(instance = self.id, fast, slow, "temperature factors must be positive, ignored");
+        }
+        self.events.spike_threshold = get("spike_threshold", self.events.spike_threshold);
+        self.events.burst_gap = get("burst_gap", self.events.burst_gap);
+        self.target_freq = get("target_freq", self.target_freq);
+        self.target_duty = get("target_duty", self.target_duty);
+
+        self.shadow_refinement =
+            get("shadow_refinement", self.shadow_refinement as f64).max(0.0) as usize;
+        self.shadow_resync = get("shadow_resync", self.shadow_resync);
+        if self.shadow_refinement == 0 {
+            self.shadow = None;
+            self.shadow_divergence = 0.0;
+        } else if self.shadow.is_none() {
+            self.resync_shadow();
+        }
         
-        self.burst_duration = get("burst_duration", self.burst_duration);
-        self.period_seconds = get("period_seconds", self.period_seconds);
-        self.update_burst_settings();
-    }
+        self.staleness.stale_after = get("input_stale_after", self.staleness.stale_after);
+        self.staleness.stale_decay = get("input_stale_decay", self.staleness.stale_decay);
 
-    fn process(&mut self) {
-        let dt = self.dt;
-        // Original working logic - simple step limiting
-        let steps = self.s_points.min(10_000).max(1);
+        let agc_tau = get("agc_tau", self.agc.tau).max(0.0);
+        if agc_tau != self.agc.tau {
+            self.agc.tau = agc_tau;
+            self.agc.reset();
+        }
 
-        for _ in 0..steps {
-            let mut vars = [self.x, self.y, self.z];
-            let mut k = [[0.0f64; 3]; 6];
-            let mut aux = [0.0f64; 3];
-
-            let f = |vars: [f64; 3], params: &Self| -> [f64; 3] {
-                let x = vars[0];
-                let y = vars[1];
-                let z = vars[2];
-                let xdot = y + 3.0 * (x * x) - (x * x * x) - params.vh * z + params.e - params.input_syn;
-                let ydot = 1.0 - 5.0 * (x * x) - y;
-                let zdot = params.mu * (-params.vh * z + params.s * (x + 1.6));
-                [xdot, ydot, zdot]
-            };
+        self.drift.offset = get("test_offset", self.drift.offset);
+        self.drift.rate = get("test_drift_rate", self.drift.rate);
+        self.drift.amplitude = get("test_drift_amplitude", self.drift.amplitude);
+        self.drift.period = get("test_drift_period", self.drift.period);
+        if !self.drift.enabled() {
+            self.drift.reset();
+        }
+
+        self.watchdog.timeout = get("quiescence_timeout", self.watchdog.timeout);
+        self.watchdog.kick_current = get("kick_current", self.watchdog.kick_current);
+        self.watchdog.kick_duration = get("kick_duration", self.watchdog.kick_duration);
+        self.watchdog.reset = get_bool("kick_reset", self.watchdog.reset);
+        if let Some(policy) = config.get("divergence_policy").and_then(|v| v.as_str()).and_then(Recovery::parse) {
+            self.divergence.policy = policy;
+        }
+
+        self.interlock.limit = get("input_limit", self.interlock.limit);
+        self.interlock.zero_outputs = get_bool("fault_zero_outputs", self.interlock.zero_outputs);
+        if get_bool("clear_fault", false) && self.interlock.fault() {
+            self.interlock.clear();
+            self.soft_start.restart();
+        }
+        self.soft_start.duration = get("ramp_duration", self.soft_start.duration).max(0.0);
+
+        self.port_metadata = get_bool("port_metadata", self.port_metadata);
+        // Extra names for existing outputs, e.g. "output_aliases": {"v": "x"}.
+        if let Some(aliases) = config.get("output_aliases").and_then(|v| v.as_object()) {
+            self.output_aliases = aliases
+                .iter()
+                .filter_map(|(alias, target)| Some((alias.clone(), target.as_str()?.to_string())))
+                .collect();
+        }
+        // Affine maps to host units by output name (aliases included), e.g.
+        // "output_scaling": {"x": {"gain": 30.0, "offset": -55.0}}.
+        if let Some(scaling) = config.get("output_scaling").and_then(|v| v.as_object()) {
+            self.output_scaling = scaling
+                .iter()
+                .map(|(name, map)| {
+                    let get = |key: &str, default: f64| map.get(key).and_then(|v| v.as_f64()).unwrap_or(default);
+                    (name.clone(), get("gain", 1.0), get("offset", 0.0))
+                })
+                .collect();
+        }
+        // The same for inputs, mapping host units onto the model's as
+        // sign gain value + offset, e.g. "input_scaling": {"i_syn": {"gain":
+        // 0.5, "sign": -1}} for a recorded current in nA of the opposite sign.
+        if let Some(scaling) = config.get("input_scaling").and_then(|v| v.as_object()) {
+            self.input_scaling = scaling
+                .iter()
+                .map(|(name, map)| {
+                    let get = |key: &str, default: f64| map.get(key).and_then(|v| v.as_f64()).unwrap_or(default);
+                    (name.clone(), get("sign", 1.0).signum() * get("gain", 1.0), get("offset", 0.0))
+                })
+                .collect();
+        }
 
-            let r0 = f(vars, self);
-            for j in 0..3 {
-                k[0][j] = dt * r0[j];
-                aux[j] = vars[j] + k[0][j] * 0.2;
+        self.current_outputs = get_bool("current_outputs", self.current_outputs);
+        self.cycle_outputs = get_bool("cycle_outputs", self.cycle_outputs);
+        // Deterministic instance coupling, e.g. "barrier_group": "pair" and
+        // "barrier_coupling": {"peer": 2, "variable": "x", "gain": 0.5}; an
+        // empty group name leaves the group and null removes the coupling.
+        if let Some(group) = config.get("barrier_group").and_then(|v| v.as_str()) {
+            if self.barrier.as_ref().map(Membership::group) != Some(group) {
+                self.barrier = (!group.is_empty()).then(|| Membership::join(group, self.id));
+            }
+        }
+        if let Some(coupling) = config.get("barrier_coupling") {
+            let variable = coupling
+                .get("variable")
+                .and_then(|v| v.as_str())
+                .unwrap_or("x");
+            self.coupling = coupling.get("peer").and_then(|v| v.as_u64()).map(|peer| Coupling {
+                peer,
+                variable: ["x", "y", "z"].iter().position(|name| *name == variable).unwrap_or(0),
+                gain: coupling.get("gain").and_then(|v| v.as_f64()).unwrap_or(1.0),
+            });
+            if self.coupling.is_none() {
+                self.coupling_current = 0.0;
             }
+        }
 
-            let r1 = f(aux, self);
-            for j in 0..3 {
-                k[1][j] = dt * r1[j];
-                aux[j] = vars[j] + k[0][j] * 0.075 + k[1][j] * 0.225;
+        // Live control endpoint (feature `control_server`); 0 stops it.
+        let port = get("control_port", self.control_port as f64).clamp(0.0, u16::MAX as f64) as u16;
+        if port != self.control_port {
+            self.control_port = port;
+            self.control = None;
+            if port != 0 {
+                let bind = config.get("control_bind").and_then(|v| v.as_str()).unwrap_or("127.0.0.1");
+                self.control = ControlServer::start(bind, port);
+                if self.control.is_none() {
+                    log_warn!(instance = self.id, port, "control server not started");
+                }
             }
+        }
 
-            let r2 = f(aux, self);
-            for j in 0..3 {
-                k[2][j] = dt * r2[j];
-                aux[j] = vars[j] + k[0][j] * 0.3 - k[1][j] * 0.9 + k[2][j] * 1.2;
+        // Electrical synapse onto the presynaptic potential on input v_pre.
+        self.g_gap = get("g_gap", self.g_gap);
+        // Graded chemical synapse from the potential on input v_pre_syn.
+        self.graded.g_max = get("syn_g_max", self.graded.g_max);
+        self.graded.e_rev = get("syn_e_rev", self.graded.e_rev);
+        self.graded.v_half = get("syn_v_half", self.graded.v_half);
+        self.graded.slope = get("syn_slope", self.graded.slope);
+        self.graded.tau_fast = get("syn_tau_fast", self.graded.tau_fast).max(0.0);
+        self.graded.tau_slow = get("syn_tau_slow", self.graded.tau_slow).max(0.0);
+        self.graded.slow_fraction = get("syn_slow_fraction", self.graded.slow_fraction).clamp(0.0, 1.0);
+        if !self.graded.enabled() {
+            self.graded.reset();
+        }
+        // Spike trains on input spike_in, convolved with an alpha or
+        // double-exponential kernel.
+        if let Some(name) = config.get("spike_kernel").and_then(|v| v.as_str()) {
+            if let Some(kernel) = spike_input::Kernel::parse(name) {
+                self.spike_input.kernel = kernel;
+                self.spike_input.reset();
+            } else {
+                log_warn!(instance = self.id, "unknown spike_kernel, ignored");
             }
+        }
+        self.spike_input.weight = get("spike_weight", self.spike_input.weight);
+        self.spike_input.tau = get("spike_tau", self.spike_input.tau).max(0.0);
+        self.spike_input.tau_rise = get("spike_tau_rise", self.spike_input.tau_rise).max(0.0);
+        self.spike_input.tau_decay = get("spike_tau_decay", self.spike_input.tau_decay).max(0.0);
+        if self.spike_input.kernel == spike_input::Kernel::Exp2 && self.spike_input.tau_rise == self.spike_input.tau_decay {
+            log_warn!(instance = self.id, "equal spike_tau_rise and spike_tau_decay give an empty exp2 kernel");
+        }
+        if !self.spike_input.enabled() {
+            self.spike_input.reset();
+        }
+        self.conductance.enabled = get_bool("conductance_inputs", self.conductance.enabled);
+        self.conductance.e_rev_exc = get("e_rev_exc", self.conductance.e_rev_exc);
+        self.conductance.e_rev_inh = get("e_rev_inh", self.conductance.e_rev_inh);
+        self.delayed_feedback.gain = get("delay_gain", self.delayed_feedback.gain);
+        self.delayed_feedback.tau = get("delay_tau", self.delayed_feedback.tau).max(0.0);
+        if self.delayed_feedback.enabled() {
+            self.delayed_feedback.reserve(self.model_dt() * self.exact_steps);
+        } else {
+            self.delayed_feedback.reset();
+        }
 
-            let r3 = f(aux, self);
-            for j in 0..3 {
-                k[3][j] = dt * r3[j];
-                aux[j] =
-                    vars[j] + k[0][j] * 0.075 + k[1][j] * 0.675 - k[2][j] * 0.6 + k[3][j] * 0.75;
+        if let Some(clamp) = config.get("dynamic_clamp") {
+            if clamp.is_object() {
+                self.dynamic_clamp.get_or_insert_with(DynamicClamp::new).configure(clamp);
+            } else {
+                self.dynamic_clamp = None;
             }
+        }
 
-            let r4 = f(aux, self);
-            for j in 0..3 {
-                k[4][j] = dt * r4[j];
-                aux[j] = vars[j] + k[0][j] * 0.660493827160493 + k[1][j] * 2.5
-                    - k[2][j] * 5.185185185185185
-                    + k[3][j] * 3.888888888888889
-                    - k[4][j] * 0.864197530864197;
+        if let Some(half_center) = config.get("half_center") {
+            if half_center.is_object() {
+                self.half_center.get_or_insert_with(HalfCenter::new).configure(half_center);
+            } else {
+                self.half_center = None;
+                self.half_center_current = 0.0;
             }
+        }
 
-            let r5 = f(aux, self);
-            for j in 0..3 {
-                k[5][j] = dt * r5[j];
+        if let Some(population) = config.get("population") {
+            let size = population.get("weights").and_then(|v| v.as_array()).map(Vec::len);
+            match &mut self.population {
+                Some(current) if size.is_none() || size == Some(current.size()) => current.configure(population),
+                _ if population.is_object() => {
+                    self.population = Population::new(population);
+                    if self.population.is_none() {
+                        log_warn!(instance = self.id, "population weights must be a square matrix of two or more neurons");
+                    }
+                }
+                _ => self.population = None,
+            }
+            if self.population.is_none() {
+                self.population_current = 0.0;
+            }
+        }
+        // Individual weights as [post, pre, value], applied after the
+        // half-center and population above.
+        if let Some(entries) = config.get("synapse_weights").and_then(|v| v.as_array()) {
+            for entry in entries {
+                let values: Vec<f64> = entry.as_array().into_iter().flatten().filter_map(Value::as_f64).collect();
+                let status = match values[..] {
+                    [post, pre, weight] if post >= 0.0 && pre >= 0.0 => self.set_weight(post as usize, pre as usize, weight),
+                    _ => Status::BadArgument,
+                };
+                if status != Status::Ok {
+                    log_warn!(instance = self.id, ?entry, "synapse weight not set");
+                }
             }
+        }
 
-            for j in 0..3 {
-                vars[j] += k[0][j] * 0.098765432098765
-                    + k[2][j] * 0.396825396825396
-                    + k[3][j] * 0.231481481481481
-                    + k[4][j] * 0.308641975308641
-                    - k[5][j] * 0.035714285714285;
+        if let Some(prep) = config.get("simulated_prep") {
+            if !prep.is_object() {
+                self.prep = None;
+            } else {
+                // Seeded from its own stream index, so the preparation's
+                // draws never shift those of the instance's own streams.
+                let seed = rng::derive(self.rng.seed(), rng::PREP_STREAM);
+                let span = self.model_burst_span;
+                self.prep.get_or_insert_with(|| SimulatedPrep::new(seed, span)).configure(prep, span);
             }
+        }
 
-            self.x = vars[0];
-            self.y = vars[1];
-            self.z = vars[2];
+        // Co-simulation bridge (feature `cosim`); an empty path stops it.
+        if let Some(path) = config.get("cosim_socket").and_then(|v| v.as_str()) {
+            if self.cosim.as_ref().map(CoSim::path) != Some(path) {
+                self.cosim = None;
+                if !path.is_empty() {
+                    self.cosim = CoSim::start(path);
+                    if self.cosim.is_none() {
+                        log_warn!(instance = self.id, path, "co-simulation bridge not started");
+                    }
+                }
+            }
+        }
+
+        let phase_bins = get("phase_bins", self.event_phases.bins() as f64).max(1.0) as usize;
+        if phase_bins != self.event_phases.bins() || get_bool("phase_reset", false) {
+            self.event_phases = PhaseHistogram::new(phase_bins);
+        }
+        // Scheduling hints: outputs read every "batch_ticks" ticks, or once
+        // "ticks_until_read" ticks from now.
+        if let Some(ticks) = config.get("batch_ticks").and_then(|v| v.as_u64()) {
+            self.batch.set_every(ticks as usize);
+        }
+        if let Some(ticks) = config.get("ticks_until_read").and_then(|v| v.as_u64()) {
+            self.batch.hint(ticks as usize);
+        }
+        if let Some(integrator) = config.get("integrator").and_then(|v| v.as_str()).and_then(Integrator::parse) {
+            self.integrator = integrator;
+        }
+        self.adaptive.abs_tol = get("abs_tol", self.adaptive.abs_tol).max(f64::MIN_POSITIVE);
+        self.adaptive.rel_tol = get("rel_tol", self.adaptive.rel_tol).max(0.0);
+        let ratio = get("multirate_ratio", self.multirate.ratio as f64).max(1.0) as usize;
+        let compensated = get_bool("compensated_summation", self.compensated);
+        if compensated != self.compensated {
+            self.compensated = compensated;
+            self.restart_compensation();
+        }
+        // Takes precedence over compensated_summation and multirate_ratio,
+        // whose bookkeeping is floating point; rk45 keeps its own f64 loop.
+        self.fixed_point = get_bool("fixed_point", self.fixed_point);
+        // Explicit steps hand over to backward Euler while the Jacobian is
+        // stiff for the current step size.
+        self.stiffness.enabled = get_bool("stiffness_switching", self.stiffness.enabled);
+        self.stiffness.threshold = get("stiffness_threshold", self.stiffness.threshold).max(0.0);
+        if !self.stiffness.enabled {
+            self.stiffness.disable();
+        }
+        // Membrane outputs and x/y/z sampled at the exact host tick time.
+        self.dense.enabled = get_bool("dense_output", self.dense.enabled);
+        // Spike times from the step's Hermite interpolant instead of the
+        // step end.
+        self.precise_spikes = get_bool("precise_spike_times", self.precise_spikes);
+        // Fixed steps taken at dt and dt/2 and extrapolated; see richardson.rs.
+        self.richardson.enabled = get_bool("richardson", self.richardson.enabled);
+        let exponential_z = get_bool("exponential_z", self.multirate.exponential_z);
+        if (ratio, exponential_z) != (self.multirate.ratio, self.multirate.exponential_z) {
+            self.multirate.ratio = ratio;
+            self.multirate.exponential_z = exponential_z;
+            self.multirate.restart();
+        }
+        // Any config change may move the fixed point, so it always wakes.
+        self.idle_tolerance = get("idle_tolerance", self.idle_tolerance).max(0.0);
+        self.idle = None;
+        // Event outputs latch substep events by default; e.g.
+        // "event_latch": {"spike": false} samples spike at the tick boundary.
+        self.event_outputs = get_bool("event_outputs", self.event_outputs);
+        if let Some(latch) = config.get("event_latch") {
+            for (flag, name) in self.event_latch.latch.iter_mut().zip(EVENT_OUTPUTS) {
+                *flag = latch.get(name).and_then(|v| v.as_bool()).unwrap_or(*flag);
+            }
+        }
+        let balance = get_bool("balance_monitor", self.balance.enabled);
+        if balance && !self.balance.enabled {
+            self.balance.enabled = true;
+            self.rebase_balance();
+        }
+        self.balance.enabled = balance;
+        self.balance.jump_threshold = get("balance_jump_threshold", self.balance.jump_threshold).max(0.0);
+        // E depends on e and vh, so any config update restarts the drift.
+        self.energy.enabled = get_bool("energy_monitor", self.energy.enabled);
+        self.rebase_energy();
+
+        // Derived outputs, e.g. "outputs_custom": {"w": "x - 0.5*z"}; the map
+        // replaces any previous set. Invalid expressions are skipped and
+        // reported in stats_json.
+        if let Some(custom) = config.get("outputs_custom").and_then(|v| v.as_object()) {
+            self.custom_outputs.clear();
+            self.custom_output_errors.clear();
+            for (name, source) in custom.iter() {
+                let Some(source) = source.as_str() else {
+                    continue;
+                };
+                match Expr::compile(source, &EXPR_VARS) {
+                    Ok(expr) => self.custom_outputs.push((name.clone(), expr, 0.0)),
+                    Err(error) => {
+                        log_warn!(instance = self.id, output = name.as_str(), %error, "invalid output expression");
+                        self.custom_output_errors.push((name.clone(), error));
+                    }
+                }
+            }
+            self.evaluate_custom_outputs();
+        }
+
+        // Per-port despiking, e.g. "input_median": {"i_syn": 3, "i_ext": 5}.
+        if let Some(windows) = config.get("input_median") {
+            if !windows.is_object() {
+                self.reject_config("input_median", "expected an object of window lengths");
+            }
+            for (name, window) in windows.as_object().into_iter().flatten() {
+                let key = format!("input_median.{name}");
+                match (self.current_input_mut(name), window.as_u64()) {
+                    (Some(port), Some(window)) => port.median.set_window(window as usize),
+                    (Some(_), None) => self.reject_config(&key, "window must be a non-negative integer"),
+                    (None, _) => self.reject_config(&key, "not a current input"),
+                }
+            }
+        }
+
+        self.max_steps = get("max_steps_per_tick", self.max_steps as f64).max(1.0) as usize;
+        self.fractional_steps = get_bool("fractional_steps", self.fractional_steps);
+        let reverse_time = get_bool("reverse_time", self.reverse_time);
+        if reverse_time != self.reverse_time {
+            // Multistep history was built with the other sign of h.
+            self.reverse_time = reverse_time;
+            self.multistep.restart();
+            log_info!(instance = self.id, t = self.t, reverse = reverse_time, "integration direction changed");
+        }
+        if !self.fractional_steps {
+            self.step_fraction = 0.0;
+        }
+        self.carry_step_deficit = get_bool("carry_step_deficit", self.carry_step_deficit);
+        if !self.carry_step_deficit {
+            self.step_deficit = 0;
+        }
+
+        self.calibration_max_rate = get("calibration_max_rate", self.calibration_max_rate).max(0.0);
+        self.burst_duration = get("burst_duration", self.burst_duration);
+        if let Some(control) = config.get("step_control").and_then(|v| v.as_str()).and_then(StepControl::parse) {
+            self.step_control = control;
+        }
+        self.model_burst_span = get("model_burst_span", self.model_burst_span).max(f64::MIN_POSITIVE);
+        // Only honoured with burst_duration <= 0; otherwise calibration picks dt.
+        let dt = get("dt", self.dt);
+        if dt > 0.0 {
+            self.dt = dt;
+        }
+        self.period_seconds = get("period_seconds", self.period_seconds);
+        self.update_burst_settings();
+
+        self.configure_tuner(config);
+
+        let cutoff = get("x_ac_cutoff", self.x_ac.cutoff);
+        if cutoff != self.x_ac.cutoff {
+            self.x_ac.cutoff = cutoff.max(0.0);
+            self.x_ac.reset();
+        }
+
+        self.approx_points = get("approx_points", self.approx_points as f64).max(8.0) as usize;
+        self.approximant = get_bool("approximant", self.approximant);
+        // The cycle is fitted to the x, y, z core alone.
+        if !self.approximant || self.whole_model() {
+            self.approx = None;
+            self.approx_pending = None;
+            self.approx_key = None;
+        } else if self.approx_key != Some(self.approx_key()) {
+            self.fit_approximant();
+        }
+
+        // Deferred changes, e.g. "at_burst_boundary": {"e": 3.5}, applied at
+        // the next burst onset or offset depending on "burst_anchor".
+        if let Some(anchor) = config.get("burst_anchor").and_then(|v| v.as_str()).and_then(Anchor::parse) {
+            self.switch.anchor = anchor;
+        }
+        if get_bool("cancel_scheduled", false) {
+            self.switch.cancel();
+        }
+        if let Some(overrides) = config.get("at_burst_boundary").and_then(|v| v.as_object()) {
+            self.switch.schedule(overrides);
+        }
+
+        // Target burst waveform in model units, inline or from a file
+        // holding a JSON array or plain numbers.
+        if let Some(template) = config.get("burst_template").and_then(|v| v.as_array()) {
+            let template: Option<Vec<f64>> = template.iter().map(|v| v.as_f64()).collect();
+            self.template.set_template(template.unwrap_or_default());
+        }
+        if let Some(path) = config.get("burst_template_file").and_then(|v| v.as_str()) {
+            let template = std::fs::read_to_string(path).ok();
+            if let Some(template) = template.as_deref().and_then(template::parse_waveform) {
+                self.template.set_template(template);
+            } else {
+                log_warn!(instance = self.id, path, "could not read burst template");
+            }
+        }
+        if let Some(distance) = config.get("template_distance").and_then(|v| v.as_str()).and_then(Distance::parse) {
+            self.template.distance = distance;
+        }
+        self.template.band = get("template_dtw_band", self.template.band).clamp(0.0, 1.0);
+        let bank = get("burst_bank_size", self.burst_bank.capacity() as f64).max(0.0) as usize;
+        self.burst_bank.set_capacity(bank);
+
+        self.spectrogram.configure(
+            get("spectrogram_window", self.spectrogram.window() as f64).max(0.0) as usize,
+            get("spectrogram_overlap", self.spectrogram.overlap()),
+            get("spectrogram_frames", self.spectrogram.capacity() as f64).max(1.0) as usize,
+        );
+
+        // Trial structure, e.g. "epochs": [{"duration": 10, "config": {"e": 3.5},
+        // "marker": 1}, ...]; an empty list disables it.
+        self.trials.looped = get_bool("epochs_loop", self.trials.looped);
+        if let Some(epochs) = config.get("epochs").and_then(|v| v.as_array()) {
+            self.trials.load(epochs);
+        } else if get_bool("restart_epochs", false) {
+            self.trials.restart();
         }
     }
-}
 
-extern "C" fn create(_id: u64) -> *mut c_void {
-    let instance = Box::new(HindmarshRosev2Rust::new());
-    Box::into_raw(instance) as *mut c_void
-}
+    fn run_trials(&mut self) {
+        let Some(overrides) = self.trials.tick(self.tick_period()).cloned() else {
+            return;
+        };
+        log_info!(instance = self.id, epoch = self.trials.epoch(), "entering epoch");
+        if !overrides.is_null() {
+            self.set_config(&overrides);
+        }
+    }
 
-extern "C" fn destroy(handle: *mut c_void) {
-    if handle.is_null() {
-        return;
+    // Host-applied keys, last value wins, for the run manifest. One-shot
+    // commands are left out since replaying them would not reproduce a run.
+    fn record_applied_config(&mut self, config: &Value) {
+        let Some(config) = config.as_object() else {
+            return;
+        };
+        for (key, value) in config.iter() {
+            if !ONE_SHOT_KEYS.contains(&key.as_str()) {
+                self.applied_config.insert(key.clone(), value.clone());
+            }
+        }
     }
-    unsafe {
-        drop(Box::from_raw(handle as *mut HindmarshRosev2Rust));
+
+    fn manifest(&self) -> Value {
+        let unix_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        serde_json::json!({
+            "plugin": "hindmarsh-rose-v2-rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "state_blob_version": state::VERSION,
+            "instance_id": self.id,
+            "config": Value::Object(self.applied_config.clone()),
+            "capabilities": capabilities(),
+            "seeds": {
+                "global": rng::global_seed(),
+                "instance": self.rng.seed(),
+                "param_drift": self.param_drift.seed()
+            },
+            "calibration": {
+                "dt": self.dt,
+                "s_points": self.s_points,
+                "burst_duration": self.burst_duration,
+                "period_seconds": self.period_seconds,
+                "calibration_changes": self.calibration_changes,
+                "step_control": self.step_control.name(),
+                "model_burst_span": self.model_burst_span
+            },
+            "parameters": {
+                "formulation": self.model.formulation().name(),
+                "model": self.model.name(),
+                "model_parameters": self.model.parameters(),
+                "e": self.e,
+                "mu": self.mu,
+                "s": self.s,
+                "vh": self.vh,
+                "i_ext": self.i_ext,
+                "freeze_z": self.freeze_z,
+                "clamp_z": self.clamp_z,
+                "z_clamp": self.z_clamp,
+                "temperature_factor_fast": self.temperature.fast,
+                "temperature_factor_slow": self.temperature.slow,
+                "canonical": formulation::canonical(&self.params(0.0), self.cfg_z)
+            },
+            "environment": {
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+                "debug_build": cfg!(debug_assertions),
+                "pid": std::process::id(),
+                "unix_time": unix_time
+            }
+        })
     }
-}
 
-extern "C" fn meta_json(_handle: *mut c_void) -> PluginString {
-    let value = serde_json::json!({
-        "name": "Hindmarsh Rose v2 Rust",
-        "default_vars": [
-            ["x", -0.9013],
-            ["y", -3.1594],
-            ["z", 3.24782],
-            ["e", 3.0],
-            ["mu", 0.006],
-            ["s", 4.0],
-            ["vh", 1.0],
-            ["dt", 0.15],
-            ["burst_duration", 1.0]
-        ]
-    });
-    PluginString::from_string(value.to_string())
-}
+    fn apply_scheduled(&mut self) {
+        let Some(overrides) = self.switch.take_ready() else {
+            return;
+        };
+        log_info!(instance = self.id, t = self.t, "scheduled config applied at burst boundary");
+        self.set_config(&overrides);
+        self.events.emit(EventKind::ScheduledConfigApplied, self.t);
+    }
 
-extern "C" fn inputs_json(_handle: *mut c_void) -> PluginString {
-    PluginString::from_string(serde_json::to_string(INPUTS).unwrap_or_default())
-}
+    fn configure_tuner(&mut self, config: &Value) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        if let Some(value) = config.get("es_param") {
+            let param = value.as_str().and_then(TunedParam::parse);
+            if param != self.tuner.as_ref().map(|tuner| tuner.param) {
+                self.tuner = param.map(|param| {
+                    ExtremumSeeker::new(param, Metric::BurstFrequency, self.param_value(param))
+                });
+            }
+        }
+        let Some(tuner) = self.tuner.as_mut() else {
+            return;
+        };
+        if let Some(metric) = config.get("es_metric").and_then(|v| v.as_str()).and_then(Metric::parse) {
+            tuner.metric = metric;
+        }
+        if let Some(goal) = config.get("es_goal").and_then(|v| v.as_str()) {
+            tuner.maximize = goal != "minimize";
+        }
+        tuner.amplitude = get("es_amplitude", tuner.amplitude);
+        tuner.frequency = get("es_frequency", tuner.frequency);
+        tuner.gain = get("es_gain", tuner.gain);
+        tuner.hp_tau = get("es_hp_tau", tuner.hp_tau);
+        let (min, max) = (get("es_min", tuner.min), get("es_max", tuner.max));
+        if min <= max {
+            tuner.min = min;
+            tuner.max = max;
+        }
+    }
 
-extern "C" fn outputs_json(_handle: *mut c_void) -> PluginString {
-    PluginString::from_string(serde_json::to_string(OUTPUTS).unwrap_or_default())
-}
+    fn param_value(&self, param: TunedParam) -> f64 {
+        match param {
+            TunedParam::E => self.e,
+            TunedParam::Mu => self.mu,
+            TunedParam::S => self.s,
+            TunedParam::Vh => self.vh,
+        }
+    }
 
-extern "C" fn behavior_json(_handle: *mut c_void) -> PluginString {
-    let behavior = serde_json::json!({
-        "supports_start_stop": true,
-        "supports_restart": true,
-        "extendable_inputs": {"type": "none"},
-        "loads_started": true
-    });
-    PluginString::from_string(behavior.to_string())
-}
+    fn set_param_value(&mut self, param: TunedParam, value: f64) {
+        match param {
+            TunedParam::E => self.e = value,
+            TunedParam::Mu => self.mu = value,
+            TunedParam::S => self.s = value,
+            TunedParam::Vh => self.vh = value,
+        }
+    }
 
-extern "C" fn ui_schema_json(_handle: *mut c_void) -> PluginString {
-    let schema = serde_json::json!({
-        "outputs": ["Membrane potential (V)", "Membrane potential (mV)"],
-        "inputs": ["i_syn"],
-        "variables": ["x", "y", "z"]
-    });
-    PluginString::from_string(schema.to_string())
-}
+    fn metric_value(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::BurstFrequency => self.burst_frequency().unwrap_or(0.0),
+            Metric::DutyCycle => self.bursts.duty_cycle().unwrap_or(0.0),
+            Metric::AbsFreqError => self
+                .burst_frequency()
+                .map_or(0.0, |freq| (freq - self.target_freq).abs()),
+            Metric::AbsDutyError => self
+                .bursts
+                .duty_cycle()
+                .map_or(0.0, |duty| (duty - self.target_duty).abs()),
+            Metric::ShadowDivergence => self.shadow_divergence,
+            Metric::TemplateDistance => self.template.last().unwrap_or(0.0),
+        }
+    }
 
-extern "C" fn set_config_json(handle: *mut c_void, data: *const u8, len: usize) {
-    if handle.is_null() || data.is_null() || len == 0 {
-        return;
+    fn run_tuner(&mut self) {
+        let Some(metric) = self.tuner.as_ref().map(|tuner| tuner.metric) else {
+            return;
+        };
+        let value = self.metric_value(metric);
+        let period = self.tick_period();
+        if let Some(tuner) = self.tuner.as_mut() {
+            let param = tuner.param;
+            let applied = tuner.update(value, period);
+            self.set_param_value(param, applied);
+        }
     }
-    let slice = unsafe { std::slice::from_raw_parts(data, len) };
-    if let Ok(json) = serde_json::from_slice::<Value>(slice) {
-        let instance = unsafe { &mut *(handle as *mut HindmarshRosev2Rust) };
-        instance.set_config(&json);
+
+    // Everything a fit depends on, so set_config refits only when one of
+    // them changes.
+    fn approx_key(&self) -> (Params<f64>, f64, Integrator, bool, usize, f64) {
+        (
+            self.params(0.0),
+            self.dt.min(0.05),
+            self.stepper(),
+            self.fixed_point,
+            self.approx_points,
+            self.events.spike_threshold,
+        )
     }
-}
 
-extern "C" fn set_input(handle: *mut c_void, name: *const u8, len: usize, value: f64) {
-    if handle.is_null() || name.is_null() || len == 0 {
-        return;
+    // Queues a fit on the approximant worker, so set_config stays cheap even
+    // on the tick thread. The current fit, if any, stays in use until
+    // poll_approximant picks up the new one; if no limit cycle is found the
+    // plugin keeps integrating normally.
+    fn fit_approximant(&mut self) {
+        let key = self.approx_key();
+        let (params, dt, stepper, fixed_point, points, threshold) = key;
+        self.approx_key = Some(key);
+        self.approx_pending = Some(PendingFit::submit([self.x, self.y, self.z], dt, points, threshold, move |vars, h, bias| {
+            step_field(stepper, fixed_point, vars, h, &Params { input: params.input - bias, ..params })
+        }));
     }
-    let slice = unsafe { std::slice::from_raw_parts(name, len) };
-    if let Ok(name) = std::str::from_utf8(slice) {
-        if name == "i_syn" {
-            let instance = unsafe { &mut *(handle as *mut HindmarshRosev2Rust) };
-            instance.input_syn = value;
+
+    fn poll_approximant(&mut self) {
+        let Some(cycle) = self.approx_pending.as_ref().and_then(PendingFit::poll) else {
+            return;
+        };
+        // A period of None means no cycle was found and integration continues.
+        log_info!(instance = self.id, period = ?cycle.as_ref().map(LimitCycle::period), "approximant refit");
+        self.approx = cycle;
+        self.approx_pending = None;
+        self.approx_phase = 0.0;
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            t: self.t,
+            steps: self.steps,
+            rng_positions: self.rng.positions(),
+            u: self.model.state().first().copied().unwrap_or(0.0),
+            cells: self
+                .half_center
+                .iter()
+                .map(HalfCenter::state)
+                .chain(self.population.iter().flat_map(|population| population.cells().iter().copied()))
+                .collect(),
+            param_drift_position: self.param_drift.position(),
+            drift_offsets: [self.param_drift.e.offset(), self.param_drift.mu.offset()],
+            prep: self.prep.as_ref().map(SimulatedPrep::snapshot),
+            controller: self.adaptive.snapshot(),
         }
     }
-}
 
-extern "C" fn process(handle: *mut c_void, _tick: u64, period_seconds: f64) {
-    if handle.is_null() {
-        return;
+    // False, leaving the state untouched, when the snapshot's cells do not
+    // match the configured half-center and population.
+    fn restore(&mut self, snapshot: &Snapshot) -> bool {
+        let cells = usize::from(self.half_center.is_some()) + self.population.as_ref().map_or(0, |population| population.size() - 1);
+        if snapshot.cells.len() != cells {
+            return false;
+        }
+        let mut restored = snapshot.cells.iter().copied();
+        if let Some(half_center) = &mut self.half_center {
+            half_center.set_state(restored.next().unwrap_or_default());
+        }
+        if let Some(population) = &mut self.population {
+            for (cell, state) in population.cells_mut().iter_mut().zip(restored) {
+                *cell = state;
+            }
+        }
+        self.x = snapshot.x;
+        self.y = snapshot.y;
+        self.z = snapshot.z;
+        self.t = snapshot.t;
+        self.steps = snapshot.steps;
+        self.rng.set_positions(snapshot.rng_positions);
+        self.param_drift.restore(snapshot.param_drift_position, snapshot.drift_offsets);
+        // A preparation is resumed only when both sides have one.
+        if let (Some(prep), Some(saved)) = (&mut self.prep, &snapshot.prep) {
+            prep.restore(saved);
+        }
+        self.adaptive.restore(&snapshot.controller);
+        // The snapshot format holds one extra state variable.
+        if let Some(u) = self.model.state_mut().first_mut() {
+            *u = snapshot.u;
+        }
+        self.resync_shadow();
+        self.rebase_balance();
+        self.multirate.restart();
+        self.dense.clear();
+        self.multistep.restart();
+        self.restart_compensation();
+        true
+    }
+
+    // `stepped` is set when the dense record holds the step that just ended.
+    fn observe_events(&mut self, finite: bool, stepped: bool) {
+        self.switch.poll(self.t, self.events.burst_gap);
+        let mut t = self.t;
+        if self.precise_spikes && stepped && self.x >= self.events.spike_threshold {
+            t = self.dense.crossing(0, self.events.spike_threshold).unwrap_or(t);
+        }
+        let detected = self.events.observe(self.x, finite, t);
+        if finite {
+            self.bursts.sample(self.x);
+        }
+        self.event_latch.observe(detected);
+        let Some(kind) = detected else {
+            return;
+        };
+        self.bursts.on_spike(t, kind == EventKind::BurstOnset);
+        self.last_spike_time = Some(self.elapsed_seconds + (t - self.tick_start) * self.seconds_per_model_unit());
+        self.switch.on_spike(self.t, kind == EventKind::BurstOnset);
+        self.template.on_spike(kind == EventKind::BurstOnset);
+        self.burst_bank.on_spike(kind == EventKind::BurstOnset);
+        self.watchdog.on_spike();
     }
-    let instance = unsafe { &mut *(handle as *mut HindmarshRosev2Rust) };
-    
-    // ALWAYS use the period_seconds from runtime, not from config
-    // This ensures the plugin respects workspace period settings
-    if (instance.period_seconds - period_seconds).abs() > f64::EPSILON {
-        instance.period_seconds = period_seconds;
-        instance.update_burst_settings();
+
+    fn run_watchdog(&mut self) {
+        let Some(intervention) = self.watchdog.tick(self.tick_period()) else {
+            return;
+        };
+        log_warn!(instance = self.id, t = self.t, ?intervention, "quiescence watchdog intervened");
+        if intervention == Intervention::Reset {
+            self.reset_state();
+        }
+        self.events.emit(EventKind::WatchdogIntervention, self.t);
     }
-    
-    instance.process();
-}
 
-extern "C" fn get_output(handle: *mut c_void, name: *const u8, len: usize) -> f64 {
-    if handle.is_null() || name.is_null() || len == 0 {
-        return 0.0;
+    // Seconds of host time per unit of model time at the current calibration.
+    fn seconds_per_model_unit(&self) -> f64 {
+        let model_per_tick = self.model_dt() * self.exact_steps;
+        if model_per_tick > 0.0 {
+            self.period_seconds / model_per_tick
+        } else {
+            0.0
+        }
     }
-    let slice = unsafe { std::slice::from_raw_parts(name, len) };
-    if let Ok(name) = std::str::from_utf8(slice) {
-        let instance = unsafe { &mut *(handle as *mut HindmarshRosev2Rust) };
-        return match name {
-            "x" => instance.x,
-            "y" => instance.y,
-            "z" => instance.z,
-            "Membrane potential (V)" => instance.x,
-            "Membrane potential (mV)" => instance.x * 1000.0,
-            _ => 0.0,
+
+    fn burst_frequency(&self) -> Option<f64> {
+        let period = self.bursts.period? * self.seconds_per_model_unit();
+        (period > 0.0).then(|| 1.0 / period)
+    }
+
+    // Returns to the configured initial conditions, jittered uniformly by
+    // +/- ic_jitter from the instance's random stream.
+    fn reset_state(&mut self) {
+        let rng = self.rng.stream(Stream::Jitter);
+        let mut jitter = || (2.0 * rng.uniform() - 1.0) * self.ic_jitter;
+        let offsets = if self.ic_jitter > 0.0 {
+            [jitter(), jitter(), jitter()]
+        } else {
+            [0.0; 3]
         };
+        self.x = self.cfg_x + offsets[0];
+        self.y = self.cfg_y + offsets[1];
+        self.z = self.cfg_z + offsets[2];
+        self.model.reset();
+        if let Some(half_center) = &mut self.half_center {
+            half_center.reset();
+        }
+        if let Some(clamp) = &mut self.dynamic_clamp {
+            clamp.reset();
+        }
+        if let Some(population) = &mut self.population {
+            population.reset();
+        }
+        self.delayed_feedback.reset();
+        self.resync_shadow();
+        self.rebase_balance();
+        self.multirate.restart();
+        self.dense.clear();
+        self.multistep.restart();
+        self.restart_compensation();
+        self.soft_start.restart();
+        self.trajectory_checksum = CHECKSUM_SEED;
     }
-    0.0
-}
 
-#[no_mangle]
-pub extern "C" fn rtsyn_plugin_api() -> *const PluginApi {
-    static API: PluginApi = PluginApi {
-        create,
-        destroy,
-        meta_json,
-        inputs_json,
-        outputs_json,
-        behavior_json: Some(behavior_json),
-        ui_schema_json: Some(ui_schema_json),
-        set_config_json,
-        set_input,
-        process,
-        get_output,
-    };
-    &API as *const PluginApi
+    // Pins z to z_clamp, restarting whatever tracked the old value.
+    fn apply_z_clamp(&mut self) {
+        if !self.clamp_z || self.z == self.z_clamp {
+            return;
+        }
+        self.z = self.z_clamp;
+        self.resync_shadow();
+        self.rebase_balance();
+        self.multirate.restart();
+        self.dense.clear();
+        self.multistep.restart();
+        self.restart_compensation();
+    }
+
+    // Multiplies z and everything expressed in its units by `factor`.
+    fn rescale_z(&mut self, factor: f64) {
+        if factor == 1.0 {
+            return;
+        }
+        self.z *= factor;
+        self.cfg_z *= factor;
+        self.z_clamp *= factor;
+        self.resync_shadow();
+        self.rebase_balance();
+        self.multirate.restart();
+        self.dense.clear();
+        self.multistep.restart();
+        self.restart_compensation();
+    }
+
+    fn restart_compensation(&mut self) {
+        self.state_sum.reset();
+        self.time_sum.reset();
+    }
+
+    // d/dt of the model's extra state variables, by name.
+    fn model_rates(&self) -> serde_json::Map<String, Value> {
+        let state = self.model.full_state([self.x, self.y, self.z]);
+        let rates = self.model.field(state, &self.core_params(self.drive()));
+        self.model.state_names().iter().map(|name| name.to_string()).zip(rates[3..].iter().map(|&rate| Value::from(rate))).collect()
+    }
+
+    fn resync_shadow(&mut self) {
+        if self.shadow_refinement > 0 {
+            self.shadow = Some([self.x, self.y, self.z]);
+        }
+        self.shadow_since_sync = 0.0;
+        self.shadow_divergence = 0.0;
+    }
+
+    fn rebase_balance(&mut self) {
+        let rate = derivatives([self.x, self.y, self.z], &self.params(self.drive()))[2];
+        self.balance.rebase(self.z, rate);
+        self.rebase_energy();
+    }
+
+    fn rebase_energy(&mut self) {
+        let vars = [self.x, self.y, self.z];
+        let params = self.params(self.drive());
+        self.energy.rebase(energy::energy(vars, &params), energy::energy_rate(vars, &params));
+    }
+
+    // The shadow copy integrates the same inputs with `shadow_refinement`
+    // substeps per main step; its distance to the main state estimates the
+    // accumulated numerical error since the last resync.
+    fn advance_shadow(&mut self, vars: [f64; 3], dt: f64) {
+        let Some(mut shadow) = self.shadow else {
+            return;
+        };
+        let n = self.shadow_refinement.max(1);
+        let h = dt / n as f64;
+        for _ in 0..n {
+            shadow = self.integrate_step(shadow, h);
+        }
+        self.shadow_divergence = vars
+            .iter()
+            .zip(shadow.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        self.shadow_since_sync += dt.abs();
+        if self.shadow_resync > 0.0 && self.shadow_since_sync >= self.shadow_resync {
+            shadow = vars;
+            self.shadow_since_sync = 0.0;
+        }
+        self.shadow = Some(shadow);
+    }
+
+    fn stats(&self) -> Value {
+        let custom_output_errors: serde_json::Map<String, Value> = self
+            .custom_output_errors
+            .iter()
+            .map(|(name, error)| (name.clone(), Value::String(error.clone())))
+            .collect();
+        let config_errors: serde_json::Map<String, Value> = self
+            .config_errors
+            .iter()
+            .map(|(key, error)| (key.clone(), Value::String(error.clone())))
+            .collect();
+        let rng_positions: serde_json::Map<String, Value> = Stream::ALL
+            .iter()
+            .zip(self.rng.positions())
+            .map(|(stream, position)| (stream.name().to_string(), Value::from(position)))
+            .collect();
+        serde_json::json!({
+            "t": self.t,
+            "elapsed_seconds": self.elapsed_seconds,
+            "integrator": self.integrator.name(),
+            "formulation": self.model.formulation().name(),
+            "model": self.model.name(),
+            "u": self.model.value("u"),
+            "model_rates": self.model_rates(),
+            "freeze_z": self.freeze_z,
+            "clamp_z": self.clamp_z,
+            "z_clamp": self.clamp_z.then_some(self.z_clamp),
+            "temperature_factor": {
+                "fast": self.temperature.fast,
+                "slow": self.temperature.slow
+            },
+            "canonical": formulation::canonical(&self.params(self.drive()), self.z),
+            "step_control": self.step_control.name(),
+            "multistep_startup_steps": self.multistep.startup_steps(),
+            "richardson": {
+                "enabled": self.richardson.enabled,
+                "max_error": self.richardson.max_error(),
+                "mean_error": self.richardson.mean_error()
+            },
+            "stiffness": {
+                "enabled": self.stiffness.enabled,
+                "threshold": self.stiffness.threshold,
+                "stiff": self.stiffness.stiff(),
+                "ratio": self.stiffness.ratio(),
+                "switches": self.stiffness.switches(),
+                "implicit_steps": self.stiffness.implicit_steps()
+            },
+            "adaptive": {
+                "accepted_steps": self.adaptive.accepted(),
+                "rejected_steps": self.adaptive.rejected(),
+                "mean_error": self.adaptive.mean_error(),
+                "step_size": self.adaptive.step_size(),
+                "abs_tol": self.adaptive.abs_tol,
+                "rel_tol": self.adaptive.rel_tol
+            },
+            "batch_ticks": self.batch.every(),
+            "multirate_ratio": self.multirate.ratio,
+            "exponential_z": self.multirate.exponential_z,
+            "compensated_summation": self.compensated,
+            "state_low_words": self.state_low_words(),
+            "trajectory_checksum": format!("{:016x}", self.trajectory_checksum),
+            "fixed_point": self.fixed_point,
+            "dense_output": self.dense.enabled,
+            "idle": self.idle.is_some(),
+            "idle_ticks": self.idle_ticks,
+            "steps": self.steps,
+            "dt": self.dt,
+            "s_points": self.s_points,
+            "reverse_time": self.reverse_time,
+            "exact_steps": self.exact_steps,
+            "step_fraction": self.step_fraction,
+            "step_deficit": self.step_deficit,
+            "max_steps_per_tick": self.max_steps,
+            "steps_clamped": self.steps_clamped,
+            "approx_period": self.approx.as_ref().map(|cycle| cycle.period()),
+            "approx_fitting": self.approx_pending.is_some(),
+            "inputs": {
+                "i_syn": {
+                    "age": self.input_syn.age(),
+                    "stale": self.input_syn.is_stale(&self.staleness),
+                    "median_window": self.input_syn.median.window()
+                },
+                "i_ext": {
+                    "age": self.input_ext.age(),
+                    "stale": self.input_ext.is_stale(&self.staleness),
+                    "median_window": self.input_ext.median.window()
+                },
+                "period_in": {
+                    "age": self.period_in.age(),
+                    "stale": self.period_in.is_stale(&self.staleness)
+                },
+                "v_pre": {
+                    "age": self.v_pre.age(),
+                    "stale": self.v_pre.is_stale(&self.staleness)
+                },
+                "v_pre_syn": {
+                    "age": self.v_pre_syn.age(),
+                    "stale": self.v_pre_syn.is_stale(&self.staleness)
+                }
+            },
+            "burst_duration": self.burst_duration,
+            "calibration_changes": self.calibration_changes,
+            "burst_frequency": self.burst_frequency(),
+            "duty_cycle": self.bursts.duty_cycle(),
+            "cost": {
+                "estimated_flops_per_tick": self.estimated_flops_per_tick(),
+                "mean_tick_ns": self.cost.mean_ns(),
+                "max_tick_ns": self.cost.max_ns()
+            },
+            "watchdog_interventions": self.watchdog.interventions(),
+            "divergence": {
+                "policy": self.divergence.policy.name(),
+                "recoveries": self.divergence.recoveries(),
+                "last_trip": self.divergence.last_trip()
+            },
+            "fault": self.interlock.fault(),
+            "fault_trips": self.interlock.trips(),
+            "soft_start_gain": self.soft_start.gain(),
+            "scheduled_pending": self.switch.pending(),
+            "scheduled_applied": self.switch.applied(),
+            "template_distance": self.template.last(),
+            "template_comparisons": self.template.comparisons(),
+            "stored_bursts": self.burst_bank.len(),
+            "spectrogram": {
+                "window": self.spectrogram.window(),
+                "hop": self.spectrogram.hop(),
+                "bins": self.spectrogram.bins(),
+                "frames": self.spectrogram.frames(),
+                "frame_period_s": self.spectrogram.hop() as f64 * self.period_seconds,
+                "bin_hz": if self.spectrogram.enabled() {
+                    1.0 / (self.spectrogram.window() as f64 * self.period_seconds)
+                } else {
+                    0.0
+                }
+            },
+            "custom_output_errors": custom_output_errors,
+            "config_errors": config_errors,
+            "z_balance_residual": self.balance.residual(),
+            "z_balance_jumps": self.balance.jumps(),
+            "energy": {
+                "enabled": self.energy.enabled,
+                "drift": self.energy.drift(),
+                "max_drift": self.energy.max_drift()
+            },
+            "noise_sigma": self.noise.sigma,
+            "param_drift": {
+                "seed": self.param_drift.seed(),
+                "e_offset": self.param_drift.e.offset(),
+                "mu_offset": self.param_drift.mu.offset()
+            },
+            "event_phase": {
+                "counts": self.event_phases.counts(),
+                "events": self.event_phases.events(),
+                "circular_mean": self.event_phases.circular_mean(),
+                "vector_strength": self.event_phases.vector_strength()
+            },
+            "barrier_group": self.barrier.as_ref().map(Membership::group),
+            "barrier_members": self.barrier.as_ref().map_or(0, Membership::members),
+            "external_current": self.external_current(),
+            "coupling_current": self.coupling_current,
+            "gap_current": self.gap_current(),
+            "graded_synapse": {
+                "activation": self.graded.activation(),
+                "current": self.graded_current()
+            },
+            "conductance_current": self.conductance.current(self.x),
+            "delayed_feedback": {
+                "current": self.delayed_feedback.current(self.x),
+                "history": self.delayed_feedback.history_len()
+            },
+            "spike_input": {
+                "kernel": self.spike_input.kernel.name(),
+                "current": self.spike_input.current(),
+                "received": self.spike_input.received()
+            },
+            "epoch": self.trials.epoch(),
+            "epoch_repetitions": self.trials.repetitions(),
+            "instance_id": self.id,
+            "seed": self.rng.seed(),
+            "control_address": self.control.as_ref().map(ControlServer::address),
+            "cosim": self.cosim.as_ref().map(|cosim| cosim.stats(self.tick)),
+            "simulated_prep": self.prep.as_ref().map(SimulatedPrep::stats),
+            "half_center": self.half_center.as_ref().map(HalfCenter::stats),
+            "dynamic_clamp": self.dynamic_clamp.as_ref().map(DynamicClamp::stats),
+            "population": self.population.as_ref().map(Population::stats),
+            "rng_positions": rng_positions,
+            "agc": {
+                "gain": self.agc.gain(),
+                "offset": self.agc.offset()
+            },
+            "tuner": self.tuner.as_ref().map(|tuner| serde_json::json!({
+                "param": tuner.param.name(),
+                "estimate": tuner.estimate()
+            }))
+        })
+    }
+
+    #[cfg(feature = "autodiff")]
+    fn field_gradients(&self) -> Value {
+        let vars = [self.x, self.y, self.z];
+        serde_json::json!(field::parameter_gradients(vars, &self.params(self.drive())))
+    }
+
+    #[cfg(not(feature = "autodiff"))]
+    fn field_gradients(&self) -> Value {
+        Value::Null
+    }
+
+    // Analytic Jacobian of the vector field at the current state and drive.
+    fn jacobian(&self) -> [[f64; 3]; 3] {
+        jacobian([self.x, self.y, self.z], &self.params(self.drive()))
+    }
+
+    // Local stability at the current state for live display: the Jacobian,
+    // its eigenvalues as [re, im] pairs and whether all of them decay.
+    fn stability(&self) -> Value {
+        let jac = self.jacobian();
+        let eigenvalues = stiffness::eigenvalues(&jac);
+        let trace = jac[0][0] + jac[1][1] + jac[2][2];
+        let determinant = jac[0][0] * (jac[1][1] * jac[2][2] - jac[1][2] * jac[2][1])
+            - jac[0][1] * (jac[1][0] * jac[2][2] - jac[1][2] * jac[2][0])
+            + jac[0][2] * (jac[1][0] * jac[2][1] - jac[1][1] * jac[2][0]);
+        serde_json::json!({
+            "t": self.t,
+            "state": [self.x, self.y, self.z],
+            "jacobian": jac,
+            "eigenvalues": eigenvalues.map(|(re, im)| [re, im]),
+            "trace": trace,
+            "determinant": determinant,
+            "max_real": eigenvalues.iter().map(|&(re, _)| re).fold(f64::NEG_INFINITY, f64::max),
+            "stable": eigenvalues.iter().all(|&(re, _)| re < 0.0)
+        })
+    }
+
+    // Membrane variable as seen on the outputs, including any injected test drift.
+    fn output_x(&self) -> f64 {
+        self.dense.state().map_or(self.x, |state| state[0]) + self.drift.value()
+    }
+
+    // Model time that corresponds exactly to the host time covered by a
+    // tick; fixed-dt stepping rounds it to a whole number of steps.
+    fn exact_tick_span(&self) -> f64 {
+        self.model_dt() * self.exact_steps * self.tick_span as f64
+    }
+
+    // Steps to take this tick: s_points per period plus, with
+    // fractional_steps, whole steps accumulated from the rounding of
+    // exact_steps, so model time keeps pace with the host clock.
+    fn tick_steps(&mut self) -> usize {
+        let steps = self.s_points.max(1) * self.tick_span;
+        if !self.fractional_steps {
+            return steps;
+        }
+        self.step_fraction += (self.exact_steps - self.s_points as f64) * self.tick_span as f64;
+        let carried = self.step_fraction.trunc();
+        self.step_fraction -= carried;
+        (steps as f64 + carried).max(0.0) as usize
+    }
+
+    fn output(&self, name: &str) -> f64 {
+        let (gain, offset) = self
+            .output_scaling
+            .iter()
+            .find(|(scaled, _, _)| scaled == name)
+            .map_or((1.0, 0.0), |(_, gain, offset)| (*gain, *offset));
+        let name = self
+            .output_aliases
+            .iter()
+            .find(|(alias, _)| alias == name)
+            .map_or(name, |(_, target)| target.as_str());
+        if DRIVE_OUTPUTS.contains(&name) {
+            if self.interlock.blocks_outputs() || self.divergence.blocks_outputs() {
+                return 0.0;
+            }
+            return self.soft_start.gain() * (gain * self.raw_output(name) + offset);
+        }
+        gain * self.raw_output(name) + offset
+    }
+
+    fn raw_output(&self, name: &str) -> f64 {
+        match name {
+            "x" | "y" | "z" => {
+                let index = ["x", "y", "z"].iter().position(|var| *var == name).unwrap_or(0);
+                self.dense.state().map_or([self.x, self.y, self.z][index], |state| state[index])
+            }
+            "Membrane potential (V)" => self.output_x(),
+            "Membrane potential (mV)" => self.output_x() * 1000.0,
+            "Shadow divergence" => self.shadow_divergence,
+            "freq_error" => self
+                .burst_frequency()
+                .map_or(0.0, |freq| freq - self.target_freq),
+            "duty_error" => self
+                .bursts
+                .duty_cycle()
+                .map_or(0.0, |duty| duty - self.target_duty),
+            "x_ac" => self.x_ac.output(),
+            "Scaled membrane potential" => self.agc.scale(self.output_x()),
+            "Tuned parameter" => self.tuner.as_ref().map_or(0.0, |tuner| tuner.estimate()),
+            "fault" => f64::from(self.interlock.fault()),
+            "diverged" => f64::from(self.divergence.tripped()),
+            "steps_clamped" => self.steps_clamped as f64,
+            "spike_time" => self.last_spike_time.unwrap_or(0.0),
+            "richardson_error" => self.richardson.tick_error(),
+            "energy_drift" => self.energy.drift(),
+            "Input stale" => f64::from(self.input_syn.is_stale(&self.staleness)),
+            "template_distance" => self.template.last().unwrap_or(0.0),
+            "epoch" => self.trials.epoch(),
+            "marker" => self.trials.marker(),
+            "z_balance_residual" => self.balance.residual(),
+            "solver_switches" => self.stiffness.switches() as f64,
+            "implicit_active" => f64::from(self.stiffness.stiff()),
+            "prep_v" => self.prep.as_ref().map_or(0.0, SimulatedPrep::recorded),
+            "prep_x" => self.prep.as_ref().map_or(0.0, SimulatedPrep::x),
+            "x1" | "x[0]" => self.raw_output("x"),
+            "x2" => self.half_center.as_ref().map_or(0.0, HalfCenter::x),
+            "i_inject" => self.dynamic_clamp.as_ref().map_or(0.0, DynamicClamp::i_inject),
+            "adaptive_dt" => self.adaptive.step_size(),
+            "step_rejections" => self.adaptive.tick_rejected() as f64,
+            "cycle_mean_x" => self.bursts.cycle_mean.unwrap_or(0.0),
+            "cycle_peak_x" => self.bursts.cycle_peak.unwrap_or(0.0),
+            "cycle_period" => self
+                .bursts
+                .period
+                .map_or(0.0, |period| period * self.seconds_per_model_unit()),
+            name if EVENT_OUTPUTS.contains(&name) => {
+                let index = EVENT_OUTPUTS.iter().position(|event| *event == name).unwrap_or(0);
+                f64::from(self.event_latch.value(index))
+            }
+            name if CURRENT_TERMS.contains(&name) => {
+                let terms = field::current_terms([self.x, self.y, self.z], &self.params(self.drive()));
+                CURRENT_TERMS
+                    .iter()
+                    .position(|term| *term == name)
+                    .map_or(0.0, |i| terms[i])
+            }
+            _ if name.starts_with("x[") => self.population.as_ref().and_then(|population| population.output(name)).unwrap_or(0.0),
+            _ => self.model.value(name).unwrap_or_else(|| {
+                self.custom_outputs
+                    .iter()
+                    .find(|(custom, _, _)| custom == name)
+                    .map_or(0.0, |(_, _, value)| *value)
+            }),
+        }
+    }
+
+    fn evaluate_custom_outputs(&mut self) {
+        let values = [
+            self.x,
+            self.y,
+            self.z,
+            self.t,
+            self.e,
+            self.mu,
+            self.s,
+            self.vh,
+            self.drive(),
+            self.x_ac.output(),
+        ];
+        for (_, expr, value) in &mut self.custom_outputs {
+            *value = expr.eval(&values);
+        }
+    }
+
+    fn input_names(&self) -> Vec<&str> {
+        let mut names = INPUTS.to_vec();
+        if self.g_gap != 0.0 {
+            names.push("v_pre");
+        }
+        if self.graded.enabled() {
+            names.push("v_pre_syn");
+        }
+        if self.spike_input.enabled() {
+            names.push("spike_in");
+        }
+        if self.conductance.enabled {
+            names.extend(["g_syn_exc", "g_syn_inh"]);
+        }
+        if self.clamp_z {
+            names.push("z_clamp");
+        }
+        if self.dynamic_clamp.is_some() {
+            names.push("v_m");
+        }
+        if self.half_center.is_some() {
+            names.extend(["i_syn1", "i_syn2"]);
+        }
+        if let Some(population) = &self.population {
+            names.extend(population.input_names());
+        }
+        names
+    }
+
+    fn output_names(&self) -> Vec<&str> {
+        let mut names = OUTPUTS.to_vec();
+        names.push("Input stale");
+        names.push("diverged");
+        names.push("steps_clamped");
+        if self.interlock.limit > 0.0 {
+            names.push("fault");
+        }
+        names.push("freq_error");
+        names.push("duty_error");
+        names.extend(self.model.state_names());
+        if self.shadow.is_some() {
+            names.push("Shadow divergence");
+        }
+        if self.tuner.is_some() {
+            names.push("Tuned parameter");
+        }
+        if self.x_ac.cutoff > 0.0 {
+            names.push("x_ac");
+        }
+        if self.agc.enabled() {
+            names.push("Scaled membrane potential");
+        }
+        if self.template.enabled() {
+            names.push("template_distance");
+        }
+        if !self.trials.is_empty() {
+            names.push("epoch");
+            names.push("marker");
+        }
+        if self.event_outputs {
+            names.extend(EVENT_OUTPUTS);
+        }
+        if self.error_controlled() {
+            names.extend(["adaptive_dt", "step_rejections"]);
+        }
+        if self.cycle_outputs {
+            names.extend(["cycle_mean_x", "cycle_peak_x", "cycle_period"]);
+        }
+        if self.current_outputs {
+            names.extend(CURRENT_TERMS);
+        }
+        if self.balance.enabled {
+            names.push("z_balance_residual");
+        }
+        if self.stiffness.enabled {
+            names.extend(["solver_switches", "implicit_active"]);
+        }
+        if self.precise_spikes {
+            names.push("spike_time");
+        }
+        if self.richardson.enabled {
+            names.push("richardson_error");
+        }
+        if self.energy.enabled {
+            names.push("energy_drift");
+        }
+        if self.prep.is_some() {
+            names.extend(["prep_v", "prep_x"]);
+        }
+        if self.half_center.is_some() {
+            names.extend(["x1", "x2"]);
+        }
+        if self.dynamic_clamp.is_some() {
+            names.push("i_inject");
+        }
+        if let Some(population) = &self.population {
+            names.extend(population.output_names());
+        }
+        names.extend(self.custom_outputs.iter().map(|(name, _, _)| name.as_str()));
+        names.extend(self.output_aliases.iter().map(|(alias, _)| alias.as_str()));
+        names
+    }
+
+    // Parameters shared with the coupled cells of half_center.rs and
+    // population.rs. With freeze_z or clamp_z the slow timescale is zero, so
+    // z stays where it is and acts as a constant bias: the 2D fast subsystem,
+    // which spikes tonically.
+    fn cell_params(&self, input: f64) -> Params<f64> {
+        Params {
+            e: self.e + self.param_drift.e.offset(),
+            mu: if self.freeze_z || self.clamp_z { 0.0 } else { self.temperature.slow_rate(self.mu + self.param_drift.mu.offset()) },
+            s: self.s,
+            vh: self.vh,
+            input,
+            shape: self.shape,
+        }
+    }
+
+    // The parameters this cell's model field takes: the shared ones plus
+    // conductance inputs and delayed feedback.
+    fn core_params(&self, input: f64) -> Params<f64> {
+        let mut params = self.cell_params(input);
+        if let Some((shape, input)) = self.conductance.fold(params.shape(), params.input) {
+            params.shape = Some(shape);
+            params.input = input;
+        }
+        if let Some((shape, input)) = self.delayed_feedback.fold(params.shape(), params.input) {
+            params.shape = Some(shape);
+            params.input = input;
+        }
+        params
+    }
+
+    // This cell's core field, with its model's extra state folded in.
+    fn params(&self, input: f64) -> Params<f64> {
+        let mut params = self.core_params(input);
+        if let Some(shape) = self.model.shape(params.shape()) {
+            params.shape = Some(shape);
+        }
+        params
+    }
+
+    // Bias current added to dx/dt: the i_ext config value plus the i_ext port.
+    fn external_current(&self) -> f64 {
+        self.i_ext + self.input_ext.value
+    }
+
+    // Input current entering the x equation (subtracted, like i_syn).
+    fn drive(&self) -> f64 {
+        self.input_syn.value - self.external_current() - self.watchdog.kick()
+            + self.coupling_current
+            + self.half_center_current
+            + self.population_current
+            - self.gap_current()
+            + self.graded_current()
+            + self.spike_input.current()
+            + self.cosim_current()
+    }
+
+    fn graded_current(&self) -> f64 {
+        if !self.graded.enabled() {
+            return 0.0;
+        }
+        self.graded.current(self.x)
+    }
+
+    // g_gap (v_pre - x), depolarizing while v_pre is above x. Like the other
+    // coupling currents it is held over a tick, at the tick's starting x.
+    fn gap_current(&self) -> f64 {
+        if self.g_gap == 0.0 {
+            return 0.0;
+        }
+        self.g_gap * (self.v_pre.value - self.x)
+    }
+
+    fn cosim_current(&self) -> f64 {
+        self.cosim.as_ref().map_or(0.0, |cosim| cosim.current)
+    }
+
+    // Holds the last coupling value while the peer has not published yet.
+    fn read_coupling(&mut self) {
+        let (Some(barrier), Some(coupling)) = (&self.barrier, self.coupling) else {
+            return;
+        };
+        if let Some(state) = barrier.read(coupling.peer, self.tick) {
+            self.coupling_current = coupling.gain * state[coupling.variable];
+        }
+    }
+
+    fn integrate_step(&self, vars: [f64; 3], dt: f64) -> [f64; 3] {
+        self.integrate_step_with(vars, dt, self.drive())
+    }
+
+    fn integrate_step_with(&self, vars: [f64; 3], dt: f64, input: f64) -> [f64; 3] {
+        step_field(self.stepper(), self.fixed_point, vars, dt, &self.params(input))
+    }
+
+    // The configured integrator, or backward Euler while the stiffness
+    // monitor has switched over.
+    fn stepper(&self) -> Integrator {
+        if self.stiffness.stiff() {
+            Integrator::BackwardEuler
+        } else {
+            self.integrator
+        }
+    }
+
+    // Re-evaluates stiffness at the current state ahead of a step of `h`.
+    fn check_stiffness(&mut self, h: f64, limit: f64) {
+        if !self.stiffness.enabled {
+            return;
+        }
+        if self.stiffness.update(&self.jacobian(), h, limit) {
+            self.multistep.restart();
+            log_info!(
+                instance = self.id,
+                stiff = self.stiffness.stiff(),
+                ratio = self.stiffness.ratio(),
+                "solver switched"
+            );
+        }
+        if self.stiffness.stiff() {
+            self.stiffness.record_implicit();
+        }
+    }
+
+    // Returns false for an unknown input name.
+    // Internal synapse weights by (post, pre) neuron, neuron 0 being this
+    // instance's cell: the population's matrix when one is configured,
+    // otherwise the half-center pair, whose one g serves both directions.
+    fn weight(&self, post: usize, pre: usize) -> Result<f64, Status> {
+        if let Some(population) = &self.population {
+            return population.weight(post, pre).ok_or(Status::BadArgument);
+        }
+        let Some(half_center) = &self.half_center else {
+            return Err(Status::NotConfigured);
+        };
+        match (post, pre) {
+            (0, 1) | (1, 0) => Ok(half_center.g()),
+            _ => Err(Status::BadArgument),
+        }
+    }
+
+    fn set_weight(&mut self, post: usize, pre: usize, weight: f64) -> Status {
+        if !weight.is_finite() {
+            return Status::BadArgument;
+        }
+        if let Err(status) = self.weight(post, pre) {
+            return status;
+        }
+        if let Some(population) = &mut self.population {
+            population.set_weight(post, pre, weight);
+        } else if let Some(half_center) = &mut self.half_center {
+            half_center.set_g(weight);
+        }
+        Status::Ok
+    }
+
+    // The port of a current input, None for other names.
+    fn current_input_mut(&mut self, name: &str) -> Option<&mut InputPort> {
+        match name {
+            "i_syn" | "i_syn1" | "i_syn[0]" => Some(&mut self.input_syn),
+            "i_ext" => Some(&mut self.input_ext),
+            "i_syn2" if self.half_center.is_some() => Some(&mut self.input_syn2),
+            _ => self.population.as_mut().and_then(|population| population.input_mut(name)),
+        }
+    }
+
+    // Records a config entry that was not applied; set_config_status then
+    // reports BadArgument and stats list it under config_errors.
+    fn reject_config(&mut self, key: &str, error: &str) {
+        log_warn!(instance = self.id, key, error, "config entry rejected");
+        self.config_errors.push((key.to_string(), error.to_string()));
+    }
+
+    fn set_input(&mut self, name: &str, value: f64) -> bool {
+        let value = self
+            .input_scaling
+            .iter()
+            .find(|(scaled, _, _)| scaled == name)
+            .map_or(value, |(_, gain, offset)| gain * value + offset);
+        match name {
+            "i_syn" | "i_syn1" | "i_syn[0]" => self.input_syn.set(value),
+            "i_ext" => self.input_ext.set(value),
+            "e" => self.set_param_value(TunedParam::E, value),
+            "z_clamp" if self.clamp_z => self.z_clamp = value,
+            "i_syn2" if self.half_center.is_some() => self.input_syn2.set(value),
+            "period_in" => self.period_in.set(value),
+            "v_live" => self.v_live.set(value),
+            "v_pre" if self.g_gap != 0.0 => self.v_pre.set(value),
+            "v_pre_syn" if self.graded.enabled() => self.v_pre_syn.set(value),
+            "spike_in" if self.spike_input.enabled() => self.spike_input.receive(value),
+            "v_m" if self.dynamic_clamp.is_some() => self.v_m.set(value),
+            "g_syn_exc" if self.conductance.enabled => self.conductance.exc.set(value),
+            "g_syn_inh" if self.conductance.enabled => self.conductance.inh.set(value),
+            "event_in" => self.event_in.set(value),
+            _ => return self.population.as_mut().is_some_and(|population| population.set_input(name, value)),
+        }
+        true
+    }
+
+    fn run_tick(&mut self, tick: u64, period_seconds: f64) {
+        // ALWAYS use the period_seconds from runtime, not from config
+        // This ensures the plugin respects workspace period settings
+        if (self.period_seconds - period_seconds).abs() > f64::EPSILON {
+            self.period_seconds = period_seconds;
+            self.update_burst_settings();
+        }
+
+        self.tick = tick;
+        self.process();
+    }
+
+    // Ok for outputs currently listed (plus the raw state variables),
+    // NotConfigured for documented outputs the config leaves disabled.
+    fn output_status(&self, name: &str) -> Status {
+        if ["x", "y", "z"].contains(&name) || self.output_names().contains(&name) {
+            Status::Ok
+        } else if ports::OUTPUT_INFO.iter().any(|info| info.name == name) {
+            Status::NotConfigured
+        } else {
+            Status::BadName
+        }
+    }
+
+    // Model time covered by one internal step at the current temperature.
+    fn model_dt(&self) -> f64 {
+        self.temperature.step(self.dt)
+    }
+
+    // Sign of model time per tick.
+    fn direction(&self) -> f64 {
+        if self.reverse_time {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    // Host time covered by the current process call.
+    fn tick_period(&self) -> f64 {
+        self.period_seconds * self.tick_span as f64
+    }
+
+    fn process(&mut self) {
+        // Every host tick is checked, including those batched below.
+        self.check_interlock();
+        // Barrier members publish every tick, so they never defer.
+        if self.barrier.is_none() && self.batch.defer() {
+            return;
+        }
+        self.tick_span = if self.barrier.is_none() { self.batch.take() } else { 1 };
+        let started = Instant::now();
+        self.apply_control_updates();
+        self.run_simulated_prep();
+        self.read_coupling();
+        if self.graded.enabled() {
+            self.graded.tick(self.v_pre_syn.value, self.tick_period());
+        }
+        if self.spike_input.enabled() {
+            self.spike_input.tick(self.tick_period());
+        }
+        if let Some(cosim) = &mut self.cosim {
+            cosim.receive();
+        }
+        self.track_period_in();
+        self.observe_event_in();
+        self.run_trials();
+        self.apply_z_clamp();
+        self.tick_start = self.t;
+        if let Some(half_center) = &mut self.half_center {
+            self.half_center_current = half_center.begin_tick(self.x);
+        }
+        if let Some(population) = &mut self.population {
+            self.population_current = population.begin_tick(self.x);
+        }
+        // Again with this tick's coupling and synaptic currents.
+        self.check_interlock();
+        self.integrate_tick();
+        self.advance_coupled_cells();
+        let period = self.tick_period();
+        if let Some(clamp) = &mut self.dynamic_clamp {
+            clamp.tick(self.x, self.v_m.value, period);
+        }
+        if self.delayed_feedback.enabled() {
+            self.delayed_feedback.record(self.x, self.t - self.tick_start);
+        }
+        self.trajectory_checksum = fold_checksum(self.trajectory_checksum, [self.x, self.y, self.z]);
+        self.dense.set_target(self.tick_start + self.direction() * self.exact_tick_span());
+        self.run_balance_monitor();
+        self.apply_scheduled();
+        // Batched ticks contribute a single sample to the per-tick buffers.
+        self.template.sample(self.x);
+        self.burst_bank.sample(self.x);
+        self.spectrogram.push(self.x);
+        self.run_tuner();
+        self.run_watchdog();
+        if self.x_ac.cutoff > 0.0 {
+            self.x_ac.apply(self.x, self.tick_period());
+        }
+        if !self.v_live.is_stale(&self.staleness) {
+            self.agc.update(self.v_live.value, self.x, self.tick_period());
+        }
+        self.input_syn.tick(self.tick_period(), &self.staleness);
+        self.input_ext.tick(self.tick_period(), &self.staleness);
+        self.input_syn2.tick(self.tick_period(), &self.staleness);
+        self.v_pre.tick(self.tick_period(), &self.staleness);
+        self.v_pre_syn.tick(self.tick_period(), &self.staleness);
+        self.conductance.tick(self.tick_period(), &self.staleness);
+        self.v_m.tick(self.tick_period(), &self.staleness);
+        let period = self.tick_period();
+        if let Some(population) = &mut self.population {
+            population.tick_inputs(period, &self.staleness);
+        }
+        self.period_in.tick(self.tick_period(), &self.staleness);
+        self.v_live.tick(self.tick_period(), &self.staleness);
+        self.event_in.tick(self.tick_period(), &self.staleness);
+        self.evaluate_custom_outputs();
+        self.drift.tick(self.tick_period());
+        self.param_drift.tick(self.tick_period());
+        self.soft_start.tick(self.tick_period());
+        self.elapsed_seconds += self.tick_period();
+        if let Some(barrier) = &self.barrier {
+            barrier.publish(self.tick, [self.x, self.y, self.z]);
+        }
+        if let Some(cosim) = &mut self.cosim {
+            cosim.send(self.tick, [self.t, self.x, self.y, self.z, self.input_syn.value]);
+        }
+        if let Some(mut control) = self.control.take() {
+            control.publish(self.elapsed_seconds, || self.control_summary());
+            self.control = Some(control);
+        }
+        self.cost.record(started);
+    }
+
+    // Latches the interlock on the total current entering dx/dt: i_syn after
+    // its filters plus i_ext and every coupling and synaptic current.
+    fn check_interlock(&mut self) {
+        let current = self.drive();
+        if self.interlock.check(current) {
+            log_warn!(instance = self.id, current, "input limit exceeded, fault latched");
+            self.events.emit(EventKind::InterlockFault, current);
+        }
+    }
+
+    // The coupled cells cover the same model time as the instance's own cell
+    // just did.
+    fn advance_coupled_cells(&mut self) {
+        let params = self.cell_params(0.0);
+        let span = self.t - self.tick_start;
+        let i_syn = self.input_syn2.value;
+        if let Some(half_center) = &mut self.half_center {
+            half_center.advance(&params, span, i_syn);
+        }
+        if let Some(population) = &mut self.population {
+            population.advance(&params, span);
+        }
+    }
+
+    // Stands in for the host: the preparation's recording arrives as v_live
+    // and as the i_syn a host-side synapse would compute from it.
+    fn run_simulated_prep(&mut self) {
+        let period = self.tick_period();
+        let x = self.x;
+        let Some(prep) = &mut self.prep else {
+            return;
+        };
+        prep.tick(period, x);
+        let (recorded, current) = (prep.recorded(), prep.synaptic_current(x));
+        self.v_live.set(recorded);
+        self.input_syn.set(current);
+    }
+
+    fn apply_control_updates(&mut self) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        for update in control.take_updates() {
+            self.set_config(&update);
+            self.record_applied_config(&update);
+            log_info!(instance = self.id, "config applied from control endpoint");
+        }
+    }
+
+    fn control_summary(&self) -> Value {
+        let outputs: serde_json::Map<String, Value> = self
+            .output_names()
+            .into_iter()
+            .map(|name| (name.to_string(), serde_json::json!(self.output(name))))
+            .collect();
+        serde_json::json!({
+            "t": self.t,
+            "elapsed_seconds": self.elapsed_seconds,
+            "params": {
+                "e": self.e,
+                "mu": self.mu,
+                "s": self.s,
+                "vh": self.vh,
+                "dt": self.dt,
+                "burst_duration": self.burst_duration,
+                "integrator": self.integrator.name()
+            },
+            "outputs": outputs
+        })
+    }
+
+    // Static per-tick work estimate; the approximant replaces integration
+    // with a single spline lookup.
+    fn flops_per_step(&self) -> u64 {
+        let stages = self.integrator.stages() * FLOPS_PER_STAGE;
+        // Step doubling takes three steps per attempt.
+        if self.error_controlled() && self.integrator != Integrator::Rk45 {
+            3 * stages
+        } else {
+            stages
+        }
+    }
+
+    fn estimated_flops_per_tick(&self) -> u64 {
+        if self.approx.is_some() {
+            return 50;
+        }
+        let substeps = self.s_points.clamp(1, self.max_steps) as u64;
+        let shadow = if self.shadow.is_some() {
+            self.shadow_refinement as u64
+        } else {
+            0
+        };
+        substeps * (1 + shadow) * self.flops_per_step()
+    }
+
+    // A live burst period measured on the biological cell replaces the
+    // configured burst_duration, so the time scaling follows the preparation.
+    // Rising edges of event_in through 0.5 are binned by the model phase at
+    // which they arrive; events before the first full cycle are ignored.
+    fn observe_event_in(&mut self) {
+        let high = self.event_in.value > 0.5;
+        if high && !self.event_in_high {
+            if let Some(phase) = self.bursts.phase(self.t) {
+                self.event_phases.add(phase);
+            }
+        }
+        self.event_in_high = high;
+    }
+
+    fn track_period_in(&mut self) {
+        let period = self.period_in.value;
+        if period <= 0.0 || self.period_in.is_stale(&self.staleness) {
+            return;
+        }
+        if (period - self.burst_duration).abs() > f64::EPSILON {
+            // With calibration_max_rate set, the calibrated burst duration
+            // slews toward the measured period by at most that fraction per
+            // second, so dt/s_points cannot chase the experimental loop.
+            let mut target = period;
+            if self.calibration_max_rate > 0.0 && self.burst_duration > 0.0 {
+                let max_step = self.burst_duration * self.calibration_max_rate * self.tick_period();
+                target = self.burst_duration + (period - self.burst_duration).clamp(-max_step, max_step);
+            }
+            self.burst_duration = target;
+            self.update_burst_settings();
+        }
+    }
+
+    fn integrate_tick(&mut self) {
+        self.event_latch.begin_tick();
+        self.divergence.begin_tick();
+        self.richardson.begin_tick();
+        self.steps_clamped = 0;
+        let dt = self.direction() * self.model_dt();
+        let substeps = self.tick_steps();
+        self.poll_approximant();
+        if let Some(cycle) = &self.approx {
+            let elapsed = dt * substeps as f64;
+            self.approx_phase = (self.approx_phase + elapsed / cycle.period()).rem_euclid(1.0);
+            let vars = cycle.eval(self.approx_phase, self.drive());
+            self.x = vars[0];
+            self.y = vars[1];
+            self.z = vars[2];
+            self.t += elapsed;
+            self.steps += substeps as u64;
+            self.observe_events(true, false);
+            return;
+        }
+        if self.idle_tick(dt, substeps) {
+            return;
+        }
+        if self.error_controlled() {
+            self.integrate_adaptive(self.model_dt() * substeps as f64);
+            self.detect_idle();
+            return;
+        }
+        // Steps beyond the per-tick budget are either dropped or, when
+        // carry_step_deficit is set, caught up on later ticks.
+        let required = substeps + self.step_deficit;
+        let steps = required.min(self.max_steps);
+        let overloaded = required > self.max_steps;
+        self.steps_clamped = required - steps;
+        if overloaded && !self.overloaded {
+            log_warn!(instance = self.id, required, budget = self.max_steps, "step budget exceeded");
+        }
+        self.overloaded = overloaded;
+        self.step_deficit = if self.carry_step_deficit {
+            required - steps
+        } else {
+            0
+        };
+
+        // The drive cannot change within a tick, so the field parameters are
+        // built once rather than per step.
+        let whole_model = self.whole_model();
+        let params = if whole_model { self.core_params(self.drive()) } else { self.params(self.drive()) };
+        for _ in 0..steps {
+            self.check_stiffness(self.model_dt(), self.integrator.stability_limit());
+            let stepper = self.stepper();
+            let multistep = if self.fixed_point { None } else { stepper.multistep_order() };
+            let vars = if whole_model {
+                let model = &self.model;
+                let state = model.full_state([self.x, self.y, self.z]);
+                let state = stepper.step(state, dt, |v| model.field(*v, &params), |v| model.jacobian(*v, &params));
+                self.store_model_state(state)
+            } else if self.multirate.enabled() && !self.fixed_point {
+                self.multirate.step(stepper, [self.x, self.y, self.z], dt, &params)
+            } else if self.compensated && !self.fixed_point {
+                let vars = [self.x, self.y, self.z];
+                let increment =
+                    stepper.increment(vars, dt, |v| derivatives(*v, &params), |v| jacobian(*v, &params));
+                self.state_sum.add(vars, increment)
+            } else if self.richardson.enabled && !self.fixed_point {
+                let step = |vars, h| step_field(stepper, false, vars, h, &params);
+                let vars = [self.x, self.y, self.z];
+                let full = step(vars, dt);
+                let half = step(step(vars, 0.5 * dt), 0.5 * dt);
+                self.richardson.extrapolate(full, half, stepper.order())
+            } else if let Some(order) = multistep {
+                self.multistep.step(order, [self.x, self.y, self.z], dt, &params)
+            } else {
+                self.step_state(stepper, dt, &params)
+            };
+            self.accept_step(vars, dt);
+        }
+        self.detect_idle();
+    }
+
+    // The plain fixed step. With the double_double feature it runs in
+    // double-double arithmetic and the state keeps its low words between
+    // steps; fixed-point runs are unaffected.
+    #[cfg(feature = "double_double")]
+    fn step_state(&mut self, stepper: Integrator, dt: f64, params: &Params<f64>) -> [f64; 3] {
+        if self.fixed_point {
+            return step_field(stepper, true, [self.x, self.y, self.z], dt, params);
+        }
+        let params = dd::lift_params(params);
+        let next = stepper.step(
+            self.state_tail.lift([self.x, self.y, self.z]),
+            dd::DoubleDouble::lit(dt),
+            |v| derivatives(*v, &params),
+            |v| jacobian(*v, &params),
+        );
+        self.state_tail.store(next)
+    }
+
+    // Null unless built with double_double.
+    #[cfg(feature = "double_double")]
+    fn state_low_words(&self) -> Option<[f64; 3]> {
+        Some(self.state_tail.lo())
+    }
+
+    #[cfg(not(feature = "double_double"))]
+    fn state_low_words(&self) -> Option<[f64; 3]> {
+        None
+    }
+
+    #[cfg(not(feature = "double_double"))]
+    fn step_state(&mut self, stepper: Integrator, dt: f64, params: &Params<f64>) -> [f64; 3] {
+        step_field(stepper, self.fixed_point, [self.x, self.y, self.z], dt, params)
+    }
+
+    // Models with state beyond x, y, z step through their own field, see
+    // model.rs.
+    fn whole_model(&self) -> bool {
+        !self.model.state().is_empty()
+    }
+
+    // Keeps the model's part of a full state and returns the core's.
+    fn store_model_state(&mut self, state: [f64; MAX_STATE]) -> [f64; 3] {
+        let extra = self.model.state_mut();
+        let len = extra.len();
+        extra.copy_from_slice(&state[3..3 + len]);
+        [state[0], state[1], state[2]]
+    }
+
+    // Calibrated runs under "step_control": "pi", the default, unless
+    // fixed-point arithmetic is on; fixed-point runs keep the legacy table.
+    fn pi_controlled(&self) -> bool {
+        self.burst_duration > 0.0 && self.step_control == StepControl::Pi && !self.fixed_point
+    }
+
+    // Rk45 always steps under error control, other integrators only when PI
+    // control covers the tick.
+    fn error_controlled(&self) -> bool {
+        self.integrator == Integrator::Rk45 || self.pi_controlled()
+    }
+
+    // Covers the tick's calibrated span with error-controlled steps. Once
+    // max_steps_per_tick attempts are spent the rest of the span is taken in
+    // a single forced step so the tick still ends on time. In reverse time
+    // the controller works forward on the negated field and each accepted
+    // step of size h moves t back by h.
+    fn integrate_adaptive(&mut self, span: f64) {
+        let whole_model = self.whole_model();
+        let params = if whole_model { self.core_params(self.drive()) } else { self.params(self.drive()) };
+        let sign = self.direction();
+        self.adaptive.begin_tick(self.model_dt());
+        let mut remaining = span;
+        let mut attempts = 0;
+        while remaining > 0.0 {
+            let force = attempts >= self.max_steps;
+            attempts += 1;
+            let state = [self.x, self.y, self.z];
+            // While stiff, implicit steps of the controller's current size
+            // replace the embedded pair. Stiffness is judged on the untruncated
+            // size so the short step closing a tick cannot flip the solver.
+            self.check_stiffness(self.adaptive.step_size(), Integrator::Rk45.stability_limit());
+            if self.stiffness.stiff() && !force {
+                let h = self.adaptive.step_size().min(remaining);
+                let state = if whole_model {
+                    let model = &self.model;
+                    let state = Integrator::BackwardEuler.step(
+                        model.full_state(state),
+                        sign * h,
+                        |v| model.field(*v, &params),
+                        |v| model.jacobian(*v, &params),
+                    );
+                    self.store_model_state(state)
+                } else {
+                    Integrator::BackwardEuler.step(
+                        state,
+                        sign * h,
+                        |v| derivatives(*v, &params),
+                        |v| jacobian(*v, &params),
+                    )
+                };
+                remaining -= h;
+                self.accept_step(state, sign * h);
+                continue;
+            }
+            let integrator = self.integrator;
+            let order = integrator.order();
+            let attempt = if whole_model {
+                let model = &self.model;
+                let state = model.full_state(state);
+                let attempt = if integrator == Integrator::Rk45 {
+                    let field = |v: &[f64; MAX_STATE]| model.field(*v, &params).map(|rate| sign * rate);
+                    self.adaptive.attempt(state, remaining, force, 4, |h| adaptive::embedded(state, h, field))
+                } else {
+                    let step = |v, h| {
+                        integrator.step(v, sign * h, |v| model.field(*v, &params), |v| model.jacobian(*v, &params))
+                    };
+                    self.adaptive.attempt(state, remaining, force, order, |h| adaptive::doubled(state, h, order, step))
+                };
+                match attempt {
+                    Attempt::Accepted { state, h } => Attempt::Accepted { state: self.store_model_state(state), h },
+                    Attempt::Rejected => Attempt::Rejected,
+                }
+            } else if integrator == Integrator::Rk45 {
+                let field = |v: &[f64; 3]| derivatives(*v, &params).map(|rate| sign * rate);
+                self.adaptive.attempt(state, remaining, force, 4, |h| adaptive::embedded(state, h, field))
+            } else {
+                // Explicit steps of -h on the field equal steps of h on its
+                // negation, so reverse runs just flip the step.
+                let step = |v, h| step_field(integrator, false, v, sign * h, &params);
+                self.adaptive.attempt(state, remaining, force, order, |h| adaptive::doubled(state, h, order, step))
+            };
+            match attempt {
+                Attempt::Accepted { state, h } => {
+                    remaining -= h;
+                    self.accept_step(state, sign * h);
+                }
+                Attempt::Rejected => {}
+            }
+        }
+        let overloaded = attempts > self.max_steps;
+        // The forced step covers the rest of the span, so nothing is
+        // deferred; it is reported as one clamped step.
+        self.steps_clamped = usize::from(overloaded);
+        if overloaded && !self.overloaded {
+            log_warn!(instance = self.id, budget = self.max_steps, "adaptive step budget exceeded");
+        }
+        self.overloaded = overloaded;
+    }
+
+    fn accept_step(&mut self, vars: [f64; 3], dt: f64) {
+        let vars = if self.noise.enabled() {
+            self.noise.apply(vars, dt, self.rng.stream(Stream::Noise))
+        } else {
+            vars
+        };
+        if self.divergence.check(&vars, self.t) {
+            self.recover_divergence(dt);
+            return;
+        }
+        if self.dense.enabled || self.precise_spikes {
+            let params = self.params(self.drive());
+            let start = [self.x, self.y, self.z];
+            self.dense.record(
+                (start, derivatives(start, &params)),
+                (vars, derivatives(vars, &params)),
+                self.t,
+                dt,
+            );
+        }
+        self.x = vars[0];
+        self.y = vars[1];
+        self.z = vars[2];
+        self.advance_time(dt);
+        self.observe_events(vars.iter().all(|v| v.is_finite()), true);
+        self.advance_shadow(vars, dt);
+        if self.balance.enabled {
+            let rate = derivatives(vars, &self.params(self.drive()))[2];
+            self.balance.step(vars[2], rate, dt);
+        }
+        if self.energy.enabled {
+            let params = self.params(self.drive());
+            self.energy.step(energy::energy(vars, &params), energy::energy_rate(vars, &params), dt);
+        }
+    }
+
+    // A discarded non-finite step still advances time, so the tick keeps its
+    // length whichever policy applies.
+    fn recover_divergence(&mut self, dt: f64) {
+        log_warn!(instance = self.id, t = self.t, policy = self.divergence.policy.name(), "non-finite state discarded");
+        self.events.observe(f64::NAN, false, self.t);
+        self.multistep.restart();
+        if self.divergence.policy == Recovery::Reset {
+            self.reset_state();
+        }
+        self.advance_time(dt);
+    }
+
+    fn advance_time(&mut self, dt: f64) {
+        if self.compensated {
+            [self.t] = self.time_sum.add([self.t], [dt]);
+        } else {
+            self.t += dt;
+        }
+        self.steps += 1;
+    }
+
+    // Silent cells parked at a stable fixed point (every |derivative| below
+    // idle_tolerance) are held there without integrating until the drive
+    // changes or a config update arrives.
+    fn detect_idle(&mut self) {
+        if self.idle_tolerance <= 0.0 {
+            return;
+        }
+        let drive = self.drive();
+        let rates = self.model.field(self.model.full_state([self.x, self.y, self.z]), &self.core_params(drive));
+        if rates.iter().all(|rate| rate.abs() < self.idle_tolerance) {
+            log_info!(instance = self.id, t = self.t, "fixed point reached, integration idle");
+            self.idle = Some(drive);
+        }
+    }
+
+    fn idle_tick(&mut self, dt: f64, steps: usize) -> bool {
+        let Some(drive) = self.idle else {
+            return false;
+        };
+        if self.drive() != drive {
+            log_info!(instance = self.id, t = self.t, "input changed, integration resumed");
+            self.idle = None;
+            return false;
+        }
+        self.t += dt * steps as f64;
+        self.steps += steps as u64;
+        self.idle_ticks += 1;
+        true
+    }
+
+    fn run_balance_monitor(&mut self) {
+        if self.energy.enabled && self.approx.is_some() {
+            self.rebase_energy();
+        }
+        if !self.balance.enabled {
+            return;
+        }
+        // The approximant has no substeps to integrate along.
+        if self.approx.is_some() {
+            self.rebase_balance();
+            return;
+        }
+        if self.balance.end_tick() {
+            log_warn!(instance = self.id, t = self.t, residual = self.balance.residual(), "z balance residual jumped");
+        }
+    }
+}
+
+// One fixed step of the vector field in the configured arithmetic. Kept
+// free of the instance so the per-step loop only passes what changes.
+fn step_field(stepper: Integrator, fixed_point: bool, vars: [f64; 3], dt: f64, params: &Params<f64>) -> [f64; 3] {
+    if fixed_point {
+        let params = fixed::narrow_params(params);
+        return fixed::widen(stepper.step(
+            fixed::narrow(vars),
+            Fixed::lit(dt),
+            |v| derivatives(*v, &params),
+            |v| jacobian(*v, &params),
+        ));
+    }
+    stepper.step(vars, dt, |v| derivatives(*v, params), |v| jacobian(*v, params))
+}
+
+// FNV-1a over the bit patterns of the state after every tick since the last
+// reset. Equal checksums across machines mean bit-identical trajectories,
+// which the `deterministic` feature is meant to guarantee.
+fn fold_checksum(hash: u64, values: [f64; 3]) -> u64 {
+    values
+        .iter()
+        .flat_map(|value| value.to_bits().to_le_bytes())
+        .fold(hash, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+// Seed of the parameter wander for an instance seeded with `seed`.
+fn param_drift_seed(seed: u64) -> u64 {
+    rng::derive(seed, rng::PARAM_DRIFT_STREAM)
+}
+
+extern "C" fn create(id: u64) -> *mut c_void {
+    telemetry::init_from_env();
+    lifecycle::into_handle(HindmarshRosev2Rust::new(id))
+}
+
+// Safe against calls still running on other threads: blocks until they
+// return, see lifecycle.rs.
+extern "C" fn destroy(handle: *mut c_void) {
+    lifecycle::destroy(handle)
+}
+
+// Every entry point reaches the instance through this guard, which gives
+// one call at a time exclusive access. None for a null, destroyed or
+// re-entered handle.
+fn enter<'a>(handle: *mut c_void) -> Option<Entered<'a, HindmarshRosev2Rust>> {
+    lifecycle::enter(handle)
+}
+
+extern "C" fn meta_json(handle: *mut c_void) -> PluginString {
+    let entered = enter(handle);
+    let instance = entered.as_deref();
+    let flops_per_step = instance.map_or(Integrator::Rk6.stages() * FLOPS_PER_STAGE, |instance| instance.flops_per_step());
+    let flops_per_tick = instance.map_or(flops_per_step, |instance| instance.estimated_flops_per_tick());
+    let value = serde_json::json!({
+        "name": "Hindmarsh Rose v2 Rust",
+        "default_vars": [
+            ["x", -0.9013],
+            ["y", -3.1594],
+            ["z", 3.24782],
+            ["e", 3.0],
+            ["mu", 0.006],
+            ["s", 4.0],
+            ["vh", 1.0],
+            ["i_ext", 0.0],
+            ["dt", 0.15],
+            ["burst_duration", 1.0],
+            ["model_burst_span", MODEL_BURST_SPAN],
+            ["calibration_max_rate", 0.0],
+            ["max_steps_per_tick", MAX_STEPS_PER_TICK as f64],
+            ["idle_tolerance", 0.0],
+            ["multirate_ratio", 1],
+            ["abs_tol", 1e-6],
+            ["rel_tol", 1e-6],
+            ["phase_bins", 20],
+            ["spike_threshold", 0.5],
+            ["burst_gap", 50.0],
+            ["shadow_refinement", 0],
+            ["shadow_resync", 100.0],
+            ["approx_points", 1024],
+            ["input_stale_after", 0.1],
+            ["input_stale_decay", 0.0],
+            ["target_freq", 0.0],
+            ["target_duty", 0.0],
+            ["x_ac_cutoff", 0.0],
+            ["agc_tau", 0.0],
+            ["test_offset", 0.0],
+            ["test_drift_rate", 0.0],
+            ["test_drift_amplitude", 0.0],
+            ["test_drift_period", 0.0],
+            ["quiescence_timeout", 0.0],
+            ["kick_current", 1.0],
+            ["kick_duration", 0.05],
+            ["ic_jitter", 0.0],
+            ["g_gap", 0.0],
+            ["syn_g_max", 0.0],
+            ["syn_e_rev", -2.0],
+            ["syn_v_half", -0.25],
+            ["syn_slope", 0.1],
+            ["syn_tau_fast", 0.0],
+            ["syn_tau_slow", 0.0],
+            ["syn_slow_fraction", 0.0],
+            ["spike_weight", 0.0],
+            ["spike_tau", 0.005],
+            ["spike_tau_rise", 0.001],
+            ["spike_tau_decay", 0.01],
+            ["e_rev_exc", 2.0],
+            ["e_rev_inh", -2.0],
+            ["delay_gain", 0.0],
+            ["delay_tau", 0.0],
+            ["noise_x", 0.0],
+            ["noise_y", 0.0],
+            ["noise_z", 0.0],
+            ["e_drift_sigma", 0.0],
+            ["e_drift_tau", 0.0],
+            ["e_drift_range", 0.0],
+            ["mu_drift_sigma", 0.0],
+            ["mu_drift_tau", 0.0],
+            ["mu_drift_range", 0.0],
+            ["temperature_factor_fast", 1.0],
+            ["temperature_factor_slow", 1.0],
+            ["u", 0.0],
+            ["u_g", SecondSlow::PINTO.g],
+            ["u_nu", SecondSlow::PINTO.nu],
+            ["u_k", SecondSlow::PINTO.k],
+            ["u_r", SecondSlow::PINTO.r],
+            ["u_y_r", SecondSlow::PINTO.y_r],
+            ["input_limit", 0.0],
+            ["ramp_duration", 0.0]
+        ],
+        "cost_hint": {
+            "flops_per_step": flops_per_step,
+            "flops_per_tick": flops_per_tick,
+            "mean_tick_ns": instance.map(|instance| instance.cost.mean_ns())
+        }
+    });
+    PluginString::from_string(value.to_string())
+}
+
+extern "C" fn inputs_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string(serde_json::to_string(INPUTS).unwrap_or_default());
+    };
+    let names = instance.input_names();
+    if instance.port_metadata {
+        return PluginString::from_string(ports::describe_inputs(&names).to_string());
+    }
+    PluginString::from_string(serde_json::to_string(&names).unwrap_or_default())
+}
+
+extern "C" fn outputs_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string(serde_json::to_string(OUTPUTS).unwrap_or_default());
+    };
+    let names = instance.output_names();
+    if instance.port_metadata {
+        return PluginString::from_string(ports::describe_outputs(&names).to_string());
+    }
+    PluginString::from_string(serde_json::to_string(&names).unwrap_or_default())
+}
+
+extern "C" fn behavior_json(_handle: *mut c_void) -> PluginString {
+    let behavior = serde_json::json!({
+        "supports_start_stop": true,
+        "supports_restart": true,
+        "extendable_inputs": {"type": "none"},
+        "loads_started": true
+    });
+    PluginString::from_string(behavior.to_string())
+}
+
+extern "C" fn ui_schema_json(handle: *mut c_void) -> PluginString {
+    let instance = enter(handle);
+    let outputs = match &instance {
+        Some(instance) => instance.output_names(),
+        None => OUTPUTS.to_vec(),
+    };
+    let schema = serde_json::json!({
+        "outputs": outputs,
+        "inputs": INPUTS,
+        "variables": ["x", "y", "z"]
+    });
+    PluginString::from_string(schema.to_string())
+}
+
+extern "C" fn set_config_json(handle: *mut c_void, data: *const u8, len: usize) {
+    if handle.is_null() || data.is_null() || len == 0 {
+        return;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(data, len) };
+    if let (Ok(json), Some(mut instance)) = (serde_json::from_slice::<Value>(slice), enter(handle)) {
+        instance.set_config(&json);
+        instance.record_applied_config(&json);
+        log_info!(instance = instance.id, "config applied");
+    }
+}
+
+extern "C" fn set_input(handle: *mut c_void, name: *const u8, len: usize, value: f64) {
+    if handle.is_null() || name.is_null() || len == 0 {
+        return;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(name, len) };
+    if let (Ok(name), Some(mut instance)) = (std::str::from_utf8(slice), enter(handle)) {
+        instance.set_input(name, value);
+    }
+}
+
+extern "C" fn process(handle: *mut c_void, tick: u64, period_seconds: f64) {
+    let Some(mut instance) = enter(handle) else {
+        return;
+    };
+    instance.run_tick(tick, period_seconds);
+}
+
+extern "C" fn get_output(handle: *mut c_void, name: *const u8, len: usize) -> f64 {
+    if handle.is_null() || name.is_null() || len == 0 {
+        return 0.0;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(name, len) };
+    if let (Ok(name), Some(instance)) = (std::str::from_utf8(slice), enter(handle)) {
+        return instance.output(name);
+    }
+    0.0
+}
+
+// Optional extension: status-code variants of the PluginApi calls. They
+// behave like their counterparts but return a status::Status code (0 on
+// success, negative on failure) and catch panics as Status::Internal.
+fn decode_name<'a>(name: *const u8, len: usize) -> Result<&'a str, Status> {
+    if name.is_null() || len == 0 {
+        return Err(Status::BadName);
+    }
+    let slice = unsafe { std::slice::from_raw_parts(name, len) };
+    std::str::from_utf8(slice).map_err(|_| Status::BadName)
+}
+
+#[no_mangle]
+extern "C" fn rtsyn_plugin_get_output_status(handle: *mut c_void, name: *const u8, len: usize, out: *mut f64) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    if out.is_null() {
+        return Status::BadArgument as i32;
+    }
+    status::guard(|| {
+        let name = match decode_name(name, len) {
+            Ok(name) => name,
+            Err(status) => return status,
+        };
+        let Some(instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        let resolved = instance
+            .output_aliases
+            .iter()
+            .find(|(alias, _)| alias == name)
+            .map_or(name, |(_, target)| target.as_str());
+        let status = instance.output_status(resolved);
+        if status == Status::Ok {
+            unsafe { *out = instance.output(name) };
+        }
+        status
+    })
+}
+
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_input_status(handle: *mut c_void, name: *const u8, len: usize, value: f64) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    status::guard(|| {
+        let name = match decode_name(name, len) {
+            Ok(name) => name,
+            Err(status) => return status,
+        };
+        let Some(mut instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        if instance.set_input(name, value) {
+            Status::Ok
+        } else {
+            Status::BadName
+        }
+    })
+}
+
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_config_status(handle: *mut c_void, data: *const u8, len: usize) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    if data.is_null() || len == 0 {
+        return Status::BadArgument as i32;
+    }
+    status::guard(|| {
+        let slice = unsafe { std::slice::from_raw_parts(data, len) };
+        let Ok(json) = serde_json::from_slice::<Value>(slice) else {
+            return Status::BadArgument;
+        };
+        let Some(mut instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        instance.set_config(&json);
+        instance.record_applied_config(&json);
+        if instance.config_errors.is_empty() {
+            Status::Ok
+        } else {
+            Status::BadArgument
+        }
+    })
+}
+
+#[no_mangle]
+extern "C" fn rtsyn_plugin_process_status(handle: *mut c_void, tick: u64, period_seconds: f64) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    status::guard(|| {
+        let Some(mut instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        instance.run_tick(tick, period_seconds);
+        Status::Ok
+    })
+}
+
+// Optional extension: the host registers a callback invoked from the process
+// thread on spikes, burst onsets, non-finite state and dt recalibration.
+// Passing a null callback unregisters it.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_event_callback(
+    handle: *mut c_void,
+    callback: Option<EventCallback>,
+    user_data: *mut c_void,
+) {
+    let Some(mut instance) = enter(handle) else {
+        return;
+    };
+    instance.events.set_callback(callback, user_data);
+}
+
+// Optional extension: writes a binary checkpoint into `buf` and returns the
+// number of bytes it needs. Nothing is written when `cap` is too small, so
+// hosts can query the size with a null buffer first.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_get_state_blob(handle: *mut c_void, buf: *mut u8, cap: usize) -> usize {
+    let Some(instance) = enter(handle) else {
+        return 0;
+    };
+    let snapshot = instance.snapshot();
+    if buf.is_null() || cap < snapshot.size() {
+        return snapshot.size();
+    }
+    let out = unsafe { std::slice::from_raw_parts_mut(buf, cap) };
+    snapshot.write(out)
+}
+
+// Optional extension: copies the rolling spectrogram of x into `buf` as
+// row-major f64 power values, oldest frame first, and returns the number of
+// values it needs. Shape and frequency resolution are reported under
+// "spectrogram" in stats_json. As with get_state_blob, nothing is written
+// when `cap` is too small.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_read_spectrogram(handle: *mut c_void, buf: *mut f64, cap: usize) -> usize {
+    let Some(instance) = enter(handle) else {
+        return 0;
+    };
+    let needed = instance.spectrogram.frames() * instance.spectrogram.bins();
+    if buf.is_null() || cap < needed {
+        return needed;
+    }
+    let out = unsafe { std::slice::from_raw_parts_mut(buf, cap) };
+    instance.spectrogram.copy_to(out)
+}
+
+// Optional extension: reads several outputs at once as (time, value) pairs.
+// `names` is a JSON array of output names; `buf` receives 2 * len(names)
+// f64 values, each pair holding the seconds of host time simulated since
+// creation (the sum of process periods) and the current output value.
+// Returns the number of values needed; nothing is written when `cap` is too
+// small, or 0 for a malformed name list.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_read_outputs_timestamped(
+    handle: *mut c_void,
+    names: *const u8,
+    len: usize,
+    buf: *mut f64,
+    cap: usize,
+) -> usize {
+    if handle.is_null() || names.is_null() || len == 0 {
+        return 0;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(names, len) };
+    let Ok(Value::Array(names)) = serde_json::from_slice::<Value>(slice) else {
+        return 0;
+    };
+    let needed = 2 * names.len();
+    if buf.is_null() || cap < needed {
+        return needed;
+    }
+    let Some(instance) = enter(handle) else {
+        return 0;
+    };
+    let out = unsafe { std::slice::from_raw_parts_mut(buf, cap) };
+    for (pair, name) in out.chunks_exact_mut(2).zip(&names) {
+        pair[0] = instance.elapsed_seconds;
+        pair[1] = name.as_str().map_or(0.0, |name| instance.output(name));
+    }
+    needed
+}
+
+// Optional extension: writes recently detected bursts (see burst_bank_size)
+// to a template file. `request` is a JSON object:
+//   {"path": "...", "count": 5, "length": 200, "average": true}
+// count defaults to every stored burst and length to their mean length in
+// ticks. An averaged export is a flat JSON array that burst_template_file
+// loads directly. Returns a status::Status code; NotConfigured when no
+// burst has been stored yet.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_export_bursts(handle: *mut c_void, request: *const u8, len: usize) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    if request.is_null() || len == 0 {
+        return Status::BadArgument as i32;
+    }
+    status::guard(|| {
+        let slice = unsafe { std::slice::from_raw_parts(request, len) };
+        let Ok(request) = serde_json::from_slice::<Value>(slice) else {
+            return Status::BadArgument;
+        };
+        let Some(path) = request.get("path").and_then(|v| v.as_str()) else {
+            return Status::BadArgument;
+        };
+        let Some(instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        let count = request
+            .get("count")
+            .and_then(|v| v.as_u64())
+            .map_or(instance.burst_bank.len(), |count| count as usize);
+        let length = request.get("length").and_then(|v| v.as_u64()).map(|length| length as usize);
+        let average = request.get("average").and_then(|v| v.as_bool()).unwrap_or(false);
+        let waveforms = instance.burst_bank.extract(count, length, average);
+        if waveforms.is_empty() {
+            return Status::NotConfigured;
+        }
+        match std::fs::write(path, template::template_json(&waveforms).to_string()) {
+            Ok(()) => Status::Ok,
+            Err(_) => Status::Io,
+        }
+    })
+}
+
+// Optional extension: tells the plugin its outputs will next be read
+// `ticks` ticks from now (counting the coming one), so the ticks before
+// that can be batched into the consuming one. Ignored for barrier members.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_tick_hint(handle: *mut c_void, ticks: u64) {
+    let Some(mut instance) = enter(handle) else {
+        return;
+    };
+    instance.batch.hint(ticks as usize);
+}
+
+// Optional extension: restores a checkpoint produced by get_state_blob.
+// Returns 0 on success and -1 for a null handle, a malformed blob or one
+// whose half-center and population cells do not match the configuration.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_state_blob(handle: *mut c_void, data: *const u8, len: usize) -> i32 {
+    if handle.is_null() || data.is_null() || len == 0 {
+        return -1;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(data, len) };
+    let (Some(snapshot), Some(mut instance)) = (Snapshot::read(slice), enter(handle)) else {
+        return -1;
+    };
+    if instance.restore(&snapshot) {
+        0
+    } else {
+        -1
+    }
+}
+
+// Optional extension: runtime counters (model time, substeps, step deficit).
+#[no_mangle]
+extern "C" fn rtsyn_plugin_stats_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string("{}".to_string());
+    };
+    PluginString::from_string(instance.stats().to_string())
+}
+
+// Optional extension: exact d(xdot, ydot, zdot)/d(e, mu, s, vh) at the
+// current state as a 3x4 row-major array. Returns null when the plugin was
+// built without the `autodiff` feature.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_field_gradients_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string("null".to_string());
+    };
+    PluginString::from_string(instance.field_gradients().to_string())
+}
+
+// Optional extension: Jacobian of the vector field at the current state with
+// its eigenvalues, trace, determinant and a "stable" flag, for GUIs showing
+// local stability live.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_jacobian_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string("null".to_string());
+    };
+    PluginString::from_string(instance.stability().to_string())
+}
+
+// Optional extension: reads and writes single internal synapse weights,
+// weights[post][pre] of a population or the half-center's g (neurons 0 and
+// 1), so a host or optimizer can adapt connectivity without resending the
+// config. Returns a status::Status code: NotConfigured without internal
+// synapses, BadArgument for a diagonal or out-of-range pair, a null `out`
+// or a non-finite weight. The half-center clamps a negative g to 0.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_get_weight(handle: *mut c_void, post: usize, pre: usize, out: *mut f64) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    if out.is_null() {
+        return Status::BadArgument as i32;
+    }
+    status::guard(|| {
+        let Some(instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        match instance.weight(post, pre) {
+            Ok(weight) => {
+                unsafe { *out = weight };
+                Status::Ok
+            }
+            Err(status) => status,
+        }
+    })
+}
+
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_weight(handle: *mut c_void, post: usize, pre: usize, weight: f64) -> i32 {
+    if handle.is_null() {
+        return Status::NullHandle as i32;
+    }
+    status::guard(|| {
+        let Some(mut instance) = enter(handle) else {
+            return Status::Closing;
+        };
+        instance.set_weight(post, pre, weight)
+    })
+}
+
+// Optional extension: session-wide seed from which every instance created
+// afterwards derives its random stream (unless it sets its own `seed`).
+#[no_mangle]
+extern "C" fn rtsyn_plugin_set_global_seed(seed: u64) {
+    rng::set_global_seed(seed);
+}
+
+// Optional extension: installs a JSON `tracing` subscriber writing to the
+// given path ("stderr" for standard error). Returns 0 on success, -1 if the
+// build lacks the `tracing` feature, the file cannot be opened or a
+// subscriber is already installed.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_install_json_logging(path: *const u8, len: usize) -> i32 {
+    if path.is_null() || len == 0 {
+        return -1;
+    }
+    let slice = unsafe { std::slice::from_raw_parts(path, len) };
+    match std::str::from_utf8(slice) {
+        Ok(path) if telemetry::install_json_subscriber(path) => 0,
+        _ => -1,
+    }
+}
+
+// Optional extension: clears a latched input interlock fault. Equivalent to
+// sending {"clear_fault": true} through the config.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_clear_fault(handle: *mut c_void) {
+    let Some(mut instance) = enter(handle) else {
+        return;
+    };
+    if instance.interlock.fault() {
+        instance.interlock.clear();
+        instance.soft_start.restart();
+    }
+    log_info!(instance = instance.id, "interlock fault cleared");
+}
+
+// Instruction set population cells are stepped with, picked at runtime;
+// null without the simd feature.
+#[cfg(feature = "simd")]
+fn simd_kernel() -> Value {
+    Value::from(simd::Kernel::detect().name())
+}
+
+#[cfg(not(feature = "simd"))]
+fn simd_kernel() -> Value {
+    Value::Null
+}
+
+fn capabilities() -> Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "entry_points": [
+            "rtsyn_plugin_api",
+            "rtsyn_plugin_api_v2",
+            "rtsyn_plugin_capabilities_json",
+            "rtsyn_plugin_set_event_callback",
+            "rtsyn_plugin_get_state_blob",
+            "rtsyn_plugin_set_state_blob",
+            "rtsyn_plugin_stats_json",
+            "rtsyn_plugin_field_gradients_json",
+            "rtsyn_plugin_jacobian_json",
+            "rtsyn_plugin_set_global_seed",
+            "rtsyn_plugin_install_json_logging",
+            "rtsyn_plugin_clear_fault",
+            "rtsyn_plugin_read_spectrogram",
+            "rtsyn_plugin_read_outputs_timestamped",
+            "rtsyn_plugin_manifest_json",
+            "rtsyn_plugin_set_tick_hint",
+            "rtsyn_plugin_get_output_status",
+            "rtsyn_plugin_set_input_status",
+            "rtsyn_plugin_set_config_status",
+            "rtsyn_plugin_process_status",
+            "rtsyn_plugin_export_bursts",
+            "rtsyn_plugin_get_weight",
+            "rtsyn_plugin_set_weight"
+        ],
+        "features": {
+            "event_callbacks": true,
+            "approximant": true,
+            "extremum_seeking": true,
+            "burst_locked_config": true,
+            "epochs": true,
+            "burst_template": true,
+            "burst_export": true,
+            "spectrogram": true,
+            "custom_outputs": true,
+            "current_outputs": true,
+            "balance_monitor": true,
+            "energy_monitor": true,
+            "state_noise": true,
+            "param_drift": true,
+            "reverse_time": true,
+            "jacobian_diagnostics": true,
+            "event_outputs": true,
+            "cycle_outputs": true,
+            "event_phase_histogram": true,
+            "barrier_coupling": true,
+            "synapse_weights": true,
+            "integrators": ["euler", "heun", "rk4", "rk6", "rk45", "backward_euler", "ab3", "ab4"],
+            "step_control": ["pi", "table"],
+            "models": model::NAMES,
+            "presets": Preset::ALL.map(Preset::name),
+            "freeze_z": true,
+            "clamp_z": true,
+            "temperature_factor": true,
+            "stiffness_switching": true,
+            "simd": cfg!(feature = "simd"),
+            "simd_kernel": simd_kernel(),
+            "fixed_point": true,
+            "double_double": cfg!(feature = "double_double"),
+            "deterministic": cfg!(feature = "deterministic"),
+            "precise_spike_times": true,
+            "richardson": true,
+            "control_server": cfg!(feature = "control_server"),
+            "cosim": cfg!(all(feature = "cosim", unix)),
+            "cosim_frame": cosim::STATE_FIELDS,
+            "simulated_prep": true,
+            "gap_junction": true,
+            "graded_synapse": true,
+            "spike_kernels": ["alpha", "exp2"],
+            "conductance_inputs": true,
+            "dynamic_clamp": true,
+            "delayed_feedback": true,
+            "half_center": true,
+            "population": true,
+            "autodiff": cfg!(feature = "autodiff"),
+            "tracing": cfg!(feature = "tracing"),
+            "recorders": [],
+            "observers": false,
+            "network_streamer": false
+        }
+    })
+}
+
+// Optional extension: lists the extra entry points and subsystems this build
+// provides so hosts can avoid calling symbols an older build lacks.
+#[no_mangle]
+pub extern "C" fn rtsyn_plugin_capabilities_json() -> PluginString {
+    PluginString::from_string(capabilities().to_string())
+}
+
+// Optional extension: a single JSON document meant to be stored next to the
+// recorded data, bundling the config applied so far, capabilities, seeds,
+// calibration, version and build/host environment.
+#[no_mangle]
+extern "C" fn rtsyn_plugin_manifest_json(handle: *mut c_void) -> PluginString {
+    let Some(instance) = enter(handle) else {
+        return PluginString::from_string("{}".to_string());
+    };
+    PluginString::from_string(instance.manifest().to_string())
+}
+
+const API: PluginApi = PluginApi {
+    create,
+    destroy,
+    meta_json,
+    inputs_json,
+    outputs_json,
+    behavior_json: Some(behavior_json),
+    ui_schema_json: Some(ui_schema_json),
+    set_config_json,
+    set_input,
+    process,
+    get_output,
+};
+
+#[no_mangle]
+pub extern "C" fn rtsyn_plugin_api() -> *const PluginApi {
+    static V1: PluginApi = API;
+    &V1 as *const PluginApi
+}
+
+type HandleJson = extern "C" fn(*mut c_void) -> PluginString;
+
+// Extended table returned by rtsyn_plugin_api_v2. `base` is the v1 table;
+// every extension is a nullable function pointer, so hosts test for null
+// instead of resolving symbols one by one. New fields are only ever
+// appended: hosts must not read past `size` bytes, which lets a newer host
+// load an older build.
+#[repr(C)]
+pub struct PluginApiV2 {
+    pub version: u32,
+    pub size: u32,
+    pub base: PluginApi,
+    // Discovery and diagnostics.
+    pub capabilities_json: Option<extern "C" fn() -> PluginString>,
+    pub manifest_json: Option<HandleJson>,
+    pub stats_json: Option<HandleJson>,
+    pub field_gradients_json: Option<HandleJson>,
+    // State.
+    pub get_state_blob: Option<extern "C" fn(*mut c_void, *mut u8, usize) -> usize>,
+    pub set_state_blob: Option<extern "C" fn(*mut c_void, *const u8, usize) -> i32>,
+    pub clear_fault: Option<extern "C" fn(*mut c_void)>,
+    // Events.
+    pub set_event_callback: Option<extern "C" fn(*mut c_void, Option<EventCallback>, *mut c_void)>,
+    // Bulk IO and scheduling.
+    pub read_spectrogram: Option<extern "C" fn(*mut c_void, *mut f64, usize) -> usize>,
+    pub read_outputs_timestamped: Option<extern "C" fn(*mut c_void, *const u8, usize, *mut f64, usize) -> usize>,
+    pub set_tick_hint: Option<extern "C" fn(*mut c_void, u64)>,
+    // Status-code variants of the v1 calls.
+    pub get_output_status: Option<extern "C" fn(*mut c_void, *const u8, usize, *mut f64) -> i32>,
+    pub set_input_status: Option<extern "C" fn(*mut c_void, *const u8, usize, f64) -> i32>,
+    pub set_config_status: Option<extern "C" fn(*mut c_void, *const u8, usize) -> i32>,
+    pub process_status: Option<extern "C" fn(*mut c_void, u64, f64) -> i32>,
+    // Process-wide settings.
+    pub set_global_seed: Option<extern "C" fn(u64)>,
+    pub install_json_logging: Option<extern "C" fn(*const u8, usize) -> i32>,
+    // Stimulus design.
+    pub export_bursts: Option<extern "C" fn(*mut c_void, *const u8, usize) -> i32>,
+    // Local stability.
+    pub jacobian_json: Option<HandleJson>,
+    // Internal coupling.
+    pub get_weight: Option<extern "C" fn(*mut c_void, usize, usize, *mut f64) -> i32>,
+    pub set_weight: Option<extern "C" fn(*mut c_void, usize, usize, f64) -> i32>,
+}
+
+#[no_mangle]
+pub extern "C" fn rtsyn_plugin_api_v2() -> *const PluginApiV2 {
+    static V2: PluginApiV2 = PluginApiV2 {
+        version: 2,
+        size: std::mem::size_of::<PluginApiV2>() as u32,
+        base: API,
+        capabilities_json: Some(rtsyn_plugin_capabilities_json),
+        manifest_json: Some(rtsyn_plugin_manifest_json),
+        stats_json: Some(rtsyn_plugin_stats_json),
+        field_gradients_json: if cfg!(feature = "autodiff") {
+            Some(rtsyn_plugin_field_gradients_json)
+        } else {
+            None
+        },
+        get_state_blob: Some(rtsyn_plugin_get_state_blob),
+        set_state_blob: Some(rtsyn_plugin_set_state_blob),
+        clear_fault: Some(rtsyn_plugin_clear_fault),
+        set_event_callback: Some(rtsyn_plugin_set_event_callback),
+        read_spectrogram: Some(rtsyn_plugin_read_spectrogram),
+        read_outputs_timestamped: Some(rtsyn_plugin_read_outputs_timestamped),
+        set_tick_hint: Some(rtsyn_plugin_set_tick_hint),
+        get_output_status: Some(rtsyn_plugin_get_output_status),
+        set_input_status: Some(rtsyn_plugin_set_input_status),
+        set_config_status: Some(rtsyn_plugin_set_config_status),
+        process_status: Some(rtsyn_plugin_process_status),
+        set_global_seed: Some(rtsyn_plugin_set_global_seed),
+        install_json_logging: if cfg!(feature = "tracing") {
+            Some(rtsyn_plugin_install_json_logging)
+        } else {
+            None
+        },
+        export_bursts: Some(rtsyn_plugin_export_bursts),
+        jacobian_json: Some(rtsyn_plugin_jacobian_json),
+        get_weight: Some(rtsyn_plugin_get_weight),
+        set_weight: Some(rtsyn_plugin_set_weight),
+    };
+    &V2 as *const PluginApiV2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reversing a run for a few ticks retraces the forward trajectory, with
+    // t counting back down, under both the embedded and the step-doubled
+    // error estimate. The cycle repels in reverse, so local error grows
+    // about e-fold every 0.1 time units and the retrace is kept short and
+    // tightly toleranced.
+    #[test]
+    fn reverse_time_retraces_the_forward_run() {
+        let tol = 1e-10;
+        let bound = 1e-5;
+        for integrator in ["rk6", "rk45"] {
+            let mut hr = HindmarshRosev2Rust::new(1);
+            hr.set_config(&serde_json::json!({"e": 2.5, "integrator": integrator, "abs_tol": tol, "rel_tol": tol}));
+            let mut trail = Vec::new();
+            for tick in 0..5_000 {
+                trail.push((hr.t, [hr.x, hr.y, hr.z]));
+                hr.run_tick(tick, 0.001);
+            }
+            hr.set_config(&serde_json::json!({"reverse_time": true}));
+            for tick in 0..5 {
+                hr.run_tick(tick, 0.001);
+            }
+            let (t, vars) = trail[trail.len() - 5];
+            assert!((hr.t - t).abs() < 1e-9, "{integrator}: t {} against {t}", hr.t);
+            for (a, b) in vars.iter().zip([hr.x, hr.y, hr.z]) {
+                assert!((a - b).abs() < bound, "{integrator}: state {a} against {b}");
+            }
+        }
+    }
+
+    // Calibrated runs default to PI control, which drives the configured
+    // integrator by step doubling over the tick's exact calibrated span;
+    // "table" brings back the legacy fixed steps, s_points of dt per tick.
+    #[test]
+    fn calibrated_runs_default_to_pi_control() {
+        let mut pi = HindmarshRosev2Rust::new(1);
+        let mut table = HindmarshRosev2Rust::new(2);
+        pi.set_config(&serde_json::json!({"integrator": "rk4"}));
+        table.set_config(&serde_json::json!({"integrator": "rk4", "step_control": "table"}));
+        assert!(pi.burst_duration > 0.0);
+        assert_eq!(pi.step_control, StepControl::Pi);
+        for tick in 0..100 {
+            pi.run_tick(tick, 0.001);
+            table.run_tick(tick, 0.001);
+        }
+        assert!(pi.adaptive.accepted() > 0);
+        assert_eq!(table.adaptive.accepted(), 0);
+        assert!((pi.t - 100.0 * pi.exact_tick_span()).abs() < 1e-9);
+        assert!((table.t - 100.0 * table.dt * table.s_points as f64).abs() < 1e-9);
+        assert_ne!(pi.exact_tick_span(), table.dt * table.s_points as f64);
+    }
+
+    // A checkpoint taken mid-run with parameter drift and the simulated
+    // preparation enabled resumes the original run sample for sample.
+    #[test]
+    fn restored_checkpoint_continues_the_drifting_run() {
+        let config = serde_json::json!({
+            "e": 2.5,
+            "e_drift_sigma": 0.05,
+            "mu_drift_sigma": 0.0002,
+            "simulated_prep": {}
+        });
+        let mut original = HindmarshRosev2Rust::new(1);
+        original.set_config(&config);
+        for tick in 0..1_000 {
+            original.run_tick(tick, 0.001);
+        }
+        let mut blob = vec![0; original.snapshot().size()];
+        original.snapshot().write(&mut blob);
+
+        let mut restored = HindmarshRosev2Rust::new(1);
+        restored.set_config(&config);
+        assert!(restored.restore(&Snapshot::read(&blob).unwrap()));
+        assert_ne!(original.param_drift.e.offset(), 0.0);
+        for tick in 1_000..2_000 {
+            original.run_tick(tick, 0.001);
+            restored.run_tick(tick, 0.001);
+            assert_eq!([restored.x, restored.y, restored.z], [original.x, original.y, original.z], "tick {tick}");
+            assert_eq!(restored.param_drift.e.offset(), original.param_drift.e.offset());
+        }
+    }
+
+    // Each input_median key sets the window of its own current input; keys
+    // that name no current input are rejected and reported.
+    #[test]
+    fn input_median_windows_apply_per_port() {
+        let mut hr = HindmarshRosev2Rust::new(1);
+        hr.set_config(&serde_json::json!({"input_median": {"i_syn": 3, "i_ext": 3, "v_live": 3, "i_syn2": 3}}));
+        assert_eq!(hr.input_syn.median.window(), 3);
+        assert_eq!(hr.input_ext.median.window(), 3);
+        let rejected: Vec<&str> = hr.config_errors.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(rejected, ["input_median.i_syn2", "input_median.v_live"]);
+        for value in [0.1, 0.1, 5.0] {
+            hr.set_input("i_ext", value);
+        }
+        assert_eq!(hr.input_ext.value, 0.1);
+        hr.set_config(&serde_json::json!({"input_median": {"i_ext": 0}}));
+        assert!(hr.config_errors.is_empty());
+        assert_eq!(hr.input_syn.median.window(), 3);
+        hr.set_input("i_ext", 5.0);
+        assert_eq!(hr.input_ext.value, 5.0);
+    }
+
+    // Switching a native set with vh != 1 to the canonical model continues
+    // the same trajectory, with z reported in units of vh z.
+    #[test]
+    fn switching_to_canonical_keeps_the_trajectory() {
+        let native = serde_json::json!({"vh": 1.5, "mu": 0.004});
+        let mut reference = HindmarshRosev2Rust::new(1);
+        let mut switched = HindmarshRosev2Rust::new(2);
+        reference.set_config(&native);
+        switched.set_config(&native);
+        for tick in 0..200 {
+            if tick == 100 {
+                switched.set_config(&serde_json::json!({"model": "hr3_canonical"}));
+                assert_eq!(switched.vh, 1.0);
+                assert!((switched.mu - 0.006).abs() < 1e-15);
+            }
+            reference.run_tick(tick, 0.001);
+            switched.run_tick(tick, 0.001);
+        }
+        // Rescaling z rounds.
+        let tol = 1e5 * f64::EPSILON;
+        assert!((switched.x - reference.x).abs() < tol);
+        assert!((switched.y - reference.y).abs() < tol);
+        assert!((switched.z - 1.5 * reference.z).abs() < tol);
+        let reported = formulation::canonical(&reference.params(0.0), reference.z);
+        assert_eq!(formulation::canonical(&switched.params(0.0), switched.z)["r"], reported["r"]);
+    }
+
+    // hr4's u is stepped with x, y and z, so a run follows the four-variable
+    // system integrated directly at a fine fixed step.
+    #[test]
+    fn hr4_steps_u_with_the_core() {
+        let mut hr = HindmarshRosev2Rust::new(1);
+        hr.set_config(&serde_json::json!({"model": "hr4", "I": 2.5, "u": 0.5}));
+        let model = SecondSlow { u: 0.5, u0: 0.5, ..SecondSlow::PINTO };
+        let params = hr.core_params(0.0);
+        let mut state = [hr.x, hr.y, hr.z, 0.5];
+        for tick in 0..2_000 {
+            hr.run_tick(tick, 0.001);
+        }
+        let steps = 20_000;
+        let h = hr.t / steps as f64;
+        for _ in 0..steps {
+            state = Integrator::Rk4.step(state, h, |v| model.field(*v, &params), |v| model.jacobian(*v, &params));
+        }
+        for (a, b) in state.iter().zip([hr.x, hr.y, hr.z, hr.model.state()[0]]) {
+            assert!((a - b).abs() < 1e-4, "state {a} against {b}");
+        }
+    }
+
+    // With portable elementary functions the trajectory is bit-identical on
+    // every platform, so its checksum is pinned. The config runs exp through
+    // exponential z and ln and cos through seeded noise.
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn pins_the_deterministic_trajectory_checksum() {
+        let mut hr = HindmarshRosev2Rust::new(1);
+        hr.set_config(&serde_json::json!({"seed": 7, "noise_x": 0.01, "exponential_z": true}));
+        for tick in 0..2_000 {
+            hr.run_tick(tick, 0.001);
+        }
+        assert_eq!(format!("{:016x}", hr.trajectory_checksum), "26f5735975eab42d");
+    }
+
+    #[test]
+    fn reads_and_writes_internal_synapse_weights() {
+        let mut hr = HindmarshRosev2Rust::new(1);
+        assert_eq!(hr.weight(0, 1), Err(Status::NotConfigured));
+        hr.set_config(&serde_json::json!({"half_center": {"g": 0.1}}));
+        assert_eq!(hr.set_weight(1, 0, 0.3), Status::Ok);
+        assert_eq!(hr.weight(0, 1), Ok(0.3));
+        assert_eq!(hr.weight(1, 1), Err(Status::BadArgument));
+
+        hr.set_config(&serde_json::json!({
+            "population": {"weights": [[0, 0.1, 0], [0.1, 0, 0.1], [0, 0.1, 0]]},
+            "synapse_weights": [[2, 0, 0.25]]
+        }));
+        assert_eq!(hr.weight(2, 0), Ok(0.25));
+        assert_eq!(hr.set_weight(0, 2, -0.5), Status::Ok);
+        assert_eq!(hr.weight(0, 2), Ok(-0.5));
+        assert_eq!(hr.set_weight(3, 0, 0.1), Status::BadArgument);
+        assert_eq!(hr.set_weight(0, 1, f64::NAN), Status::BadArgument);
+        assert_eq!(hr.weight(0, 1), Ok(0.1));
+    }
+
+    // The interlock sees the total injected current, and a spike on a tick
+    // deferred by batching still trips it.
+    #[test]
+    fn interlock_checks_the_total_current_on_every_tick() {
+        let mut hr = HindmarshRosev2Rust::new(1);
+        hr.set_config(&serde_json::json!({"input_limit": 1.0, "i_ext": 2.0}));
+        hr.run_tick(0, 0.001);
+        assert!(hr.interlock.fault());
+
+        let mut hr = HindmarshRosev2Rust::new(2);
+        hr.set_config(&serde_json::json!({"input_limit": 1.0}));
+        hr.batch.hint(4);
+        hr.set_input("i_syn", 5.0);
+        hr.run_tick(0, 0.001);
+        hr.set_input("i_syn", 0.0);
+        assert!(hr.interlock.fault());
+    }
 }
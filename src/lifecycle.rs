@@ -0,0 +1,250 @@
+// Instance handles that stay sound against calls racing with destroy and
+// with each other. A handle names a slot in a process-wide table plus the
+// generation the slot was at when the instance went in; slots are never
+// freed, so a handle used after destroy only finds a newer generation and
+// gets its null-handle fallback. The instance sits behind its slot's mutex:
+// calls from different threads take turns, and destroy waits for the one
+// inside to return. A call or destroy made from inside one of the
+// instance's own event callbacks gets the fallback rather than deadlocking
+// on the lock its outer call holds.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+// Low bits of a handle hold the slot index plus one, so no handle is null;
+// the high bits hold the generation.
+const INDEX_BITS: u32 = usize::BITS / 2;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: usize = usize::MAX >> INDEX_BITS;
+
+struct Contents {
+    generation: usize,
+    value: Option<Box<dyn Any>>,
+}
+
+// The instance only ever moves between threads under its slot's mutex,
+// which is what the host already relies on when it hands one handle to
+// several threads.
+unsafe impl Send for Contents {}
+
+struct Slot {
+    contents: Mutex<Contents>,
+}
+
+struct Table {
+    slots: Vec<&'static Slot>,
+    free: Vec<usize>,
+}
+
+static TABLE: Mutex<Table> = Mutex::new(Table {
+    slots: Vec::new(),
+    free: Vec::new(),
+});
+
+thread_local! {
+    // Slots this thread is inside, so re-entry is refused instead of
+    // blocking on itself.
+    static HELD: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+// A panic caught by status::guard poisons the lock; the instance is still
+// as usable as the panicking call left it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn encode(index: usize, generation: usize) -> *mut c_void {
+    ((generation << INDEX_BITS) | (index + 1)) as *mut c_void
+}
+
+fn decode(handle: *mut c_void) -> Option<(usize, usize)> {
+    let bits = handle as usize;
+    let index = (bits & INDEX_MASK).checked_sub(1)?;
+    Some((index, bits >> INDEX_BITS))
+}
+
+fn held(index: usize) -> bool {
+    HELD.with(|held| held.borrow().contains(&index))
+}
+
+fn slot(index: usize) -> Option<&'static Slot> {
+    lock(&TABLE).slots.get(index).copied()
+}
+
+pub struct Entered<'a, T> {
+    index: usize,
+    contents: MutexGuard<'a, Contents>,
+    value: PhantomData<&'a mut T>,
+}
+
+impl<T: 'static> Deref for Entered<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        let value = self.contents.value.as_deref().and_then(|v| v.downcast_ref());
+        value.expect("enter checked the slot holds a T")
+    }
+}
+
+impl<T: 'static> DerefMut for Entered<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        let value = self.contents.value.as_deref_mut().and_then(|v| v.downcast_mut());
+        value.expect("enter checked the slot holds a T")
+    }
+}
+
+impl<T> Drop for Entered<'_, T> {
+    fn drop(&mut self) {
+        HELD.with(|held| held.borrow_mut().retain(|&index| index != self.index));
+    }
+}
+
+// Null once the table has run out of indices.
+pub fn into_handle<T: 'static>(value: T) -> *mut c_void {
+    let mut table = lock(&TABLE);
+    let index = match table.free.pop() {
+        Some(index) => index,
+        None if table.slots.len() < INDEX_MASK => {
+            table.slots.push(Box::leak(Box::new(Slot {
+                contents: Mutex::new(Contents {
+                    generation: 0,
+                    value: None,
+                }),
+            })));
+            table.slots.len() - 1
+        }
+        None => return std::ptr::null_mut(),
+    };
+    let slot = table.slots[index];
+    drop(table);
+    let mut contents = lock(&slot.contents);
+    contents.value = Some(Box::new(value));
+    encode(index, contents.generation)
+}
+
+// Blocks while another thread is inside the instance. None for a null or
+// destroyed handle, or one this thread is already inside.
+pub fn enter<'a, T: 'static>(handle: *mut c_void) -> Option<Entered<'a, T>> {
+    let (index, generation) = decode(handle)?;
+    if held(index) {
+        return None;
+    }
+    let contents = lock(&slot(index)?.contents);
+    if contents.generation != generation || !contents.value.as_deref()?.is::<T>() {
+        return None;
+    }
+    HELD.with(|held| held.borrow_mut().push(index));
+    Some(Entered {
+        index,
+        contents,
+        value: PhantomData,
+    })
+}
+
+// Waits for the call inside, if any, then drops the instance; of several
+// racing destroys only the first finds its generation.
+pub fn destroy(handle: *mut c_void) {
+    let Some((index, generation)) = decode(handle) else {
+        return;
+    };
+    if held(index) {
+        return;
+    }
+    let Some(slot) = slot(index) else {
+        return;
+    };
+    let mut contents = lock(&slot.contents);
+    if contents.generation != generation {
+        return;
+    }
+    let value = contents.value.take();
+    contents.generation = (generation + 1) & GENERATION_MASK;
+    drop(contents);
+    lock(&TABLE).free.push(index);
+    drop(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn stale_handles_find_nothing() {
+        let handle = into_handle(1u32);
+        destroy(handle);
+        assert!(enter::<u32>(handle).is_none());
+        // The slot is reused under a new generation.
+        let reused = into_handle(2u32);
+        assert_ne!(reused, handle);
+        assert!(enter::<u32>(handle).is_none());
+        assert_eq!(*enter::<u32>(reused).unwrap(), 2);
+        destroy(handle);
+        assert_eq!(*enter::<u32>(reused).unwrap(), 2);
+        destroy(reused);
+    }
+
+    #[test]
+    fn refuses_re_entry_from_the_same_thread() {
+        let handle = into_handle(0u32);
+        let entered = enter::<u32>(handle).unwrap();
+        assert!(enter::<u32>(handle).is_none());
+        destroy(handle);
+        drop(entered);
+        assert!(enter::<u32>(handle).is_some());
+        destroy(handle);
+    }
+
+    #[test]
+    fn calls_on_several_threads_take_turns() {
+        let handle = into_handle(0u64) as usize;
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    for _ in 0..10_000 {
+                        let mut entered = enter::<u64>(handle as *mut c_void).unwrap();
+                        // A non-atomic read-modify-write; any overlap loses counts.
+                        let n = *entered;
+                        std::hint::black_box(&mut *entered);
+                        *entered = n + 1;
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(*enter::<u64>(handle as *mut c_void).unwrap(), 40_000);
+        destroy(handle as *mut c_void);
+    }
+
+    #[test]
+    fn destroy_waits_for_calls_racing_with_it() {
+        let handle = into_handle(vec![0u8; 64]) as usize;
+        let stop = Arc::new(AtomicBool::new(false));
+        let callers: Vec<_> = (0..4)
+            .map(|_| {
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        if let Some(mut entered) = enter::<Vec<u8>>(handle as *mut c_void) {
+                            entered.iter_mut().for_each(|b| *b = b.wrapping_add(1));
+                        }
+                    }
+                })
+            })
+            .collect();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        destroy(handle as *mut c_void);
+        assert!(enter::<Vec<u8>>(handle as *mut c_void).is_none());
+        stop.store(true, Ordering::Relaxed);
+        for caller in callers {
+            caller.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,371 @@
+// Elementary functions for everything that feeds the trajectory or an
+// output. By default they are the platform's libm. With the `deterministic`
+// feature they are computed here from +, -, *, / and sqrt only, which IEEE
+// 754 rounds identically everywhere, so the same config and inputs give
+// bit-identical results on x86_64 and aarch64 (Rust never contracts a * b + c
+// into an FMA on its own). The portable versions are accurate to a few ulp,
+// except powf, which goes through exp(y ln x) and so loses about |y ln x|
+// ulp.
+
+#[cfg(not(feature = "deterministic"))]
+mod imp {
+    pub fn exp(x: f64) -> f64 {
+        x.exp()
+    }
+
+    pub fn ln(x: f64) -> f64 {
+        x.ln()
+    }
+
+    pub fn sin(x: f64) -> f64 {
+        x.sin()
+    }
+
+    pub fn cos(x: f64) -> f64 {
+        x.cos()
+    }
+
+    pub fn sin_cos(x: f64) -> (f64, f64) {
+        x.sin_cos()
+    }
+
+    pub fn tanh(x: f64) -> f64 {
+        x.tanh()
+    }
+
+    pub fn powf(x: f64, y: f64) -> f64 {
+        x.powf(y)
+    }
+
+    pub fn powi(x: f64, n: i32) -> f64 {
+        x.powi(n)
+    }
+
+    pub fn cbrt(x: f64) -> f64 {
+        x.cbrt()
+    }
+
+    pub fn acos(x: f64) -> f64 {
+        x.acos()
+    }
+
+    pub fn atan2(y: f64, x: f64) -> f64 {
+        y.atan2(x)
+    }
+
+    pub fn hypot(x: f64, y: f64) -> f64 {
+        x.hypot(y)
+    }
+}
+
+#[cfg(feature = "deterministic")]
+mod imp {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, LOG2_E, PI, SQRT_2};
+
+    // ln 2 and pi/2 split so that k * HI is exact for the k that occur.
+    const LN2_HI: f64 = 6.931_471_803_691_238e-1;
+    const LN2_LO: f64 = 1.908_214_929_270_587_7e-10;
+    const PIO2_1: f64 = 1.570_796_326_734_125_6;
+    const PIO2_2: f64 = 6.077_100_506_303_966e-11;
+    const PIO2_3: f64 = 2.022_266_248_711_166_5e-21;
+    // Minimax kernels on [-pi/4, pi/4], from fdlibm.
+    const SIN: [f64; 6] = [
+        -1.666_666_666_666_663_2e-1,
+        8.333_333_333_322_49e-3,
+        -1.984_126_982_985_795e-4,
+        2.755_731_370_707_006_8e-6,
+        -2.505_076_025_340_686_3e-8,
+        1.589_690_995_211_55e-10,
+    ];
+    const COS: [f64; 6] = [
+        4.166_666_666_666_66e-2,
+        -1.388_888_888_887_411e-3,
+        2.480_158_728_947_673e-5,
+        -2.755_731_435_139_066_3e-7,
+        2.087_572_321_298_175e-9,
+        -1.135_964_755_778_819_5e-11,
+    ];
+
+    fn horner(x: f64, coefficients: &[f64]) -> f64 {
+        coefficients.iter().rev().fold(0.0, |sum, &c| sum * x + c)
+    }
+
+    // 2^k for -2044 <= k <= 2046, applied in two halves so neither factor
+    // leaves the normal range.
+    fn scale(x: f64, k: i32) -> f64 {
+        let half = k / 2;
+        let factor = |n: i32| f64::from_bits(((n + 1023) as u64) << 52);
+        x * factor(half) * factor(k - half)
+    }
+
+    // exp(r) - 1 by Taylor series, for |r| <= 0.35.
+    fn expm1_small(r: f64) -> f64 {
+        let terms = (2..=14).rev().fold(0.0, |sum, n| (sum + 1.0) * r / n as f64);
+        r * (1.0 + terms)
+    }
+
+    pub fn exp(x: f64) -> f64 {
+        if x.is_nan() {
+            return x;
+        }
+        if x > 709.782_712_893_384 {
+            return f64::INFINITY;
+        }
+        if x < -745.133_219_101_941_1 {
+            return 0.0;
+        }
+        let k = (x * LOG2_E).round();
+        let r = (x - k * LN2_HI) - k * LN2_LO;
+        scale(1.0 + expm1_small(r), k as i32)
+    }
+
+    pub fn ln(x: f64) -> f64 {
+        if x.is_nan() || x < 0.0 {
+            return f64::NAN;
+        }
+        if x == 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if x.is_infinite() {
+            return x;
+        }
+        let (x, bias) = if x < f64::MIN_POSITIVE {
+            (x * 18_014_398_509_481_984.0, 54)
+        } else {
+            (x, 0)
+        };
+        let bits = x.to_bits();
+        let mut e = ((bits >> 52) & 0x7ff) as i32 - 1023 - bias;
+        let mut m = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+        if m > SQRT_2 {
+            m *= 0.5;
+            e += 1;
+        }
+        // ln m = 2 atanh s with |s| <= 0.172.
+        let s = (m - 1.0) / (m + 1.0);
+        let s2 = s * s;
+        let series = (1..12).rev().fold(0.0, |sum, n| (sum + 1.0 / (2 * n + 1) as f64) * s2);
+        let e = e as f64;
+        e * LN2_HI + (2.0 * s * (1.0 + series) + e * LN2_LO)
+    }
+
+    // x - k pi/2 with k reduced mod 4. Accurate while |x| < 2^20 pi/2;
+    // beyond that the result is still reproducible, just not the true sine.
+    fn reduce(x: f64) -> (f64, u32) {
+        let k = (x / FRAC_PI_2).round();
+        let r = ((x - k * PIO2_1) - k * PIO2_2) - k * PIO2_3;
+        (r, k.rem_euclid(4.0) as u32)
+    }
+
+    fn sin_kernel(r: f64) -> f64 {
+        let z = r * r;
+        r + r * z * horner(z, &SIN)
+    }
+
+    fn cos_kernel(r: f64) -> f64 {
+        let z = r * r;
+        1.0 - 0.5 * z + z * z * horner(z, &COS)
+    }
+
+    pub fn sin(x: f64) -> f64 {
+        if !x.is_finite() {
+            return f64::NAN;
+        }
+        let (r, quadrant) = reduce(x);
+        match quadrant {
+            0 => sin_kernel(r),
+            1 => cos_kernel(r),
+            2 => -sin_kernel(r),
+            _ => -cos_kernel(r),
+        }
+    }
+
+    pub fn cos(x: f64) -> f64 {
+        if !x.is_finite() {
+            return f64::NAN;
+        }
+        let (r, quadrant) = reduce(x);
+        match quadrant {
+            0 => cos_kernel(r),
+            1 => -sin_kernel(r),
+            2 => -cos_kernel(r),
+            _ => sin_kernel(r),
+        }
+    }
+
+    // Both from one reduction; each equals what sin and cos return alone.
+    pub fn sin_cos(x: f64) -> (f64, f64) {
+        if !x.is_finite() {
+            return (f64::NAN, f64::NAN);
+        }
+        let (r, quadrant) = reduce(x);
+        let (s, c) = (sin_kernel(r), cos_kernel(r));
+        match quadrant {
+            0 => (s, c),
+            1 => (c, -s),
+            2 => (-s, -c),
+            _ => (-c, s),
+        }
+    }
+
+    pub fn tanh(x: f64) -> f64 {
+        if x.is_nan() {
+            return x;
+        }
+        let a = x.abs();
+        if a > 22.0 {
+            return 1f64.copysign(x);
+        }
+        let t = if 2.0 * a <= 0.35 { expm1_small(2.0 * a) } else { exp(2.0 * a) - 1.0 };
+        (t / (t + 2.0)).copysign(x)
+    }
+
+    pub fn powi(x: f64, n: i32) -> f64 {
+        let mut base = x;
+        let mut m = n.unsigned_abs();
+        let mut result = 1.0;
+        while m > 0 {
+            if m & 1 == 1 {
+                result *= base;
+            }
+            m >>= 1;
+            if m > 0 {
+                base *= base;
+            }
+        }
+        if n < 0 {
+            1.0 / result
+        } else {
+            result
+        }
+    }
+
+    pub fn powf(x: f64, y: f64) -> f64 {
+        if y == 0.0 || x == 1.0 {
+            return 1.0;
+        }
+        if x.is_nan() || y.is_nan() {
+            return f64::NAN;
+        }
+        let integral = y.fract() == 0.0;
+        if x < 0.0 && !integral && y.is_finite() {
+            return f64::NAN;
+        }
+        let odd = integral && y.abs() < 9_007_199_254_740_992.0 && y.rem_euclid(2.0) == 1.0;
+        let magnitude = if x == 0.0 || x.is_infinite() {
+            if (y > 0.0) == (x == 0.0) {
+                0.0
+            } else {
+                f64::INFINITY
+            }
+        } else if y.is_infinite() {
+            match x.abs().partial_cmp(&1.0) {
+                Some(std::cmp::Ordering::Less) if y > 0.0 => 0.0,
+                Some(std::cmp::Ordering::Greater) if y < 0.0 => 0.0,
+                Some(std::cmp::Ordering::Equal) => 1.0,
+                _ => f64::INFINITY,
+            }
+        } else if integral && y.abs() <= 64.0 {
+            powi(x.abs(), y as i32)
+        } else {
+            exp(y * ln(x.abs()))
+        };
+        if x.is_sign_negative() && odd {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn cbrt(x: f64) -> f64 {
+        if x == 0.0 || !x.is_finite() {
+            return x;
+        }
+        let a = x.abs();
+        let y = exp(ln(a) / 3.0);
+        let y = y - (y * y * y - a) / (3.0 * y * y);
+        y.copysign(x)
+    }
+
+    // atan t for 0 <= t <= 1, halving the argument twice so the series
+    // runs on t <= tan(pi/16).
+    fn atan_unit(t: f64) -> f64 {
+        let halve = |t: f64| t / (1.0 + (1.0 + t * t).sqrt());
+        let t = halve(halve(t));
+        let t2 = t * t;
+        let series = (1..13).rev().fold(0.0, |sum, n| {
+            let term = 1.0 / (2 * n + 1) as f64;
+            (if n % 2 == 1 { sum - term } else { sum + term }) * t2
+        });
+        4.0 * t * (1.0 + series)
+    }
+
+    pub fn atan2(y: f64, x: f64) -> f64 {
+        if x.is_nan() || y.is_nan() {
+            return f64::NAN;
+        }
+        let (ax, ay) = (x.abs(), y.abs());
+        let angle = if ay <= ax {
+            if ax == 0.0 {
+                0.0
+            } else if ay.is_infinite() {
+                FRAC_PI_4
+            } else {
+                atan_unit(ay / ax)
+            }
+        } else {
+            FRAC_PI_2 - atan_unit(ax / ay)
+        };
+        let angle = if x.is_sign_negative() { PI - angle } else { angle };
+        angle.copysign(y)
+    }
+
+    pub fn acos(x: f64) -> f64 {
+        if x.is_nan() || x.abs() > 1.0 {
+            return f64::NAN;
+        }
+        atan2(((1.0 - x) * (1.0 + x)).sqrt(), x)
+    }
+
+    pub fn hypot(x: f64, y: f64) -> f64 {
+        let (a, b) = (x.abs(), y.abs());
+        if a.is_infinite() || b.is_infinite() {
+            return f64::INFINITY;
+        }
+        if a.is_nan() || b.is_nan() {
+            return f64::NAN;
+        }
+        let (big, small) = if a >= b { (a, b) } else { (b, a) };
+        if big == 0.0 {
+            return 0.0;
+        }
+        let r = small / big;
+        big * (1.0 + r * r).sqrt()
+    }
+}
+
+pub use imp::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn sin_cos_matches_sin_and_cos() {
+        for i in -2000..2000 {
+            let x = i as f64 * 0.0123;
+            let (s, c) = sin_cos(x);
+            assert_eq!((s.to_bits(), c.to_bits()), (sin(x).to_bits(), cos(x).to_bits()), "at {x}");
+        }
+    }
+
+    #[test]
+    fn sin_cos_is_accurate() {
+        for i in -2000..2000 {
+            let x = i as f64 * 0.0123;
+            let (s, c) = sin_cos(x);
+            assert!((s - x.sin()).abs() < 4.0 * f64::EPSILON && (c - x.cos()).abs() < 4.0 * f64::EPSILON, "at {x}");
+        }
+    }
+}
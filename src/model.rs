@@ -0,0 +1,125 @@
+// Neuron models selected by the "model" config key. A model owns its vector
+// field and the parameter names it is configured with: `field` is the
+// derivative of the full state, x, y, z and any state variables of its own,
+// and `configure_core` reads the x, y, z core's parameters in the model's
+// formulation, see formulation.rs. The core's values stay on the instance,
+// where drift, temperature and the conductance inputs act on them.
+//
+// A model without extra state runs on field.rs directly, with every solver
+// option. One with extra state is stepped as a whole through `field` and
+// `jacobian` in every substep, by the configured integrator under fixed or
+// PI steps, so the extra state sees the core move within a tick; ab3 and ab4
+// take their RK4 starter there. The core-only options, multirate_ratio,
+// exponential_z, compensated_summation, richardson, fixed_point,
+// double_double and the approximant, do not apply to such a model. The extra
+// state variables are also outputs under their own names. Adding a model
+// means implementing NeuronModel and listing it in `parse`.
+
+use crate::field::{self, Params, Shape};
+use crate::formulation::Formulation;
+use crate::hr4::SecondSlow;
+use serde_json::Value;
+
+pub const NAMES: [&str; 3] = ["hr3", "hr3_canonical", "hr4"];
+
+// x, y, z and at most one variable of the model's own.
+pub const MAX_STATE: usize = 4;
+
+pub trait NeuronModel: std::fmt::Debug + Send {
+    fn name(&self) -> &'static str;
+
+    // The parameter set `configure_core` reads.
+    fn formulation(&self) -> Formulation;
+
+    // Reads the core's parameters from `config` into their native-equivalent
+    // set `core`.
+    fn configure_core(&self, config: &Value, core: &mut Params<f64>) {
+        self.formulation().configure(config, core);
+    }
+
+    // Names of the state variables beyond x, y, z, indexed like `state`.
+    fn state_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn state(&self) -> &[f64] {
+        &[]
+    }
+
+    fn state_mut(&mut self) -> &mut [f64] {
+        &mut []
+    }
+
+    // [x, y, z] followed by `state`, zero-padded.
+    fn full_state(&self, core: [f64; 3]) -> [f64; MAX_STATE] {
+        let mut full = [0.0; MAX_STATE];
+        full[..3].copy_from_slice(&core);
+        full[3..3 + self.state().len()].copy_from_slice(self.state());
+        full
+    }
+
+    // d/dt of a full state laid out as `full_state`, with `p` the core's
+    // parameters before the model's own contribution.
+    fn field(&self, state: [f64; MAX_STATE], p: &Params<f64>) -> [f64; MAX_STATE] {
+        let [dx, dy, dz] = field::derivatives([state[0], state[1], state[2]], p);
+        [dx, dy, dz, 0.0]
+    }
+
+    // d field / d state, laid out as `field`.
+    fn jacobian(&self, state: [f64; MAX_STATE], p: &Params<f64>) -> [[f64; MAX_STATE]; MAX_STATE] {
+        let core = field::jacobian([state[0], state[1], state[2]], p);
+        let mut out = [[0.0; MAX_STATE]; MAX_STATE];
+        for (row, core) in out.iter_mut().zip(core) {
+            row[..3].copy_from_slice(&core);
+        }
+        out
+    }
+
+    // The core's field with the extra state at its current value folded in,
+    // for diagnostics that evaluate the core alone; None leaves it as is,
+    // which keeps the textbook fast path.
+    fn shape(&self, _shape: Shape<f64>) -> Option<Shape<f64>> {
+        None
+    }
+
+    fn configure(&mut self, _config: &Value) {}
+
+    // Returns `state` to its configured initial values.
+    fn reset(&mut self) {}
+
+    fn parameters(&self) -> Value {
+        Value::Null
+    }
+
+    fn value(&self, name: &str) -> Option<f64> {
+        let index = self.state_names().iter().position(|var| *var == name)?;
+        self.state().get(index).copied()
+    }
+}
+
+// The three-variable model, which is the core alone, in either parameter
+// set.
+#[derive(Debug, Clone, Copy)]
+pub struct Hr3(pub Formulation);
+
+impl NeuronModel for Hr3 {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            Formulation::Native => "hr3",
+            Formulation::Canonical => "hr3_canonical",
+        }
+    }
+
+    fn formulation(&self) -> Formulation {
+        self.0
+    }
+}
+
+pub fn parse(name: &str) -> Option<Box<dyn NeuronModel>> {
+    match name {
+        "hr3" => Some(Box::new(Hr3(Formulation::Native))),
+        "hr3_canonical" => Some(Box::new(Hr3(Formulation::Canonical))),
+        "hr4" => Some(Box::new(SecondSlow::PINTO)),
+        _ => None,
+    }
+}
@@ -0,0 +1,103 @@
+// Slow random-walk wander of e and mu, for the regime changes seen over
+// minutes in biological preparations. Each parameter carries an offset from
+// its configured value that follows an Ornstein-Uhlenbeck walk in host time,
+//
+//   d offset = -offset / tau dt + sigma dW
+//
+// with sigma per square root of a second and tau in seconds (tau = 0 gives a
+// free random walk). A positive range reflects the offset at +/- range, so
+// the parameter never leaves value +/- range. The walk has its own seed, so
+// the same seed reproduces the same wander whatever else is enabled.
+
+use crate::rng::Rng;
+
+#[derive(Debug, Clone, Default)]
+pub struct Walk {
+    pub sigma: f64,
+    pub tau: f64,
+    pub range: f64,
+    offset: f64,
+}
+
+impl Walk {
+    pub fn enabled(&self) -> bool {
+        self.sigma > 0.0
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    fn step(&mut self, dt: f64, rng: &mut Rng) {
+        if !self.enabled() {
+            self.offset = 0.0;
+            return;
+        }
+        if self.tau > 0.0 {
+            self.offset -= self.offset * (dt / self.tau).min(1.0);
+        }
+        self.offset += self.sigma * dt.sqrt() * rng.gaussian();
+        if self.range > 0.0 {
+            // Reflect back inside; the clamp only matters for steps larger
+            // than the whole band.
+            if self.offset > self.range {
+                self.offset = 2.0 * self.range - self.offset;
+            } else if self.offset < -self.range {
+                self.offset = -2.0 * self.range - self.offset;
+            }
+            self.offset = self.offset.clamp(-self.range, self.range);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamDrift {
+    pub e: Walk,
+    pub mu: Walk,
+    seed: u64,
+    rng: Rng,
+}
+
+impl ParamDrift {
+    pub fn new(seed: u64) -> Self {
+        Self { e: Walk::default(), mu: Walk::default(), seed, rng: Rng::new(seed) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.e.enabled() || self.mu.enabled()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // A new seed restarts the wander from the configured values.
+    pub fn reseed(&mut self, seed: u64) {
+        if seed != self.seed {
+            self.seed = seed;
+            self.rng = Rng::new(seed);
+            self.e.offset = 0.0;
+            self.mu.offset = 0.0;
+        }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.rng.position()
+    }
+
+    // Resumes a checkpointed wander of the current seed.
+    pub fn restore(&mut self, position: u64, offsets: [f64; 2]) {
+        self.rng.set_position(position);
+        [self.e.offset, self.mu.offset] = offsets;
+    }
+
+    pub fn tick(&mut self, period: f64) {
+        if self.enabled() && period > 0.0 {
+            self.e.step(period, &mut self.rng);
+            self.mu.step(period, &mut self.rng);
+        } else {
+            self.e.offset = 0.0;
+            self.mu.offset = 0.0;
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// Distribution of the model's burst phase at which external events (e.g.
+// living-neuron spikes on event_in) arrive. Phase runs from 0 at a burst
+// onset to 1 at the next one, using the last measured cycle period. Besides
+// the histogram, the circular mean and vector strength summarize which way
+// and how tightly the events are locked to the model.
+
+use crate::math;
+use std::f64::consts::TAU;
+
+#[derive(Debug, Clone)]
+pub struct PhaseHistogram {
+    counts: Vec<u64>,
+    sum_cos: f64,
+    sum_sin: f64,
+    events: u64,
+}
+
+impl PhaseHistogram {
+    pub fn new(bins: usize) -> Self {
+        Self {
+            counts: vec![0; bins.max(1)],
+            sum_cos: 0.0,
+            sum_sin: 0.0,
+            events: 0,
+        }
+    }
+
+    pub fn bins(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn add(&mut self, phase: f64) {
+        let phase = phase.rem_euclid(1.0);
+        let bin = ((phase * self.counts.len() as f64) as usize).min(self.counts.len() - 1);
+        self.counts[bin] += 1;
+        let (sin, cos) = math::sin_cos(TAU * phase);
+        self.sum_cos += cos;
+        self.sum_sin += sin;
+        self.events += 1;
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    // Mean phase in [0, 1), or None before the first event.
+    pub fn circular_mean(&self) -> Option<f64> {
+        (self.events > 0).then(|| (math::atan2(self.sum_sin, self.sum_cos) / TAU).rem_euclid(1.0))
+    }
+
+    // Resultant length in [0, 1]: 0 for uniform phases, 1 for perfect locking.
+    pub fn vector_strength(&self) -> f64 {
+        if self.events == 0 {
+            return 0.0;
+        }
+        math::hypot(self.sum_cos, self.sum_sin) / self.events as f64
+    }
+}
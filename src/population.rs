@@ -0,0 +1,218 @@
+// Population of N HR cells coupled through a weight matrix: neuron 0 is the
+// instance's own cell and neurons 1..N-1 are integrated here. weights[i][j] is
+// the maximal conductance of the graded synapse from neuron j onto neuron i
+// (the half-center synapse, see half_center.rs), so
+//
+//   I_i = sum_j weights[i][j] (x_i - reversal) / (1 + exp(-slope (x_j - threshold)))
+//
+// enters dx_i/dt like i_syn; a reversal above rest makes a synapse
+// excitatory. As in the half-center all currents come from the states at the
+// start of the tick. Configured with
+//
+//   "population": {"weights": [[0, 0.1, 0], [0.1, 0, 0.1], [0, 0.1, 0]],
+//                  "reversal": -2.0, "threshold": -0.25, "slope": 10.0,
+//                  "e": [3.0, 3.1], "initial": [[-1.2, -6.0, 3.0], ...]}
+//
+// where "e" and "initial" list neurons 1..N-1 in order (missing entries copy
+// neuron 0's e and take staggered defaults). Each neuron k gets an input
+// i_syn[k] and an output x[k]; i_syn[0] and x[0] are the instance's own
+// i_syn and x. Removed again with "population": false. With the `simd`
+// feature neurons 1..N-1 are stepped four at a time, see simd.rs.
+
+use crate::field::Params;
+use crate::half_center::Synapse;
+use crate::inputs::{InputPort, Staleness};
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct Population {
+    weights: Vec<Vec<f64>>,
+    synapse: Synapse,
+    e: Vec<Option<f64>>,
+    initial: Vec<[f64; 3]>,
+    cells: Vec<[f64; 3]>,
+    inputs: Vec<InputPort>,
+    currents: Vec<f64>,
+    input_names: Vec<String>,
+    output_names: Vec<String>,
+}
+
+impl Population {
+    // None unless `weights` is a square matrix of at least two neurons.
+    pub fn new(config: &Value) -> Option<Self> {
+        let weights: Vec<Vec<f64>> = config
+            .get("weights")?
+            .as_array()?
+            .iter()
+            .map(|row| row.as_array().map(|row| row.iter().map(|w| w.as_f64().unwrap_or(0.0)).collect()))
+            .collect::<Option<_>>()?;
+        let n = weights.len();
+        if n < 2 || weights.iter().any(|row: &Vec<f64>| row.len() != n) {
+            return None;
+        }
+        let initial: Vec<[f64; 3]> = (1..n).map(|k| [-1.2, -6.0, 3.0 + 0.05 * k as f64]).collect();
+        let mut population = Self {
+            weights,
+            synapse: Synapse {
+                g: 1.0,
+                reversal: -2.0,
+                threshold: -0.25,
+                slope: 10.0,
+            },
+            e: vec![None; n - 1],
+            cells: initial.clone(),
+            initial,
+            inputs: vec![InputPort::new(); n - 1],
+            currents: vec![0.0; n],
+            input_names: (0..n).map(|k| format!("i_syn[{k}]")).collect(),
+            output_names: (0..n).map(|k| format!("x[{k}]")).collect(),
+        };
+        population.configure(config);
+        Some(population)
+    }
+
+    // Updates everything but the size; a new matrix of another size needs a
+    // new population.
+    pub fn configure(&mut self, config: &Value) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        self.synapse.reversal = get("reversal", self.synapse.reversal);
+        self.synapse.threshold = get("threshold", self.synapse.threshold);
+        self.synapse.slope = get("slope", self.synapse.slope);
+        if let Some(rows) = config.get("weights").and_then(|v| v.as_array()) {
+            for (row, values) in self.weights.iter_mut().zip(rows) {
+                for (weight, value) in row.iter_mut().zip(values.as_array().into_iter().flatten()) {
+                    *weight = value.as_f64().unwrap_or(0.0);
+                }
+            }
+        }
+        if let Some(values) = config.get("e").and_then(|v| v.as_array()) {
+            for (e, value) in self.e.iter_mut().zip(values) {
+                *e = value.as_f64();
+            }
+        }
+        if let Some(values) = config.get("initial").and_then(|v| v.as_array()) {
+            for (initial, value) in self.initial.iter_mut().zip(values) {
+                let state: Vec<f64> = value.as_array().into_iter().flatten().filter_map(Value::as_f64).collect();
+                if let Ok(state) = <[f64; 3]>::try_from(state) {
+                    *initial = state;
+                }
+            }
+            self.reset();
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.weights.len()
+    }
+
+    // weights[post][pre], or None on the diagonal or out of range.
+    pub fn weight(&self, post: usize, pre: usize) -> Option<f64> {
+        (post != pre).then(|| self.weights.get(post)?.get(pre).copied()).flatten()
+    }
+
+    // Returns false where `weight` would return None.
+    pub fn set_weight(&mut self, post: usize, pre: usize, weight: f64) -> bool {
+        match self.weights.get_mut(post).and_then(|row| row.get_mut(pre)) {
+            Some(entry) if post != pre => {
+                *entry = weight;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // States of neurons 1..N-1.
+    pub fn cells(&self) -> &[[f64; 3]] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [[f64; 3]] {
+        &mut self.cells
+    }
+
+    pub fn reset(&mut self) {
+        self.cells.clone_from(&self.initial);
+        self.currents.fill(0.0);
+    }
+
+    pub fn input_names(&self) -> impl Iterator<Item = &str> {
+        self.input_names.iter().map(String::as_str)
+    }
+
+    pub fn output_names(&self) -> impl Iterator<Item = &str> {
+        self.output_names.iter().map(String::as_str)
+    }
+
+    // Sets i_syn[k] for k >= 1; returns false for names that are not ours.
+    pub fn set_input(&mut self, name: &str, value: f64) -> bool {
+        self.input_mut(name).map(|input| input.set(value)).is_some()
+    }
+
+    // The port of an input named i_syn[k], k >= 1.
+    pub fn input_mut(&mut self, name: &str) -> Option<&mut InputPort> {
+        let k = index(name, "i_syn[").filter(|&k| k >= 1 && k < self.size())?;
+        self.inputs.get_mut(k - 1)
+    }
+
+    // x of neuron k >= 1 for an output named x[k].
+    pub fn output(&self, name: &str) -> Option<f64> {
+        let k = index(name, "x[").filter(|&k| k >= 1)?;
+        self.cells.get(k - 1).map(|cell| cell[0])
+    }
+
+    // Computes this tick's synaptic currents with neuron 0 at `x0` and
+    // returns the one into neuron 0.
+    pub fn begin_tick(&mut self, x0: f64) -> f64 {
+        let x = |k: usize| if k == 0 { x0 } else { self.cells[k - 1][0] };
+        for (i, row) in self.weights.iter().enumerate() {
+            self.currents[i] = row
+                .iter()
+                .enumerate()
+                .filter(|&(j, &weight)| j != i && weight != 0.0)
+                .map(|(j, &weight)| Synapse { g: weight, ..self.synapse }.current(x(j), x(i)))
+                .sum();
+        }
+        self.currents[0]
+    }
+
+    // Advances neurons 1..N-1 by the signed model time `span` with neuron 0's
+    // parameters `params`.
+    pub fn advance(&mut self, params: &Params<f64>, span: f64) {
+        let lane_params = |k: usize| (self.e[k].unwrap_or(params.e), self.inputs[k].value + self.currents[k + 1]);
+        #[cfg(feature = "simd")]
+        crate::simd::advance_cells(&mut self.cells, params, lane_params, span);
+        #[cfg(not(feature = "simd"))]
+        for (k, cell) in self.cells.iter_mut().enumerate() {
+            let (e, input) = lane_params(k);
+            *cell = crate::half_center::advance_cell(*cell, &Params { e, input, ..*params }, span);
+        }
+    }
+
+    pub fn tick_inputs(&mut self, period: f64, staleness: &Staleness) {
+        for input in &mut self.inputs {
+            input.tick(period, staleness);
+        }
+    }
+
+    pub fn stats(&self) -> Value {
+        serde_json::json!({
+            "size": self.size(),
+            "weights": self.weights,
+            "cells": self.cells,
+            "currents": self.currents,
+            "e": self.e,
+            "synapse": {
+                "reversal": self.synapse.reversal,
+                "threshold": self.synapse.threshold,
+                "slope": self.synapse.slope
+            }
+        })
+    }
+}
+
+// k for a port named `prefix`k].
+pub fn index(name: &str, prefix: &str) -> Option<usize> {
+    name.strip_prefix(prefix)?.strip_suffix(']')?.parse().ok()
+}
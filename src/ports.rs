@@ -0,0 +1,110 @@
+// Descriptive metadata for every port, reported by inputs_json/outputs_json
+// when the host opts in with "port_metadata": true. Model quantities are
+// dimensionless Hindmarsh-Rose units ("au").
+
+use serde_json::Value;
+
+pub struct PortInfo {
+    pub name: &'static str,
+    pub units: &'static str,
+    pub description: &'static str,
+    pub range: Option<(f64, f64)>,
+    pub default: f64,
+}
+
+const fn port(
+    name: &'static str,
+    units: &'static str,
+    description: &'static str,
+    range: Option<(f64, f64)>,
+    default: f64,
+) -> PortInfo {
+    PortInfo {
+        name,
+        units,
+        description,
+        range,
+        default,
+    }
+}
+
+pub const INPUT_INFO: &[PortInfo] = &[
+    port("i_syn", "au", "Synaptic current, subtracted from dx/dt", Some((-10.0, 10.0)), 0.0),
+    port("i_ext", "au", "External bias current added to dx/dt on top of the i_ext config value", Some((-10.0, 10.0)), 0.0),
+    port("e", "au", "Excitability e; each value replaces the configured one", None, 3.25),
+    port("period_in", "s", "Measured burst period of the living neuron", Some((0.0, 60.0)), 0.0),
+    port("v_live", "V", "Living membrane potential used for gain control", None, 0.0),
+    port("event_in", "flag", "External events; rising edges through 0.5 are phase-binned", Some((0.0, 1.0)), 0.0),
+    port("v_pre", "au", "Presynaptic potential of the g_gap electrical synapse", Some((-2.0, 2.5)), 0.0),
+    port("v_pre_syn", "au", "Presynaptic potential of the graded chemical synapse", None, 0.0),
+    port("spike_in", "flag", "Presynaptic spike events; every nonzero value is one spike", None, 0.0),
+    port("g_syn_exc", "au", "Excitatory conductance, reversing at e_rev_exc", None, 0.0),
+    port("g_syn_inh", "au", "Inhibitory conductance, reversing at e_rev_inh", None, 0.0),
+    port("z_clamp", "au", "Value z is held at while clamp_z is set", None, 0.0),
+    port("v_m", "V", "Membrane potential of the dynamically clamped living cell", None, 0.0),
+    port("i_syn1", "au", "Synaptic current into half-center neuron 1, same as i_syn", Some((-10.0, 10.0)), 0.0),
+    port("i_syn2", "au", "Synaptic current into half-center neuron 2", Some((-10.0, 10.0)), 0.0),
+];
+
+pub const OUTPUT_INFO: &[PortInfo] = &[
+    port("Membrane potential (V)", "V", "Fast variable x", Some((-2.0, 2.5)), 0.0),
+    port("Membrane potential (mV)", "mV", "Fast variable x times 1000", Some((-2000.0, 2500.0)), 0.0),
+    port("Shadow divergence", "au", "Distance between main and shadow state", None, 0.0),
+    port("Tuned parameter", "au", "Extremum-seeking parameter estimate", None, 0.0),
+    port("x_ac", "au", "High-pass filtered x", Some((-2.5, 2.5)), 0.0),
+    port("Scaled membrane potential", "V", "x mapped onto the living recording by AGC", None, 0.0),
+    port("Input stale", "flag", "1 while i_syn has not been updated recently", Some((0.0, 1.0)), 0.0),
+    port("fault", "flag", "1 while the input interlock fault is latched", Some((0.0, 1.0)), 0.0),
+    port("template_distance", "au", "Distance between the last burst and the target template", None, 0.0),
+    port("spike", "flag", "1 on ticks containing a spike onset", Some((0.0, 1.0)), 0.0),
+    port("burst_onset", "flag", "1 on ticks containing a burst onset", Some((0.0, 1.0)), 0.0),
+    port("adaptive_dt", "au", "Current internal step of the adaptive integrator", None, 0.0),
+    port("step_rejections", "count", "Steps rejected by the adaptive integrator this tick", None, 0.0),
+    port("cycle_mean_x", "au", "Mean x over the last complete burst cycle", None, 0.0),
+    port("cycle_peak_x", "au", "Peak x over the last complete burst cycle", None, 0.0),
+    port("cycle_period", "s", "Onset-to-onset period of the last burst cycle", None, 0.0),
+    port("I_cubic", "au", "Cubic term of dx/dt, b x^2 - a x^3", None, 0.0),
+    port("I_y", "au", "Contribution of y to dx/dt", None, 0.0),
+    port("I_z", "au", "Adaptation term of dx/dt, -vh*z", None, 0.0),
+    port("I_e", "au", "Constant applied current e", None, 0.0),
+    port("I_syn", "au", "Synaptic term of dx/dt: minus the drive current, plus any conductance input and delayed feedback current", None, 0.0),
+    port("z_balance_residual", "au", "Change in z minus the integral of dz/dt since the last rebase", None, 0.0),
+    port("epoch", "index", "Current trial epoch, -1 when no sequence is running", None, -1.0),
+    port("marker", "code", "Marker code of the current trial epoch", None, 0.0),
+    port("freq_error", "Hz", "Measured minus target burst frequency", None, 0.0),
+    port("duty_error", "ratio", "Measured minus target duty cycle", Some((-1.0, 1.0)), 0.0),
+    port("prep_v", "V", "Recorded membrane potential of the simulated preparation", None, 0.0),
+    port("prep_x", "au", "Fast variable of the simulated preparation", None, 0.0),
+    port("solver_switches", "count", "Switches between the explicit and implicit solver so far", None, 0.0),
+    port("implicit_active", "flag", "1 while stiffness has switched stepping to backward Euler", Some((0.0, 1.0)), 0.0),
+    port("diverged", "flag", "1 on ticks in which a non-finite step was discarded", Some((0.0, 1.0)), 0.0),
+    port("steps_clamped", "count", "Steps cut from this tick by max_steps_per_tick", None, 0.0),
+    port("spike_time", "s", "Host time of the last spike, interpolated within the step", None, 0.0),
+    port("energy_drift", "au", "Change in HR energy minus the integral of its exact rate", None, 0.0),
+    port("x1", "au", "Fast variable of half-center neuron 1", Some((-2.0, 2.5)), 0.0),
+    port("x2", "au", "Fast variable of half-center neuron 2", Some((-2.0, 2.5)), 0.0),
+    port("i_inject", "nA", "Dynamic clamp current into the living cell, conductance units times V", None, 0.0),
+    port("u", "au", "Second slow variable of the 4D model", None, 0.0),
+    port("richardson_error", "au", "Largest Richardson local error estimate of this tick's steps", None, 0.0),
+];
+
+fn lookup(table: &[PortInfo], name: &str) -> Value {
+    match table.iter().find(|info| info.name == name) {
+        Some(info) => serde_json::json!({
+            "name": info.name,
+            "units": info.units,
+            "description": info.description,
+            "range": info.range.map(|(lo, hi)| [lo, hi]),
+            "default": info.default
+        }),
+        None => serde_json::json!({ "name": name }),
+    }
+}
+
+pub fn describe_inputs(names: &[&str]) -> Value {
+    Value::Array(names.iter().map(|name| lookup(INPUT_INFO, name)).collect())
+}
+
+pub fn describe_outputs(names: &[&str]) -> Value {
+    Value::Array(names.iter().map(|name| lookup(OUTPUT_INFO, name)).collect())
+}
@@ -0,0 +1,179 @@
+// Simulated preparation for full-rig dry runs: a second, noisier HR cell
+// standing in for the living neuron, recorded through an emulated electrode.
+// Each tick the main model stimulates it through an electrical coupling and
+// its recording is fed back the way the host would: as v_live and as an
+// i_syn computed from the recorded voltage. Configured with
+//
+//   "simulated_prep": {"burst_duration": 1.3, "noise": 0.05, "gain_in": 0.1,
+//                      "gain_out": 0.1, "electrode_noise": 0.0005, ...}
+//
+// and removed again with "simulated_prep": false.
+
+use crate::drift::DriftInjection;
+use crate::field::{derivatives, jacobian, Params};
+use crate::math;
+use crate::rk::Integrator;
+use crate::rng::Rng;
+use crate::state::PrepSnapshot;
+use serde_json::Value;
+
+// Largest internal step of the preparation, in model time.
+const MAX_STEP: f64 = 0.05;
+
+#[derive(Debug, Clone)]
+pub struct SimulatedPrep {
+    params: Params<f64>,
+    // Model time per second of host time.
+    time_scale: f64,
+    // Standard deviation of the current noise per unit sqrt(model time).
+    noise: f64,
+    // Coupling from the main model into the preparation and back.
+    gain_in: f64,
+    gain_out: f64,
+    // Electrode: volts per model unit, resting offset, RC time constant in
+    // seconds and white measurement noise in volts.
+    scale: f64,
+    offset: f64,
+    tau: f64,
+    electrode_noise: f64,
+    drift: DriftInjection,
+    state: [f64; 3],
+    filtered: f64,
+    recorded: f64,
+    rng: Rng,
+}
+
+impl SimulatedPrep {
+    pub fn new(seed: u64, model_burst_span: f64) -> Self {
+        Self {
+            // Slightly off the default cell so the two do not lock trivially.
+            params: Params {
+                e: 3.1,
+                mu: 0.0055,
+                s: 4.0,
+                vh: 1.0,
+                ..Params::DEFAULT
+            },
+            time_scale: model_burst_span / 1.3,
+            noise: 0.05,
+            gain_in: 0.1,
+            gain_out: 0.1,
+            scale: 0.03,
+            offset: -0.055,
+            tau: 0.002,
+            electrode_noise: 0.0005,
+            drift: DriftInjection::default(),
+            state: [-1.3, -7.3, 3.0],
+            filtered: -1.3,
+            recorded: 0.0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn configure(&mut self, config: &Value, model_burst_span: f64) {
+        let get = |key: &str, default: f64| -> f64 {
+            config.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+        };
+        self.params.e = get("e", self.params.e);
+        self.params.mu = get("mu", self.params.mu);
+        self.params.s = get("s", self.params.s);
+        self.params.vh = get("vh", self.params.vh);
+        if let Some(duration) = config.get("burst_duration").and_then(|v| v.as_f64()) {
+            if duration > 0.0 {
+                self.time_scale = model_burst_span / duration;
+            }
+        }
+        self.noise = get("noise", self.noise).max(0.0);
+        self.gain_in = get("gain_in", self.gain_in);
+        self.gain_out = get("gain_out", self.gain_out);
+        self.scale = get("electrode_scale", self.scale);
+        self.offset = get("electrode_offset", self.offset);
+        self.tau = get("electrode_tau", self.tau).max(0.0);
+        self.electrode_noise = get("electrode_noise", self.electrode_noise).max(0.0);
+        self.drift.rate = get("drift_rate", self.drift.rate);
+        self.drift.amplitude = get("drift_amplitude", self.drift.amplitude);
+        self.drift.period = get("drift_period", self.drift.period);
+    }
+
+    // Advances the preparation by `period` seconds while the main model sits
+    // at `model_x`.
+    pub fn tick(&mut self, period: f64, model_x: f64) {
+        let span = period * self.time_scale;
+        let steps = (span / MAX_STEP).ceil().max(1.0) as usize;
+        let h = span / steps as f64;
+        for _ in 0..steps {
+            // Coupling current, subtracted in dx/dt like i_syn.
+            self.params.input = self.gain_in * (self.state[0] - model_x);
+            let params = self.params;
+            self.state = Integrator::Rk4.step(
+                self.state,
+                h,
+                |v| derivatives(*v, &params),
+                |v| jacobian(*v, &params),
+            );
+            // Euler-Maruyama current noise on the fast variable.
+            self.state[0] += self.noise * h.sqrt() * self.rng.gaussian();
+        }
+        let alpha = if self.tau > 0.0 {
+            1.0 - math::exp(-period / self.tau)
+        } else {
+            1.0
+        };
+        self.filtered += alpha * (self.state[0] - self.filtered);
+        self.drift.tick(period);
+        let noise = self.electrode_noise * self.rng.gaussian();
+        self.recorded = self.scale * self.filtered + self.offset + self.drift.value() + noise;
+    }
+
+    // Membrane potential as the amplifier reported it this tick, in volts.
+    pub fn recorded(&self) -> f64 {
+        self.recorded
+    }
+
+    // Synaptic current for the main model from this tick's recording, mapped
+    // back to model units the way a host-side synapse would.
+    pub fn synaptic_current(&self, model_x: f64) -> f64 {
+        let estimate = if self.scale != 0.0 {
+            (self.recorded - self.offset) / self.scale
+        } else {
+            0.0
+        };
+        self.gain_out * (model_x - estimate)
+    }
+
+    pub fn snapshot(&self) -> PrepSnapshot {
+        PrepSnapshot {
+            rng_position: self.rng.position(),
+            state: self.state,
+            filtered: self.filtered,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &PrepSnapshot) {
+        self.rng.set_position(snapshot.rng_position);
+        self.state = snapshot.state;
+        self.filtered = snapshot.filtered;
+    }
+
+    pub fn x(&self) -> f64 {
+        self.state[0]
+    }
+
+    pub fn stats(&self) -> Value {
+        serde_json::json!({
+            "state": self.state,
+            "time_scale": self.time_scale,
+            "noise": self.noise,
+            "gain_in": self.gain_in,
+            "gain_out": self.gain_out,
+            "electrode": {
+                "scale": self.scale,
+                "offset": self.offset,
+                "tau": self.tau,
+                "noise": self.electrode_noise,
+                "drift": self.drift.value()
+            },
+            "rng_position": self.rng.position()
+        })
+    }
+}
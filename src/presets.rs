@@ -0,0 +1,70 @@
+// Named regimes for the "preset" config key. Each is the native model with
+// s = 4, mu = 0.006 and vh = 1, differing only in e, and starts on its
+// attractor so the regime shows from the first tick:
+//
+//   quiescent             e = 1.0, the stable rest point
+//   square_wave_bursting  e = 2.5, regular three-spike bursts
+//   chaotic               e = 3.25, bursts of irregular length
+//   tonic_spiking         e = 5.0, regular spikes, period ~10.8
+//
+// Keys given alongside "preset" override the preset's values.
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Quiescent,
+    SquareWaveBursting,
+    Chaotic,
+    TonicSpiking,
+}
+
+impl Preset {
+    pub const ALL: [Self; 4] = [Self::Quiescent, Self::SquareWaveBursting, Self::Chaotic, Self::TonicSpiking];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quiescent => "quiescent",
+            Self::SquareWaveBursting => "square_wave_bursting",
+            Self::Chaotic => "chaotic",
+            Self::TonicSpiking => "tonic_spiking",
+        }
+    }
+
+    fn config(self) -> Value {
+        let (e, [x, y, z]) = match self {
+            Self::Quiescent => (1.0, [-1.394_376_308_613_022, -8.721_426_450_106_39, 0.822_494_765_547_912]),
+            Self::SquareWaveBursting => (2.5, [-1.109_488, -5.419_255, 2.113_969]),
+            Self::Chaotic => (3.25, [-0.901_374_755_102_107_2, -3.159_488_296_655_01, 3.247_826_955_037_619]),
+            Self::TonicSpiking => (5.0, [-0.735_042, -2.216_406, 4.719_038]),
+        };
+        json!({
+            "model": "hr3",
+            "freeze_z": false,
+            "e": e,
+            "mu": 0.006,
+            "s": 4.0,
+            "vh": 1.0,
+            "x": x,
+            "y": y,
+            "z": z
+        })
+    }
+}
+
+// The preset's keys overlaid with the rest of `config`, or None when there is
+// no preset to apply.
+pub fn expand(config: &Value) -> Option<Value> {
+    let preset = Preset::parse(config.get("preset")?.as_str()?)?;
+    let mut expanded = preset.config();
+    if let (Some(target), Some(overrides)) = (expanded.as_object_mut(), config.as_object()) {
+        for (key, value) in overrides.iter().filter(|(key, _)| *key != "preset") {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+    Some(expanded)
+}
@@ -0,0 +1,27 @@
+// Soft start for the stimulation-driving outputs: a linear 0 -> 1 gain over
+// `duration` seconds, restarted whenever the model comes (back) online.
+#[derive(Debug, Clone, Default)]
+pub struct SoftStart {
+    pub duration: f64,
+    elapsed: f64,
+}
+
+impl SoftStart {
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    pub fn tick(&mut self, period: f64) {
+        if self.elapsed < self.duration {
+            self.elapsed += period;
+        }
+    }
+
+    pub fn gain(&self) -> f64 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
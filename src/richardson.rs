@@ -0,0 +1,66 @@
+// Richardson extrapolation accuracy mode. Every step is taken once at dt and
+// twice at dt/2; for a method of order p the difference between the two
+// results is (2^p - 1) times the error of the half-step result, which gives
+// both a local error estimate and an extrapolated state one order higher.
+// The estimate is what validates a calibrated dt: if it is not small next
+// to the state, the step is too coarse for the dynamics.
+
+#[derive(Debug, Clone)]
+pub struct Richardson {
+    pub enabled: bool,
+    tick_error: f64,
+    max_error: f64,
+    error_sum: f64,
+    steps: u64,
+}
+
+impl Richardson {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            tick_error: 0.0,
+            max_error: 0.0,
+            error_sum: 0.0,
+            steps: 0,
+        }
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.tick_error = 0.0;
+    }
+
+    // Combines the full step and the two half steps of a method of `order`,
+    // recording the max-norm error estimate.
+    pub fn extrapolate(&mut self, full: [f64; 3], half: [f64; 3], order: u32) -> [f64; 3] {
+        let scale = 1.0 / (2f64.powi(order as i32) - 1.0);
+        let mut out = half;
+        let mut error: f64 = 0.0;
+        for ((value, h), f) in out.iter_mut().zip(half).zip(full) {
+            let correction = (h - f) * scale;
+            *value += correction;
+            error = error.max(correction.abs());
+        }
+        self.tick_error = self.tick_error.max(error);
+        self.max_error = self.max_error.max(error);
+        self.error_sum += error;
+        self.steps += 1;
+        out
+    }
+
+    // Largest estimated local error of the steps in the current tick.
+    pub fn tick_error(&self) -> f64 {
+        self.tick_error
+    }
+
+    pub fn max_error(&self) -> f64 {
+        self.max_error
+    }
+
+    pub fn mean_error(&self) -> f64 {
+        if self.steps == 0 {
+            0.0
+        } else {
+            self.error_sum / self.steps as f64
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// Runge-Kutta stepping shared by every model variant, generic over the
+// state dimension N and the number of stages S, plus the selectable solvers
+// built on it.
+
+use crate::field::{Float, Scalar};
+use crate::implicit;
+
+// Solver selected with the "integrator" config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    Euler,
+    Heun,
+    Rk4,
+    Rk6,
+    // Adaptive Dormand-Prince 5(4), see adaptive.rs.
+    Rk45,
+    // Backward Euler with Newton iterations, see implicit.rs.
+    BackwardEuler,
+    // Adams-Bashforth, see multistep.rs.
+    Ab3,
+    Ab4,
+}
+
+impl Integrator {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "euler" => Some(Self::Euler),
+            "heun" => Some(Self::Heun),
+            "rk4" => Some(Self::Rk4),
+            "rk6" => Some(Self::Rk6),
+            "rk45" | "dopri5" => Some(Self::Rk45),
+            "backward_euler" | "implicit" => Some(Self::BackwardEuler),
+            "ab3" => Some(Self::Ab3),
+            "ab4" => Some(Self::Ab4),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Euler => "euler",
+            Self::Heun => "heun",
+            Self::Rk4 => "rk4",
+            Self::Rk6 => "rk6",
+            Self::Rk45 => "rk45",
+            Self::BackwardEuler => "backward_euler",
+            Self::Ab3 => "ab3",
+            Self::Ab4 => "ab4",
+        }
+    }
+
+    // Field evaluations per step, for cost estimates.
+    pub fn stages(self) -> u64 {
+        match self {
+            Self::Euler => 1,
+            Self::Heun => 2,
+            Self::Rk4 => 4,
+            Self::Rk6 => 6,
+            Self::Rk45 => 7,
+            // Typically two Newton iterations, each a field evaluation, a
+            // Jacobian and a 3x3 solve.
+            Self::BackwardEuler => 6,
+            Self::Ab3 | Self::Ab4 => 1,
+        }
+    }
+
+    // Length of the stability interval on the negative real axis, in units
+    // of dt times the eigenvalue magnitude; approximate for the higher
+    // order tableaus.
+    pub fn stability_limit(self) -> f64 {
+        match self {
+            Self::Euler | Self::Heun => 2.0,
+            Self::Rk4 => 2.785,
+            Self::Rk6 => 3.0,
+            Self::Rk45 => 3.3,
+            Self::BackwardEuler => f64::INFINITY,
+            Self::Ab3 => 6.0 / 11.0,
+            Self::Ab4 => 0.3,
+        }
+    }
+
+    // Order of accuracy of a single `step`. The six-stage tableau inherited
+    // from RTXI is fifth order despite its name; the multistep methods step
+    // here with their RK4 starter.
+    pub fn order(self) -> u32 {
+        match self {
+            Self::Euler | Self::BackwardEuler => 1,
+            Self::Heun => 2,
+            Self::Rk4 | Self::Ab3 | Self::Ab4 => 4,
+            Self::Rk6 | Self::Rk45 => 5,
+        }
+    }
+
+    // History length of the multistep methods.
+    pub fn multistep_order(self) -> Option<usize> {
+        match self {
+            Self::Ab3 => Some(3),
+            Self::Ab4 => Some(4),
+            _ => None,
+        }
+    }
+
+    // One fixed step of size `dt`. Rk45 uses its fifth-order weights without
+    // error control here; the adaptive loop drives it through
+    // integrate_embedded instead. The multistep methods have no history
+    // here and take their RK4 starter step. Only the implicit method uses
+    // `jacobian`.
+    pub fn step<const N: usize, T, F, J>(self, y: [T; N], dt: T, f: F, jacobian: J) -> [T; N]
+    where
+        T: Float,
+        F: FnMut(&[T; N]) -> [T; N],
+        J: FnMut(&[T; N]) -> [[T; N]; N],
+    {
+        let increment = self.increment(y, dt, f, jacobian);
+        let mut out = y;
+        for j in 0..N {
+            out[j] = out[j] + increment[j];
+        }
+        out
+    }
+
+    // The change `step` applies to `y`, for callers doing their own
+    // (e.g. compensated) accumulation.
+    pub fn increment<const N: usize, T, F, J>(self, y: [T; N], dt: T, f: F, jacobian: J) -> [T; N]
+    where
+        T: Float,
+        F: FnMut(&[T; N]) -> [T; N],
+        J: FnMut(&[T; N]) -> [[T; N]; N],
+    {
+        match self {
+            Self::Euler => increment(&EULER, y, dt, f),
+            Self::Heun => increment(&HEUN, y, dt, f),
+            Self::Rk4 | Self::Ab3 | Self::Ab4 => increment(&RK4, y, dt, f),
+            Self::Rk6 => increment(&RK6, y, dt, f),
+            Self::Rk45 => increment(&DOPRI5, y, dt, f),
+            Self::BackwardEuler => {
+                let mut next = implicit::backward_euler(y, dt, f, jacobian);
+                for j in 0..N {
+                    next[j] = next[j] - y[j];
+                }
+                next
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tableau<const S: usize> {
+    // Strictly lower-triangular stage coefficients.
+    pub a: [[f64; S]; S],
+    pub b: [f64; S],
+}
+
+pub const EULER: Tableau<1> = Tableau {
+    a: [[0.0]],
+    b: [1.0],
+};
+
+// Explicit trapezoidal rule.
+pub const HEUN: Tableau<2> = Tableau {
+    a: [[0.0, 0.0], [1.0, 0.0]],
+    b: [0.5, 0.5],
+};
+
+// Classical fourth-order Runge-Kutta.
+pub const RK4: Tableau<4> = Tableau {
+    a: [
+        [0.0, 0.0, 0.0, 0.0],
+        [0.5, 0.0, 0.0, 0.0],
+        [0.0, 0.5, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+    ],
+    b: [1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0],
+};
+
+// Six-stage scheme inherited from the original RTXI Hindmarsh-Rose module.
+pub const RK6: Tableau<6> = Tableau {
+    a: [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [0.2, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [0.075, 0.225, 0.0, 0.0, 0.0, 0.0],
+        [0.3, -0.9, 1.2, 0.0, 0.0, 0.0],
+        [0.075, 0.675, -0.6, 0.75, 0.0, 0.0],
+        [
+            0.660493827160493,
+            2.5,
+            -5.185185185185185,
+            3.888888888888889,
+            -0.864197530864197,
+            0.0,
+        ],
+    ],
+    b: [
+        0.098765432098765,
+        0.0,
+        0.396825396825396,
+        0.231481481481481,
+        0.308641975308641,
+        -0.035714285714285,
+    ],
+};
+
+// Dormand-Prince 5(4). `b` holds the fifth-order weights and `e` the
+// difference to the embedded fourth-order ones, so that sum(e_i * k_i)
+// estimates the local error of the step.
+pub const DOPRI5: Tableau<7> = Tableau {
+    a: [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0, 0.0],
+        [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0, 0.0],
+        [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0, 0.0],
+        [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0],
+    ],
+    b: [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0],
+};
+
+pub const DOPRI5_ERROR: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+// Increment of one step of size `dt` from `y` for the autonomous system
+// y' = f(y). Zero coefficients are skipped so sparse tableaus cost nothing
+// extra.
+#[inline(always)]
+pub fn increment<const N: usize, const S: usize, T, F>(tableau: &Tableau<S>, y: [T; N], dt: T, f: F) -> [T; N]
+where
+    T: Scalar,
+    F: FnMut(&[T; N]) -> [T; N],
+{
+    let k = stages(tableau, y, dt, f);
+    combine(&tableau.b, [T::lit(0.0); N], &k)
+}
+
+// Advances `y` by one step, additionally returning the local error
+// estimate sum(error_i * k_i) of an embedded pair.
+pub fn integrate_embedded<const N: usize, const S: usize, F>(
+    tableau: &Tableau<S>,
+    error: &[f64; S],
+    y: [f64; N],
+    dt: f64,
+    f: F,
+) -> ([f64; N], [f64; N])
+where
+    F: FnMut(&[f64; N]) -> [f64; N],
+{
+    let k = stages(tableau, y, dt, f);
+    (combine(&tableau.b, y, &k), combine(error, [0.0; N], &k))
+}
+
+// Stage increments k_i = dt * f(y + sum_j a_ij k_j).
+#[inline(always)]
+fn stages<const N: usize, const S: usize, T, F>(tableau: &Tableau<S>, y: [T; N], dt: T, mut f: F) -> [[T; N]; S]
+where
+    T: Scalar,
+    F: FnMut(&[T; N]) -> [T; N],
+{
+    let mut k = [[T::lit(0.0); N]; S];
+    for stage in 0..S {
+        let mut aux = y;
+        for (m, &a) in tableau.a[stage][..stage].iter().enumerate() {
+            if a != 0.0 {
+                for j in 0..N {
+                    aux[j] = aux[j] + k[m][j] * T::lit(a);
+                }
+            }
+        }
+        let r = f(&aux);
+        for j in 0..N {
+            k[stage][j] = dt * r[j];
+        }
+    }
+    k
+}
+
+#[inline(always)]
+fn combine<const N: usize, const S: usize, T: Scalar>(weights: &[f64; S], y: [T; N], k: &[[T; N]; S]) -> [T; N] {
+    let mut out = y;
+    for j in 0..N {
+        let mut increment = T::lit(0.0);
+        for (stage, &w) in weights.iter().enumerate() {
+            if w != 0.0 {
+                increment = increment + k[stage][j] * T::lit(w);
+            }
+        }
+        out[j] = out[j] + increment;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Harmonic oscillator q' = p, p' = -q from (1, 0), whose solution is
+    // (cos t, -sin t).
+    fn oscillator_error(integrator: Integrator, steps: usize) -> f64 {
+        let dt = 1.0 / steps as f64;
+        let mut y = [1.0, 0.0];
+        for _ in 0..steps {
+            y = integrator.step(y, dt, |v| [v[1], -v[0]], |_| [[0.0, 1.0], [-1.0, 0.0]]);
+        }
+        (y[0] - 1f64.cos()).hypot(y[1] + 1f64.sin())
+    }
+
+    #[test]
+    fn converges_at_its_order_on_the_harmonic_oscillator() {
+        for integrator in [
+            Integrator::Euler,
+            Integrator::Heun,
+            Integrator::Rk4,
+            Integrator::Rk6,
+            Integrator::Rk45,
+            Integrator::BackwardEuler,
+        ] {
+            let observed = (oscillator_error(integrator, 20) / oscillator_error(integrator, 40)).log2();
+            let expected = f64::from(integrator.order());
+            assert!(
+                (observed - expected).abs() < 0.25,
+                "{} converges at order {observed:.2}, expected {expected}",
+                integrator.name()
+            );
+        }
+    }
+
+    #[test]
+    fn embedded_error_estimate_tracks_the_local_error() {
+        for dt in [0.2, 0.1] {
+            let (y, error) = integrate_embedded(&DOPRI5, &DOPRI5_ERROR, [1.0, 0.0], dt, |v| [v[1], -v[0]]);
+            let actual = (y[0] - dt.cos()).hypot(y[1] + dt.sin());
+            let estimate = error[0].hypot(error[1]);
+            // The estimate is the fourth-order method's error, so it bounds
+            // the fifth-order solution's.
+            assert!(estimate > actual, "dt {dt}: estimate {estimate:e} below actual {actual:e}");
+            assert!(estimate < 1e-3 * dt, "dt {dt}: estimate {estimate:e} too large");
+        }
+    }
+}
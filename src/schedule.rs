@@ -0,0 +1,98 @@
+// Config changes deferred to a burst boundary so that experimental condition
+// switches never land in the middle of the cycle being analyzed. A burst is
+// considered over once `burst_gap` model time has passed without a spike.
+// Boundaries are detected per substep but the overrides are applied between
+// ticks, never inside the integration loop.
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Onset,
+    Offset,
+}
+
+impl Anchor {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "onset" => Some(Self::Onset),
+            "offset" => Some(Self::Offset),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BurstLockedSwitch {
+    pub anchor: Anchor,
+    pending: Option<Map<String, Value>>,
+    ready: bool,
+    in_burst: bool,
+    last_spike: f64,
+    applied: u64,
+}
+
+impl BurstLockedSwitch {
+    pub fn new() -> Self {
+        Self {
+            anchor: Anchor::Offset,
+            pending: None,
+            ready: false,
+            in_burst: false,
+            last_spike: 0.0,
+            applied: 0,
+        }
+    }
+
+    // Later schedules override earlier ones key by key.
+    pub fn schedule(&mut self, overrides: &Map<String, Value>) {
+        let pending = self.pending.get_or_insert_with(Map::new);
+        for (key, value) in overrides.iter() {
+            pending.insert(key.clone(), value.clone());
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending = None;
+        self.ready = false;
+    }
+
+    pub fn pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn applied(&self) -> u64 {
+        self.applied
+    }
+
+    // Called on every detected spike.
+    pub fn on_spike(&mut self, t: f64, onset: bool) {
+        self.in_burst = true;
+        self.last_spike = t;
+        if onset && self.anchor == Anchor::Onset {
+            self.ready |= self.pending.is_some();
+        }
+    }
+
+    // Called once per substep to detect the end of the current burst; the
+    // gap is measured either way so a reversed run ends bursts too.
+    pub fn poll(&mut self, t: f64, burst_gap: f64) {
+        if self.in_burst && (t - self.last_spike).abs() >= burst_gap {
+            self.in_burst = false;
+            if self.anchor == Anchor::Offset {
+                self.ready |= self.pending.is_some();
+            }
+        }
+    }
+
+    // Returns the overrides once their boundary has been reached.
+    pub fn take_ready(&mut self) -> Option<Value> {
+        if !self.ready {
+            return None;
+        }
+        self.ready = false;
+        let pending = self.pending.take()?;
+        self.applied += 1;
+        Some(Value::Object(pending))
+    }
+}
@@ -0,0 +1,372 @@
+// Vectorised Runge-Kutta stepping over wide::f64x4, compiled in with the
+// `simd` feature. The generic stage loops in rk.rs run unchanged on vectors;
+// only the layout differs:
+//
+//   packed  one cell's [x, y, z, 0] in a single vector, so every stage
+//           combination y + sum(a_ij k_j) is one vector operation.
+//   lanes   four cells per vector and one vector per variable, so the field
+//           itself is evaluated for four cells at once.
+//
+// benches/simd.rs compares both with the scalar path through `CellGroup`.
+// Only the lanes layout pays off (about 3x per cell for RK4); packing costs
+// more than the three additions it saves, so single instances keep stepping
+// scalar while population.rs advances its neurons with `advance_cells`.
+// Backward Euler has no vector form.
+//
+// advance_cells is compiled twice and picked at runtime, see Kernel, so one
+// prebuilt library steps populations with 256-bit AVX2 vectors on the lab
+// machines that have them and still loads on older x86_64 CPUs. It works on
+// plain lane arrays rather than f64x4, whose instruction set is fixed when
+// the crate is compiled. Neither build contracts into FMA, so both round
+// identically. On aarch64 NEON is part of the baseline target and the
+// generic build already uses it.
+
+use crate::field::{derivatives, Params};
+use crate::half_center::MAX_STEP;
+use crate::rk::{self, Integrator, Tableau};
+use std::ops::{Add, Mul, Neg, Sub};
+use wide::f64x4;
+
+pub const LANES: usize = 4;
+
+// Defaults of a fresh instance.
+const INITIAL_STATE: [f64; 3] = [-0.9013747551021072, -3.15948829665501, 3.247826955037619];
+
+// Dispatches to the tableau `Integrator::increment` uses, or None for the
+// implicit method.
+fn increment<const N: usize, F>(integrator: Integrator, y: [f64x4; N], dt: f64, f: F) -> Option<[f64x4; N]>
+where
+    F: FnMut(&[f64x4; N]) -> [f64x4; N],
+{
+    fn run<const N: usize, const S: usize, F>(tableau: &Tableau<S>, y: [f64x4; N], dt: f64, f: F) -> [f64x4; N]
+    where
+        F: FnMut(&[f64x4; N]) -> [f64x4; N],
+    {
+        rk::increment(tableau, y, f64x4::splat(dt), f)
+    }
+    match integrator {
+        Integrator::Euler => Some(run(&rk::EULER, y, dt, f)),
+        Integrator::Heun => Some(run(&rk::HEUN, y, dt, f)),
+        Integrator::Rk4 | Integrator::Ab3 | Integrator::Ab4 => Some(run(&rk::RK4, y, dt, f)),
+        Integrator::Rk6 => Some(run(&rk::RK6, y, dt, f)),
+        Integrator::Rk45 => Some(run(&rk::DOPRI5, y, dt, f)),
+        Integrator::BackwardEuler => None,
+    }
+}
+
+fn pack(vars: [f64; 3]) -> f64x4 {
+    f64x4::from([vars[0], vars[1], vars[2], 0.0])
+}
+
+fn unpack(v: f64x4) -> [f64; 3] {
+    let [x, y, z, _] = v.to_array();
+    [x, y, z]
+}
+
+// One explicit step of a single cell in the packed layout.
+fn packed_step(integrator: Integrator, vars: [f64; 3], dt: f64, p: &Params<f64>) -> Option<[f64; 3]> {
+    let y = pack(vars);
+    let [inc] = increment(integrator, [y], dt, |v| [pack(derivatives(unpack(v[0]), p))])?;
+    Some(unpack(y + inc))
+}
+
+fn splat_params(p: &Params<f64>) -> Params<f64x4> {
+    p.map(f64x4::splat)
+}
+
+fn lane(v: &f64x4, i: usize) -> f64 {
+    v.to_array()[i]
+}
+
+fn set_lane(v: &mut f64x4, i: usize, value: f64) {
+    let mut lanes = v.to_array();
+    lanes[i] = value;
+    *v = f64x4::from(lanes);
+}
+
+// Four lanes as a plain array; LLVM vectorises the element-wise operations
+// for whatever instruction set the enclosing function enables.
+#[derive(Debug, Clone, Copy)]
+struct Lanes([f64; LANES]);
+
+macro_rules! lanewise {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for Lanes {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $method(self, rhs: Self) -> Self {
+                Self(std::array::from_fn(|i| self.0[i] $op rhs.0[i]))
+            }
+        }
+    };
+}
+
+lanewise!(Add, add, +);
+lanewise!(Sub, sub, -);
+lanewise!(Mul, mul, *);
+
+impl Neg for Lanes {
+    type Output = Self;
+
+    #[inline(always)]
+    fn neg(self) -> Self {
+        Self(self.0.map(|v| -v))
+    }
+}
+
+impl crate::field::Scalar for Lanes {
+    #[inline(always)]
+    fn lit(value: f64) -> Self {
+        Self([value; LANES])
+    }
+}
+
+// Instruction set advance_cells runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Generic,
+    Avx2,
+}
+
+impl Kernel {
+    // The widest kernel this CPU supports; std caches the detection.
+    pub fn detect() -> Self {
+        if Self::Avx2.available() {
+            Self::Avx2
+        } else {
+            Self::Generic
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Generic if cfg!(target_arch = "aarch64") => "neon",
+            Self::Generic => "generic",
+            Self::Avx2 => "avx2",
+        }
+    }
+
+    pub fn available(self) -> bool {
+        match self {
+            Self::Generic => true,
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2 => std::arch::is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Self::Avx2 => false,
+        }
+    }
+}
+
+// Independent cells sharing e, mu, s and vh, each with its own input,
+// stored four to a vector. The padding lanes of the last vector are
+// integrated along with the rest and never read.
+#[derive(Debug, Clone)]
+pub struct CellGroup {
+    cells: usize,
+    state: Vec<[f64x4; 3]>,
+    input: Vec<f64x4>,
+    params: Params<f64>,
+    integrator: Integrator,
+}
+
+impl CellGroup {
+    pub fn new(cells: usize) -> Self {
+        let chunks = cells.div_ceil(LANES);
+        Self {
+            cells,
+            state: vec![INITIAL_STATE.map(f64x4::splat); chunks],
+            input: vec![f64x4::splat(0.0); chunks],
+            params: Params::DEFAULT,
+            integrator: Integrator::Rk4,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells == 0
+    }
+
+    // Accepts the explicit "integrator" names; false for anything else.
+    pub fn set_integrator(&mut self, name: &str) -> bool {
+        match Integrator::parse(name) {
+            Some(Integrator::BackwardEuler) | None => false,
+            Some(integrator) => {
+                self.integrator = integrator;
+                true
+            }
+        }
+    }
+
+    pub fn set_e(&mut self, e: f64) {
+        self.params.e = e;
+    }
+
+    pub fn set_input(&mut self, cell: usize, value: f64) {
+        set_lane(&mut self.input[cell / LANES], cell % LANES, value);
+    }
+
+    pub fn set_state(&mut self, cell: usize, vars: [f64; 3]) {
+        let chunk = &mut self.state[cell / LANES];
+        for (v, value) in chunk.iter_mut().zip(vars) {
+            set_lane(v, cell % LANES, value);
+        }
+    }
+
+    pub fn state(&self, cell: usize) -> [f64; 3] {
+        self.state[cell / LANES].each_ref().map(|v| lane(v, cell % LANES))
+    }
+
+    // Four cells per field evaluation.
+    pub fn step(&mut self, dt: f64) {
+        let shared = splat_params(&self.params);
+        for (vars, &input) in self.state.iter_mut().zip(&self.input) {
+            let params = Params { input, ..shared };
+            if let Some(inc) = increment(self.integrator, *vars, dt, |v| derivatives(*v, &params)) {
+                for (v, d) in vars.iter_mut().zip(inc) {
+                    *v = *v + d;
+                }
+            }
+        }
+    }
+
+    // One cell at a time in the packed layout.
+    pub fn step_packed(&mut self, dt: f64) {
+        for cell in 0..self.cells {
+            let params = Params {
+                input: lane(&self.input[cell / LANES], cell % LANES),
+                ..self.params
+            };
+            if let Some(next) = packed_step(self.integrator, self.state(cell), dt, &params) {
+                self.set_state(cell, next);
+            }
+        }
+    }
+
+    // Scalar reference: one cell at a time through Integrator::step, as an
+    // instance without the feature steps.
+    pub fn step_scalar(&mut self, dt: f64) {
+        for cell in 0..self.cells {
+            let params = Params {
+                input: lane(&self.input[cell / LANES], cell % LANES),
+                ..self.params
+            };
+            let next = self.integrator.step(
+                self.state(cell),
+                dt,
+                |v| derivatives(*v, &params),
+                |v| crate::field::jacobian(*v, &params),
+            );
+            self.set_state(cell, next);
+        }
+    }
+}
+
+// Advances cells that share `shared` except for their (e, input), given per
+// cell by `lane_params`, over the signed model time `span`. Four cells share
+// each field evaluation; the steps are those of half_center::advance_cell,
+// and every lane rounds as that scalar path does.
+pub fn advance_cells(
+    cells: &mut [[f64; 3]],
+    shared: &Params<f64>,
+    lane_params: impl Fn(usize) -> (f64, f64),
+    span: f64,
+) {
+    advance_cells_on(Kernel::detect(), cells, shared, &lane_params, span);
+}
+
+fn advance_cells_on(
+    kernel: Kernel,
+    cells: &mut [[f64; 3]],
+    shared: &Params<f64>,
+    lane_params: &impl Fn(usize) -> (f64, f64),
+    span: f64,
+) {
+    match kernel {
+        // Only reached once `available` confirmed the CPU has AVX2.
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx2 => unsafe { advance_cells_avx2(cells, shared, lane_params, span) },
+        _ => advance_lanes(cells, shared, lane_params, span),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn advance_cells_avx2(
+    cells: &mut [[f64; 3]],
+    shared: &Params<f64>,
+    lane_params: &impl Fn(usize) -> (f64, f64),
+    span: f64,
+) {
+    advance_lanes(cells, shared, lane_params, span);
+}
+
+// Inlined into each kernel so it is compiled for that kernel's features.
+#[inline(always)]
+fn advance_lanes(
+    cells: &mut [[f64; 3]],
+    shared: &Params<f64>,
+    lane_params: &impl Fn(usize) -> (f64, f64),
+    span: f64,
+) {
+    if span == 0.0 {
+        return;
+    }
+    let steps = (span.abs() / MAX_STEP).ceil().max(1.0) as usize;
+    let h = Lanes([span / steps as f64; LANES]);
+    let splat = shared.map(|value| Lanes([value; LANES]));
+    for (chunk, group) in cells.chunks_mut(LANES).enumerate() {
+        let mut e = [shared.e; LANES];
+        let mut input = [0.0; LANES];
+        let mut lanes = [[0.0; LANES]; 3];
+        for (i, cell) in group.iter().enumerate() {
+            (e[i], input[i]) = lane_params(chunk * LANES + i);
+            for (var, value) in lanes.iter_mut().zip(cell) {
+                var[i] = *value;
+            }
+        }
+        let params = Params { e: Lanes(e), input: Lanes(input), ..splat };
+        let mut state = lanes.map(Lanes);
+        for _ in 0..steps {
+            let inc = rk::increment(&rk::RK4, state, h, #[inline(always)] |v| derivatives(*v, &params));
+            for (v, d) in state.iter_mut().zip(inc) {
+                *v = *v + d;
+            }
+        }
+        for (i, cell) in group.iter_mut().enumerate() {
+            *cell = state.map(|v| v.0[i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::half_center::advance_cell;
+
+    // Five cells fill one vector and pad the next; under every kernel the
+    // CPU supports each must land exactly where the scalar path puts it,
+    // forward and in reverse time.
+    #[test]
+    fn advance_cells_matches_the_scalar_path() {
+        let shared = Params { mu: 0.005, ..Params::DEFAULT };
+        let lane_params = |k: usize| (3.0 + 0.1 * k as f64, 0.05 * k as f64);
+        let start: Vec<[f64; 3]> = (0..5).map(|k| [-1.2 + 0.3 * k as f64, -6.0, 3.0 + 0.05 * k as f64]).collect();
+        let kernels = [Kernel::Generic, Kernel::Avx2].into_iter().filter(|kernel| kernel.available());
+        for (kernel, span) in kernels.flat_map(|kernel| [0.1, 0.37, -0.2].map(|span| (kernel, span))) {
+            let mut cells = start.clone();
+            for _ in 0..200 {
+                advance_cells_on(kernel, &mut cells, &shared, &lane_params, span);
+            }
+            for (k, (cell, initial)) in cells.iter().zip(&start).enumerate() {
+                let (e, input) = lane_params(k);
+                let params = Params { e, input, ..shared };
+                let expected = (0..200).fold(*initial, |state, _| advance_cell(state, &params, span));
+                assert_eq!(cell.map(f64::to_bits), expected.map(f64::to_bits), "{}: cell {k}, span {span}", kernel.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+// Rolling short-time power spectrum of x, sampled once per tick. Every `hop`
+// samples the last `window` samples are Hann-windowed and transformed; the
+// resulting one-sided power spectrum (window / 2 + 1 bins) is stored in a
+// ring of `capacity` rows that hosts read back in one bulk copy.
+
+use crate::math;
+use std::f64::consts::TAU;
+
+pub const MAX_WINDOW: usize = 4096;
+
+#[derive(Debug, Clone, Default)]
+pub struct Spectrogram {
+    window: usize,
+    hop: usize,
+    overlap: f64,
+    capacity: usize,
+    history: Vec<f64>,
+    head: usize,
+    since_frame: usize,
+    filled: usize,
+    taper: Vec<f64>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    rows: Vec<f64>,
+    next_row: usize,
+    stored: usize,
+}
+
+impl Spectrogram {
+    pub fn new() -> Self {
+        Self {
+            overlap: 0.5,
+            capacity: 64,
+            ..Self::default()
+        }
+    }
+
+    // window is rounded up to a power of two; overlap is the fraction of a
+    // window shared by consecutive frames. A zero window disables the buffer.
+    pub fn configure(&mut self, window: usize, overlap: f64, capacity: usize) {
+        let window = if window == 0 {
+            0
+        } else {
+            window.next_power_of_two().clamp(8, MAX_WINDOW)
+        };
+        let overlap = overlap.clamp(0.0, 0.95);
+        let hop = ((window as f64 * (1.0 - overlap)).round() as usize).max(1);
+        let capacity = capacity.max(1);
+        if (window, overlap, capacity) == (self.window, self.overlap, self.capacity) {
+            return;
+        }
+        *self = Self {
+            window,
+            hop,
+            overlap,
+            capacity,
+            history: vec![0.0; window],
+            taper: (0..window)
+                .map(|i| 0.5 - 0.5 * math::cos(TAU * i as f64 / window as f64))
+                .collect(),
+            re: vec![0.0; window],
+            im: vec![0.0; window],
+            rows: vec![0.0; capacity * (window / 2 + 1)],
+            ..Self::default()
+        };
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.window > 0
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    pub fn overlap(&self) -> f64 {
+        self.overlap
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn bins(&self) -> usize {
+        if self.enabled() {
+            self.window / 2 + 1
+        } else {
+            0
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.stored
+    }
+
+    pub fn push(&mut self, x: f64) {
+        if !self.enabled() {
+            return;
+        }
+        self.history[self.head] = x;
+        self.head = (self.head + 1) % self.window;
+        self.filled = (self.filled + 1).min(self.window);
+        self.since_frame += 1;
+        if self.filled == self.window && self.since_frame >= self.hop {
+            self.since_frame = 0;
+            self.transform();
+        }
+    }
+
+    // Copies the stored frames, oldest first, into `out` and returns the
+    // number of values written.
+    pub fn copy_to(&self, out: &mut [f64]) -> usize {
+        let bins = self.bins();
+        let first = (self.next_row + self.capacity - self.stored) % self.capacity.max(1);
+        for frame in 0..self.stored {
+            let row = (first + frame) % self.capacity;
+            out[frame * bins..(frame + 1) * bins].copy_from_slice(&self.rows[row * bins..(row + 1) * bins]);
+        }
+        self.stored * bins
+    }
+
+    fn transform(&mut self) {
+        let n = self.window;
+        // Mean removal keeps the DC bin from swamping the slow bursting band.
+        let mean = self.history.iter().sum::<f64>() / n as f64;
+        for i in 0..n {
+            let sample = self.history[(self.head + i) % n];
+            self.re[i] = (sample - mean) * self.taper[i];
+            self.im[i] = 0.0;
+        }
+        fft(&mut self.re, &mut self.im);
+
+        let bins = self.bins();
+        let norm = 1.0 / (n as f64 * n as f64);
+        let row = &mut self.rows[self.next_row * bins..(self.next_row + 1) * bins];
+        for (k, power) in row.iter_mut().enumerate() {
+            *power = (self.re[k] * self.re[k] + self.im[k] * self.im[k]) * norm;
+        }
+        self.next_row = (self.next_row + 1) % self.capacity;
+        self.stored = (self.stored + 1).min(self.capacity);
+    }
+}
+
+// In-place iterative radix-2 FFT; the length must be a power of two.
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -TAU / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = math::sin_cos(angle * k as f64);
+                let a = start + k;
+                let b = a + len / 2;
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,145 @@
+// Event-driven synaptic input. Every nonzero value written to input spike_in
+// is one presynaptic spike, and the current is the spike train convolved
+// with a kernel of unit peak scaled by spike_weight:
+//
+//   alpha  k(t) = (t / tau) exp(1 - t / tau)
+//   exp2   k(t) = (exp(-t / tau_decay) - exp(-t / tau_rise)) / peak
+//
+// with times in host seconds. Both kernels are two linear states advanced
+// exactly, so there is no per-spike bookkeeping. Spikes arriving during a
+// tick start at its beginning, and the current is sampled mid-tick and held
+// for the tick. It enters dx/dt like i_syn, so negative weights excite.
+
+use crate::math;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Alpha,
+    Exp2,
+}
+
+impl Kernel {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "alpha" => Some(Self::Alpha),
+            "exp2" | "double_exponential" => Some(Self::Exp2),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alpha => "alpha",
+            Self::Exp2 => "exp2",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpikeInput {
+    pub kernel: Kernel,
+    pub weight: f64,
+    pub tau: f64,
+    pub tau_rise: f64,
+    pub tau_decay: f64,
+    pending: u32,
+    states: [f64; 2],
+    current: f64,
+    received: u64,
+}
+
+impl Default for SpikeInput {
+    fn default() -> Self {
+        Self {
+            kernel: Kernel::Alpha,
+            weight: 0.0,
+            tau: 0.005,
+            tau_rise: 0.001,
+            tau_decay: 0.01,
+            pending: 0,
+            states: [0.0; 2],
+            current: 0.0,
+            received: 0,
+        }
+    }
+}
+
+impl SpikeInput {
+    pub fn enabled(&self) -> bool {
+        self.weight != 0.0
+    }
+
+    pub fn receive(&mut self, value: f64) {
+        if value != 0.0 {
+            self.pending += 1;
+        }
+    }
+
+    pub fn tick(&mut self, period: f64) {
+        let spikes = f64::from(std::mem::take(&mut self.pending));
+        self.received += spikes as u64;
+        match self.kernel {
+            Kernel::Alpha => self.states[0] += spikes,
+            Kernel::Exp2 => {
+                self.states[0] += spikes;
+                self.states[1] += spikes;
+            }
+        }
+        self.advance(period / 2.0);
+        self.current = self.weight * self.value();
+        self.advance(period / 2.0);
+    }
+
+    // Kernel value of the current states, normalized to unit peak.
+    fn value(&self) -> f64 {
+        match self.kernel {
+            Kernel::Alpha => std::f64::consts::E * self.states[1],
+            Kernel::Exp2 => (self.states[0] - self.states[1]) / self.exp2_peak(),
+        }
+    }
+
+    // Alpha: states[0] decays with tau and feeds states[1], giving
+    // (t / tau) exp(-t / tau) per spike. Exp2: states[0] and states[1] decay
+    // with tau_decay and tau_rise.
+    fn advance(&mut self, h: f64) {
+        match self.kernel {
+            Kernel::Alpha => {
+                if self.tau <= 0.0 {
+                    self.states = [0.0; 2];
+                    return;
+                }
+                let decay = math::exp(-h / self.tau);
+                self.states[1] = (self.states[1] + self.states[0] * h / self.tau) * decay;
+                self.states[0] *= decay;
+            }
+            Kernel::Exp2 => {
+                let decay = |tau: f64| if tau > 0.0 { math::exp(-h / tau) } else { 0.0 };
+                self.states[0] *= decay(self.tau_decay);
+                self.states[1] *= decay(self.tau_rise);
+            }
+        }
+    }
+
+    fn exp2_peak(&self) -> f64 {
+        let (rise, decay) = (self.tau_rise, self.tau_decay);
+        if rise <= 0.0 || decay <= 0.0 || rise == decay {
+            return 1.0;
+        }
+        let t_peak = rise * decay / (decay - rise) * math::ln(decay / rise);
+        math::exp(-t_peak / decay) - math::exp(-t_peak / rise)
+    }
+
+    pub fn current(&self) -> f64 {
+        self.current
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    pub fn reset(&mut self) {
+        self.pending = 0;
+        self.states = [0.0; 2];
+        self.current = 0.0;
+    }
+}
@@ -0,0 +1,247 @@
+// Compact little-endian checkpoint layout:
+//   magic "HRSB" | u16 version | u16 reserved | f64 x | f64 y | f64 z | f64 t
+//   v2: | u64 steps
+//   v3: | u64 rng_position (jitter stream)
+//   v4: | u64 noise stream position
+//   v5: | f64 u (second slow variable of the 4D model, 0 otherwise)
+//   v6: | u64 n | n x (f64 x | f64 y | f64 z) for the cells integrated
+//       alongside the instance's own: the half-center's second neuron, then
+//       population neurons 1..N-1
+//   v7: | u64 param drift stream position | f64 e offset | f64 mu offset
+//       | u64 n | n x (u64 stream position | f64 x | f64 y | f64 z
+//       | f64 filtered) for the simulated preparation, n = 0 or 1
+//       | f64 step size | f64 previous error | u64 after rejection, the
+//       error controller's carried state
+// Readers accept any version up to VERSION so older blobs stay loadable.
+
+const MAGIC: [u8; 4] = *b"HRSB";
+pub const VERSION: u16 = 7;
+const HEADER_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub t: f64,
+    pub steps: u64,
+    // Indexed like rng::Stream::ALL.
+    pub rng_positions: [u64; 2],
+    pub u: f64,
+    pub cells: Vec<[f64; 3]>,
+    pub param_drift_position: u64,
+    // Offsets of e and mu from their configured values.
+    pub drift_offsets: [f64; 2],
+    pub prep: Option<PrepSnapshot>,
+    pub controller: ControllerSnapshot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ControllerSnapshot {
+    pub step_size: f64,
+    pub previous_error: f64,
+    pub after_rejection: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrepSnapshot {
+    pub rng_position: u64,
+    pub state: [f64; 3],
+    // Electrode voltage after the RC filter, in model units.
+    pub filtered: f64,
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, data: &[u8]) {
+        self.out[self.pos..self.pos + data.len()].copy_from_slice(data);
+        self.pos += data.len();
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let chunk = self.data.get(self.pos..self.pos + N)?;
+        self.pos += N;
+        chunk.try_into().ok()
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.take().map(f64::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+}
+
+impl Snapshot {
+    pub fn size(&self) -> usize {
+        HEADER_LEN + (16 + 3 * self.cells.len() + 5 * usize::from(self.prep.is_some())) * 8
+    }
+
+    // Writes the blob into `out`, returning the number of bytes required.
+    // Nothing is written when `out` is too small.
+    pub fn write(&self, out: &mut [u8]) -> usize {
+        let size = self.size();
+        if out.len() < size {
+            return size;
+        }
+        let mut w = Writer { out, pos: 0 };
+        w.bytes(&MAGIC);
+        w.bytes(&VERSION.to_le_bytes());
+        w.bytes(&[0, 0]);
+        w.f64(self.x);
+        w.f64(self.y);
+        w.f64(self.z);
+        w.f64(self.t);
+        w.u64(self.steps);
+        for position in self.rng_positions {
+            w.u64(position);
+        }
+        w.f64(self.u);
+        w.u64(self.cells.len() as u64);
+        for cell in &self.cells {
+            for value in cell {
+                w.f64(*value);
+            }
+        }
+        w.u64(self.param_drift_position);
+        for offset in self.drift_offsets {
+            w.f64(offset);
+        }
+        w.u64(u64::from(self.prep.is_some()));
+        if let Some(prep) = &self.prep {
+            w.u64(prep.rng_position);
+            for value in prep.state {
+                w.f64(value);
+            }
+            w.f64(prep.filtered);
+        }
+        w.f64(self.controller.step_size);
+        w.f64(self.controller.previous_error);
+        w.u64(u64::from(self.controller.after_rejection));
+        size
+    }
+
+    pub fn read(data: &[u8]) -> Option<Self> {
+        let mut r = Reader { data, pos: 0 };
+        if r.take::<4>()? != MAGIC {
+            return None;
+        }
+        let version = r.u16()?;
+        if version == 0 || version > VERSION {
+            return None;
+        }
+        r.u16()?;
+        let mut snapshot = Self {
+            x: r.f64()?,
+            y: r.f64()?,
+            z: r.f64()?,
+            t: r.f64()?,
+            steps: 0,
+            rng_positions: [0; 2],
+            u: 0.0,
+            cells: Vec::new(),
+            param_drift_position: 0,
+            drift_offsets: [0.0; 2],
+            prep: None,
+            controller: ControllerSnapshot::default(),
+        };
+        if version >= 2 {
+            snapshot.steps = r.u64()?;
+        }
+        if version >= 3 {
+            snapshot.rng_positions[0] = r.u64()?;
+        }
+        if version >= 4 {
+            snapshot.rng_positions[1] = r.u64()?;
+        }
+        if version >= 5 {
+            snapshot.u = r.f64()?;
+        }
+        if version >= 6 {
+            let n = usize::try_from(r.u64()?).ok()?;
+            // A corrupt count must not allocate more than the blob can hold.
+            if n > data.len() / 24 {
+                return None;
+            }
+            snapshot.cells = (0..n).map(|_| Some([r.f64()?, r.f64()?, r.f64()?])).collect::<Option<_>>()?;
+        }
+        if version >= 7 {
+            snapshot.param_drift_position = r.u64()?;
+            snapshot.drift_offsets = [r.f64()?, r.f64()?];
+            snapshot.prep = match r.u64()? {
+                0 => None,
+                1 => Some(PrepSnapshot {
+                    rng_position: r.u64()?,
+                    state: [r.f64()?, r.f64()?, r.f64()?],
+                    filtered: r.f64()?,
+                }),
+                _ => return None,
+            };
+            snapshot.controller = ControllerSnapshot {
+                step_size: r.f64()?,
+                previous_error: r.f64()?,
+                after_rejection: r.u64()? != 0,
+            };
+        }
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_blob() {
+        let snapshot = Snapshot {
+            x: -1.2,
+            y: -6.0,
+            z: 3.1,
+            t: 812.5,
+            steps: 8125,
+            rng_positions: [3, 17],
+            u: 0.25,
+            cells: vec![[0.5, -2.0, 3.0], [-1.0, -5.5, 2.9]],
+            param_drift_position: 42,
+            drift_offsets: [0.03, -0.0001],
+            prep: Some(PrepSnapshot {
+                rng_position: 9,
+                state: [-1.1, -6.2, 3.05],
+                filtered: -1.15,
+            }),
+            controller: ControllerSnapshot {
+                step_size: 0.012,
+                previous_error: 0.4,
+                after_rejection: true,
+            },
+        };
+        let mut blob = vec![0; snapshot.size()];
+        assert_eq!(snapshot.write(&mut blob), HEADER_LEN + (16 + 6 + 5) * 8);
+        assert_eq!(Snapshot::read(&blob), Some(snapshot));
+        assert_eq!(Snapshot::read(&blob[..blob.len() - 1]), None);
+    }
+}
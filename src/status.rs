@@ -0,0 +1,28 @@
+// Status codes returned by the *_status entry points, letting hosts tell a
+// genuine 0.0 apart from a failed call.
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok = 0,
+    NullHandle = -1,
+    // Unknown port name or a name that is not valid UTF-8.
+    BadName = -2,
+    // Known output that the current config does not enable.
+    NotConfigured = -3,
+    // The call panicked; the instance may be left mid-update.
+    Internal = -4,
+    // Null or empty data, malformed JSON, or config entries that were
+    // rejected (listed under config_errors in the stats).
+    BadArgument = -5,
+    // destroy has been called on the handle.
+    Closing = -6,
+    // A file could not be written.
+    Io = -7,
+}
+
+// Runs `f`, mapping a panic to Status::Internal instead of unwinding into
+// the host.
+pub fn guard(f: impl FnOnce() -> Status) -> i32 {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(Status::Internal) as i32
+}
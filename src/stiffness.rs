@@ -0,0 +1,113 @@
+// Stiffness heuristic for automatic solver switching. The Jacobian's
+// eigenvalues come from its characteristic cubic in closed form; a step is
+// stiff when h times the largest decaying eigenvalue magnitude nears the
+// explicit method's stability limit. Around burst onset the hyperpolarised
+// trough pushes dx'/dx strongly negative, which is where the switch to
+// backward Euler usually happens. The way back needs the ratio to fall to
+// half the threshold, so the solver does not flap at the boundary.
+
+use crate::math;
+use std::f64::consts::TAU;
+
+// Eigenvalues of a 3x3 matrix as (re, im) pairs, complex pairs adjacent.
+pub fn eigenvalues(m: &[[f64; 3]; 3]) -> [(f64, f64); 3] {
+    // lambda^3 + a lambda^2 + b lambda + c = 0
+    let a = -(m[0][0] + m[1][1] + m[2][2]);
+    let b = m[0][0] * m[1][1] - m[0][1] * m[1][0] + m[0][0] * m[2][2] - m[0][2] * m[2][0] + m[1][1] * m[2][2]
+        - m[1][2] * m[2][1];
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    let c = -det;
+    // Depressed cubic t^3 + p t + q with lambda = t - a / 3.
+    let shift = -a / 3.0;
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = math::powi(q / 2.0, 2) + math::powi(p / 3.0, 3);
+    if discriminant > 0.0 {
+        let root = discriminant.sqrt();
+        let u = math::cbrt(-q / 2.0 + root);
+        let v = math::cbrt(-q / 2.0 - root);
+        let re = -(u + v) / 2.0 + shift;
+        let im = 3f64.sqrt() / 2.0 * (u - v);
+        [(u + v + shift, 0.0), (re, im), (re, -im)]
+    } else if p == 0.0 {
+        [(shift, 0.0); 3]
+    } else {
+        let radius = 2.0 * (-p / 3.0).sqrt();
+        let angle = math::acos(((3.0 * q / (2.0 * p)) * (-3.0 / p).sqrt()).clamp(-1.0, 1.0)) / 3.0;
+        [0.0, 1.0, 2.0].map(|k| (radius * math::cos(angle - TAU * k / 3.0) + shift, 0.0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StiffnessMonitor {
+    pub enabled: bool,
+    // Fraction of the stability limit at which the implicit solver takes over.
+    pub threshold: f64,
+    stiff: bool,
+    ratio: f64,
+    switches: u64,
+    implicit_steps: u64,
+}
+
+impl StiffnessMonitor {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.8,
+            stiff: false,
+            ratio: 0.0,
+            switches: 0,
+            implicit_steps: 0,
+        }
+    }
+
+    pub fn stiff(&self) -> bool {
+        self.enabled && self.stiff
+    }
+
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    pub fn switches(&self) -> u64 {
+        self.switches
+    }
+
+    pub fn implicit_steps(&self) -> u64 {
+        self.implicit_steps
+    }
+
+    // Re-evaluates stiffness for a step of `h` from the state whose Jacobian
+    // is `jacobian`, with `limit` the explicit solver's stability limit.
+    // Returns true when the solver changes.
+    pub fn update(&mut self, jacobian: &[[f64; 3]; 3], h: f64, limit: f64) -> bool {
+        let rate = eigenvalues(jacobian)
+            .iter()
+            .filter(|(re, _)| *re < 0.0)
+            .map(|(re, im)| math::hypot(*re, *im))
+            .fold(0.0, f64::max);
+        self.ratio = h * rate / limit;
+        let stiff = if self.stiff {
+            self.ratio > 0.5 * self.threshold
+        } else {
+            self.ratio > self.threshold
+        };
+        if stiff == self.stiff {
+            return false;
+        }
+        self.stiff = stiff;
+        self.switches += 1;
+        true
+    }
+
+    pub fn record_implicit(&mut self) {
+        self.implicit_steps += 1;
+    }
+
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.stiff = false;
+        self.ratio = 0.0;
+    }
+}
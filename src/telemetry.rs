@@ -0,0 +1,55 @@
+// Structured logging through `tracing` when the `tracing` feature is enabled.
+// Without it the log macros expand to nothing, so instrumentation costs
+// nothing in default builds.
+
+#[cfg(feature = "tracing")]
+macro_rules! log_info {
+    ($($arg:tt)*) => { tracing::info!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_info {
+    ($($arg:tt)*) => {{}};
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { tracing::warn!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {{}};
+}
+
+// Installs a global JSON subscriber writing to `path`, or to stderr when
+// `path` is "stderr". Returns false if the file cannot be opened or a
+// subscriber is already installed.
+#[cfg(feature = "tracing")]
+pub fn install_json_subscriber(path: &str) -> bool {
+    let builder = tracing_subscriber::fmt().json();
+    let result = if path == "stderr" {
+        builder.with_writer(std::io::stderr).try_init()
+    } else {
+        match std::fs::OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => builder.with_writer(std::sync::Mutex::new(file)).try_init(),
+            Err(_) => return false,
+        }
+    };
+    result.is_ok()
+}
+
+#[cfg(not(feature = "tracing"))]
+pub fn install_json_subscriber(_path: &str) -> bool {
+    false
+}
+
+// Honours HINDMARSH_ROSE_LOG once per process, on the first instance created.
+pub fn init_from_env() {
+    static ONCE: std::sync::Once = std::sync::Once::new();
+    ONCE.call_once(|| {
+        if let Ok(path) = std::env::var("HINDMARSH_ROSE_LOG") {
+            install_json_subscriber(&path);
+        }
+    });
+}
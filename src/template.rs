@@ -0,0 +1,297 @@
+// Online comparison of the model's bursts against a target waveform. x is
+// sampled once per tick from a burst onset to the last spike before the next
+// onset; each completed burst is resampled to the template length and scored
+// by RMSE or by dynamic time warping restricted to a Sakoe-Chiba band.
+
+use crate::math;
+use serde_json::Value;
+use std::collections::VecDeque;
+
+// Longest burst recorded, in ticks; samples beyond it are dropped.
+const MAX_BURST_SAMPLES: usize = 1 << 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distance {
+    Rmse,
+    Dtw,
+}
+
+impl Distance {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "rmse" => Some(Self::Rmse),
+            "dtw" => Some(Self::Dtw),
+            _ => None,
+        }
+    }
+}
+
+// Accepts a JSON array or whitespace/comma separated numbers.
+pub fn parse_waveform(text: &str) -> Option<Vec<f64>> {
+    if let Ok(Value::Array(values)) = serde_json::from_str::<Value>(text) {
+        return values.iter().map(Value::as_f64).collect();
+    }
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse().ok())
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateMatcher {
+    template: Vec<f64>,
+    pub distance: Distance,
+    // DTW band half-width as a fraction of the template length.
+    pub band: f64,
+    burst: Vec<f64>,
+    resampled: Vec<f64>,
+    // DTW cost rows, sized with the template so comparisons never allocate.
+    rows: [Vec<(f64, usize)>; 2],
+    recording: bool,
+    spiked: bool,
+    burst_end: usize,
+    last: Option<f64>,
+    comparisons: u64,
+}
+
+impl TemplateMatcher {
+    pub fn new() -> Self {
+        Self {
+            template: Vec::new(),
+            distance: Distance::Rmse,
+            band: 0.1,
+            burst: Vec::new(),
+            resampled: Vec::new(),
+            rows: [Vec::new(), Vec::new()],
+            recording: false,
+            spiked: false,
+            burst_end: 0,
+            last: None,
+            comparisons: 0,
+        }
+    }
+
+    pub fn set_template(&mut self, template: Vec<f64>) {
+        self.template = template;
+        self.resampled = vec![0.0; self.template.len()];
+        self.rows = [
+            vec![(0.0, 0); self.template.len() + 1],
+            vec![(0.0, 0); self.template.len() + 1],
+        ];
+        self.last = None;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.template.len() >= 2
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.last
+    }
+
+    pub fn comparisons(&self) -> u64 {
+        self.comparisons
+    }
+
+    // Called on every detected spike, before the tick's sample.
+    pub fn on_spike(&mut self, onset: bool) {
+        if !self.enabled() {
+            return;
+        }
+        if onset {
+            if self.recording {
+                self.compare();
+            }
+            self.burst.clear();
+            self.burst_end = 0;
+            self.recording = true;
+        }
+        self.spiked = true;
+    }
+
+    // Called once per tick with the current membrane variable.
+    pub fn sample(&mut self, x: f64) {
+        if !self.recording {
+            return;
+        }
+        if self.burst.len() < MAX_BURST_SAMPLES {
+            self.burst.push(x);
+        }
+        if self.spiked {
+            self.burst_end = self.burst.len();
+            self.spiked = false;
+        }
+    }
+
+    fn compare(&mut self) {
+        let burst = &self.burst[..self.burst_end];
+        if burst.len() < 2 {
+            return;
+        }
+        resample(burst, &mut self.resampled);
+        let score = match self.distance {
+            Distance::Rmse => rmse(&self.resampled, &self.template),
+            Distance::Dtw => dtw(&self.resampled, &self.template, self.band, &mut self.rows),
+        };
+        self.last = Some(score);
+        self.comparisons += 1;
+    }
+}
+
+// The last `capacity` complete bursts, sampled like TemplateMatcher (once
+// per tick from onset to the last spike before the next onset), kept for
+// export as stimulation templates.
+#[derive(Debug, Clone)]
+pub struct BurstBank {
+    capacity: usize,
+    bursts: VecDeque<Vec<f64>>,
+    current: Vec<f64>,
+    recording: bool,
+    spiked: bool,
+    burst_end: usize,
+}
+
+impl BurstBank {
+    pub fn new() -> Self {
+        Self {
+            capacity: 0,
+            bursts: VecDeque::new(),
+            current: Vec::new(),
+            recording: false,
+            spiked: false,
+            burst_end: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.bursts.len()
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.bursts.len() > capacity {
+            self.bursts.pop_front();
+        }
+        if capacity == 0 {
+            self.recording = false;
+        }
+    }
+
+    pub fn on_spike(&mut self, onset: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        if onset {
+            if self.recording && self.burst_end >= 2 {
+                if self.bursts.len() == self.capacity {
+                    self.bursts.pop_front();
+                }
+                let mut burst = std::mem::take(&mut self.current);
+                burst.truncate(self.burst_end);
+                self.bursts.push_back(burst);
+            }
+            self.current.clear();
+            self.burst_end = 0;
+            self.recording = true;
+        }
+        self.spiked = true;
+    }
+
+    pub fn sample(&mut self, x: f64) {
+        if !self.recording {
+            return;
+        }
+        if self.current.len() < MAX_BURST_SAMPLES {
+            self.current.push(x);
+        }
+        if self.spiked {
+            self.burst_end = self.current.len();
+            self.spiked = false;
+        }
+    }
+
+    // The newest `count` bursts, oldest first, onset-aligned and resampled
+    // to `length` points (default: their mean length). With `average` they
+    // are collapsed into their pointwise mean.
+    pub fn extract(&self, count: usize, length: Option<usize>, average: bool) -> Vec<Vec<f64>> {
+        let count = count.min(self.bursts.len());
+        if count == 0 {
+            return Vec::new();
+        }
+        let selected = self.bursts.range(self.bursts.len() - count..);
+        let mean_length = selected.clone().map(Vec::len).sum::<usize>() / count;
+        let length = length.unwrap_or(mean_length).max(2);
+        let waveforms: Vec<Vec<f64>> = selected
+            .map(|burst| {
+                let mut out = vec![0.0; length];
+                resample(burst, &mut out);
+                out
+            })
+            .collect();
+        if !average {
+            return waveforms;
+        }
+        let mut mean = vec![0.0; length];
+        for waveform in &waveforms {
+            for (sum, value) in mean.iter_mut().zip(waveform) {
+                *sum += value / count as f64;
+            }
+        }
+        vec![mean]
+    }
+}
+
+// A single waveform is written as a flat JSON array, which
+// burst_template_file reads back directly; several as an array of arrays.
+pub fn template_json(waveforms: &[Vec<f64>]) -> Value {
+    match waveforms {
+        [single] => serde_json::json!(single),
+        _ => serde_json::json!(waveforms),
+    }
+}
+
+// Linear interpolation of `source` onto `target.len()` evenly spaced points.
+fn resample(source: &[f64], target: &mut [f64]) {
+    let scale = (source.len() - 1) as f64 / (target.len() - 1) as f64;
+    for (i, out) in target.iter_mut().enumerate() {
+        let pos = i as f64 * scale;
+        let lo = (pos.floor() as usize).min(source.len() - 2);
+        let frac = pos - lo as f64;
+        *out = source[lo] + (source[lo + 1] - source[lo]) * frac;
+    }
+}
+
+fn rmse(a: &[f64], b: &[f64]) -> f64 {
+    let sum: f64 = a.iter().zip(b).map(|(p, q)| (p - q) * (p - q)).sum();
+    (sum / a.len() as f64).sqrt()
+}
+
+// Root-mean-square cost along the optimal warping path of two equal-length
+// sequences, searched within |i - j| <= band * len.
+fn dtw(a: &[f64], b: &[f64], band: f64, rows: &mut [Vec<(f64, usize)>; 2]) -> f64 {
+    let n = a.len();
+    let width = ((band * n as f64).ceil() as usize).max(1);
+    let [previous, current] = rows;
+    previous.fill((f64::INFINITY, 0));
+    previous[0] = (0.0, 0);
+    for i in 1..=n {
+        current.fill((f64::INFINITY, 0));
+        let lo = i.saturating_sub(width).max(1);
+        let hi = (i + width).min(n);
+        for j in lo..=hi {
+            let cost = math::powi(a[i - 1] - b[j - 1], 2);
+            let best = [previous[j - 1], previous[j], current[j - 1]]
+                .into_iter()
+                .min_by(|p, q| p.0.total_cmp(&q.0))
+                .unwrap_or((f64::INFINITY, 0));
+            current[j] = (best.0 + cost, best.1 + 1);
+        }
+        std::mem::swap(previous, current);
+    }
+    let (total, length) = previous[n];
+    (total / length.max(1) as f64).sqrt()
+}
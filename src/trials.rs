@@ -0,0 +1,106 @@
+// Autonomous trial structure: a list of epochs, each holding its duration in
+// seconds, the config overrides to apply on entry and a marker code exposed
+// as an output for aligning the model with the recording.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct Epoch {
+    pub duration: f64,
+    pub config: Value,
+    pub marker: f64,
+}
+
+impl Epoch {
+    fn parse(value: &Value) -> Option<Self> {
+        let duration = value.get("duration")?.as_f64()?;
+        Some(Self {
+            duration: duration.max(0.0),
+            config: value.get("config").cloned().unwrap_or(Value::Null),
+            marker: value.get("marker").and_then(|v| v.as_f64()).unwrap_or(0.0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrialEngine {
+    epochs: Vec<Epoch>,
+    pub looped: bool,
+    index: usize,
+    elapsed: f64,
+    entered: bool,
+    finished: bool,
+    repetitions: u64,
+}
+
+impl TrialEngine {
+    // Entries without a numeric duration are skipped.
+    pub fn load(&mut self, epochs: &[Value]) {
+        self.epochs = epochs.iter().filter_map(Epoch::parse).collect();
+        self.restart();
+    }
+
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.elapsed = 0.0;
+        self.entered = false;
+        self.finished = false;
+        self.repetitions = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epochs.is_empty()
+    }
+
+    pub fn running(&self) -> bool {
+        !self.epochs.is_empty() && !self.finished
+    }
+
+    // Index of the current epoch, or -1 once the sequence has finished.
+    pub fn epoch(&self) -> f64 {
+        if self.running() {
+            self.index as f64
+        } else {
+            -1.0
+        }
+    }
+
+    pub fn marker(&self) -> f64 {
+        if self.running() {
+            self.epochs[self.index].marker
+        } else {
+            0.0
+        }
+    }
+
+    pub fn repetitions(&self) -> u64 {
+        self.repetitions
+    }
+
+    // Called at the start of every process tick of `period` seconds. Returns
+    // the overrides of the epoch being entered, if any.
+    pub fn tick(&mut self, period: f64) -> Option<&Value> {
+        if !self.running() {
+            return None;
+        }
+        if !self.entered {
+            self.entered = true;
+            return Some(&self.epochs[self.index].config);
+        }
+        self.elapsed += period;
+        if self.elapsed < self.epochs[self.index].duration {
+            return None;
+        }
+        self.elapsed -= self.epochs[self.index].duration;
+        self.index += 1;
+        if self.index == self.epochs.len() {
+            self.repetitions += 1;
+            if !self.looped {
+                self.finished = true;
+                return None;
+            }
+            self.index = 0;
+        }
+        Some(&self.epochs[self.index].config)
+    }
+}
@@ -0,0 +1,116 @@
+// Dither-based extremum seeking: the tuned parameter is modulated by a slow
+// sinusoid, the high-passed metric is demodulated against the same sinusoid
+// to estimate the local gradient, and the parameter estimate is integrated
+// along (or against) that gradient.
+
+use crate::math;
+use std::f64::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunedParam {
+    E,
+    Mu,
+    S,
+    Vh,
+}
+
+impl TunedParam {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(Self::E),
+            "mu" => Some(Self::Mu),
+            "s" => Some(Self::S),
+            "vh" => Some(Self::Vh),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::E => "e",
+            Self::Mu => "mu",
+            Self::S => "s",
+            Self::Vh => "vh",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    BurstFrequency,
+    DutyCycle,
+    AbsFreqError,
+    AbsDutyError,
+    ShadowDivergence,
+    TemplateDistance,
+}
+
+impl Metric {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "burst_frequency" => Some(Self::BurstFrequency),
+            "duty_cycle" => Some(Self::DutyCycle),
+            "abs_freq_error" => Some(Self::AbsFreqError),
+            "abs_duty_error" => Some(Self::AbsDutyError),
+            "shadow_divergence" => Some(Self::ShadowDivergence),
+            "template_distance" => Some(Self::TemplateDistance),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtremumSeeker {
+    pub param: TunedParam,
+    pub metric: Metric,
+    pub maximize: bool,
+    pub amplitude: f64,
+    pub frequency: f64,
+    pub gain: f64,
+    pub hp_tau: f64,
+    pub min: f64,
+    pub max: f64,
+    estimate: f64,
+    phase: f64,
+    mean: Option<f64>,
+}
+
+impl ExtremumSeeker {
+    pub fn new(param: TunedParam, metric: Metric, start: f64) -> Self {
+        Self {
+            param,
+            metric,
+            maximize: true,
+            amplitude: 0.05,
+            frequency: 0.01,
+            gain: 0.1,
+            hp_tau: 100.0,
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY,
+            estimate: start,
+            phase: 0.0,
+            mean: None,
+        }
+    }
+
+    pub fn estimate(&self) -> f64 {
+        self.estimate
+    }
+
+    // Advances the controller by `period` seconds given the current metric
+    // value and returns the parameter value to apply.
+    pub fn update(&mut self, metric: f64, period: f64) -> f64 {
+        let mean = self.mean.get_or_insert(metric);
+        if self.hp_tau > 0.0 {
+            *mean += (metric - *mean) * (period / self.hp_tau).min(1.0);
+        }
+        let high_passed = metric - *mean;
+
+        let direction = if self.maximize { 1.0 } else { -1.0 };
+        let gradient = high_passed * math::sin(self.phase);
+        self.estimate = (self.estimate + direction * self.gain * gradient * period).clamp(self.min, self.max);
+
+        self.phase = (self.phase + TAU * self.frequency * period) % TAU;
+        (self.estimate + self.amplitude * math::sin(self.phase)).clamp(self.min, self.max)
+    }
+}
@@ -0,0 +1,72 @@
+// Quiescence watchdog: if no spike has been seen for `timeout` seconds of host
+// time, either inject a depolarising current of `kick_current` for
+// `kick_duration` seconds or, with `reset` set, request a return to the
+// configured initial conditions.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intervention {
+    Kick,
+    Reset,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuiescenceWatchdog {
+    pub timeout: f64,
+    pub kick_current: f64,
+    pub kick_duration: f64,
+    pub reset: bool,
+    silent_for: f64,
+    kick_remaining: f64,
+    interventions: u64,
+}
+
+impl QuiescenceWatchdog {
+    pub fn new() -> Self {
+        Self {
+            timeout: 0.0,
+            kick_current: 1.0,
+            kick_duration: 0.05,
+            reset: false,
+            silent_for: 0.0,
+            kick_remaining: 0.0,
+            interventions: 0,
+        }
+    }
+
+    pub fn on_spike(&mut self) {
+        self.silent_for = 0.0;
+    }
+
+    // Current to add to the drive during this tick.
+    pub fn kick(&self) -> f64 {
+        if self.kick_remaining > 0.0 {
+            self.kick_current
+        } else {
+            0.0
+        }
+    }
+
+    pub fn interventions(&self) -> u64 {
+        self.interventions
+    }
+
+    pub fn tick(&mut self, period: f64) -> Option<Intervention> {
+        self.kick_remaining = (self.kick_remaining - period).max(0.0);
+        if self.timeout <= 0.0 {
+            self.silent_for = 0.0;
+            return None;
+        }
+        self.silent_for += period;
+        if self.silent_for < self.timeout {
+            return None;
+        }
+        self.silent_for = 0.0;
+        self.interventions += 1;
+        if self.reset {
+            Some(Intervention::Reset)
+        } else {
+            self.kick_remaining = self.kick_duration;
+            Some(Intervention::Kick)
+        }
+    }
+}